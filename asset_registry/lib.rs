@@ -0,0 +1,145 @@
+//! # asset_registry
+//!
+//! A registry of subsa asset symbols.
+//!
+//! The contract address of an asset is its `AssetId`, which is not human-readable. Once an
+//! asset was created with an `asset_symbol`, its manager calls `register_symbol` with this
+//! registry, and the asset registers itself as the contract of its symbol. Each symbol can
+//! only be registered once, and each asset only registers one symbol.
+//!
+//! ## Docs
+//!
+//! Check readme for more information.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::asset_registry::{AssetRegistry, AssetRegistryRef};
+
+/// The asset_registry smart contract
+#[ink::contract]
+mod asset_registry {
+    use ink::codegen::EmitEvent;
+    use ink::storage::Mapping;
+
+    use ssa::Error;
+
+    /// Defines the storage of the registry.
+    #[ink(storage)]
+    #[derive(Default)]
+    pub struct AssetRegistry {
+        // Contract registered for each symbol, and symbol registered by each contract ↓
+        assets: Mapping<[u8; 12], AccountId>,
+        symbols: Mapping<AccountId, [u8; 12]>,
+    }
+
+    /// Event emitted when an asset registers its symbol.
+    #[ink(event)]
+    pub struct AssetRegistered {
+        #[ink(topic)]
+        symbol: [u8; 12],
+        #[ink(topic)]
+        asset: AccountId,
+    }
+
+    impl AssetRegistry {
+        // Creates a new, empty registry.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Returns the asset contract registered for `symbol`, if any.
+        #[ink(message)]
+        pub fn asset_of(&self, symbol: [u8; 12]) -> Option<AccountId> {
+            self.assets.get(symbol)
+        }
+
+        /// Returns the symbol registered by the asset contract `asset`, if any.
+        #[ink(message)]
+        pub fn symbol_of(&self, asset: AccountId) -> Option<[u8; 12]> {
+            self.symbols.get(asset)
+        }
+    }
+
+    impl ssa::SymbolRegistry for AssetRegistry {
+        #[ink(message)]
+        fn register_asset(&mut self, symbol: [u8; 12]) -> Result<(), Error> {
+            let asset = self.env().caller();
+
+            // check if the symbol is not empty
+            if symbol == [0; 12] {
+                return Err(Error::InvalidSymbol);
+            }
+
+            // check if neither the symbol nor the asset is registered yet
+            if self.assets.contains(symbol) {
+                return Err(Error::SymbolTaken);
+            }
+            if self.symbols.contains(asset) {
+                return Err(Error::AssetAlreadyRegistered);
+            }
+
+            self.assets.insert(symbol, &asset);
+            self.symbols.insert(asset, &symbol);
+
+            // emit asset registered event
+            // Note: named in full, as the subsa dependency implements EmitEvent for the same
+            // environment
+            EmitEvent::<AssetRegistry>::emit_event(self.env(), AssetRegistered { symbol, asset });
+
+            Ok(())
+        }
+    }
+
+    // Unit tests
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ssa::SymbolRegistry;
+
+        fn account(byte: u8) -> AccountId {
+            AccountId::from([byte; 32])
+        }
+
+        fn set_caller(caller: AccountId) {
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(caller);
+        }
+
+        fn symbol(name: &[u8]) -> [u8; 12] {
+            let mut symbol = [0; 12];
+            symbol[..name.len()].copy_from_slice(name);
+            symbol
+        }
+
+        // Test if an asset can register its symbol once, and be looked up both ways
+        #[ink::test]
+        fn register_asset_works() {
+            let asset = account(0x9);
+            let mut registry = AssetRegistry::new();
+            assert_eq!(registry.asset_of(symbol(b"TSSA")), None);
+
+            set_caller(asset);
+            assert_eq!(registry.register_asset([0; 12]), Err(Error::InvalidSymbol));
+            assert_eq!(registry.register_asset(symbol(b"TSSA")), Ok(()));
+            assert_eq!(registry.asset_of(symbol(b"TSSA")), Some(asset));
+            assert_eq!(registry.symbol_of(asset), Some(symbol(b"TSSA")));
+            assert_eq!(
+                registry.register_asset(symbol(b"OTHER")),
+                Err(Error::AssetAlreadyRegistered)
+            );
+
+            // another asset cannot take the symbol
+            set_caller(account(0x8));
+            assert_eq!(
+                registry.register_asset(symbol(b"TSSA")),
+                Err(Error::SymbolTaken)
+            );
+            assert_eq!(registry.register_asset(symbol(b"OTHER")), Ok(()));
+            assert_eq!(registry.asset_of(symbol(b"OTHER")), Some(account(0x8)));
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! # compliance_hook
+//!
+//! A sample transfer hook for subsa assets.
+//!
+//! Once the manager of an asset sets it with `set_transfer_hook`, the asset asks the hook
+//! before every balance movement and notifies it afterwards. This hook rejects movements from
+//! or to accounts its owner blocked, and movements above a per-movement limit, while letting
+//! revocations through so that the clawback address can still pull tokens from blocked
+//! accounts. It also keeps the volume and number of movements the asset reported.
+//!
+//! ## Docs
+//!
+//! Check readme for more information.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::compliance_hook::{ComplianceHook, ComplianceHookRef, Error, BLOCKED, OVER_LIMIT};
+
+/// The compliance_hook smart contract
+#[ink::contract]
+mod compliance_hook {
+    use ink::storage::Mapping;
+
+    use scale::{Decode, Encode};
+    use ssa::TransferOperation;
+
+    /// Rejection code of a movement from or to a blocked account.
+    pub const BLOCKED: u32 = 1;
+
+    /// Rejection code of a movement above the limit.
+    pub const OVER_LIMIT: u32 = 2;
+
+    /// Defines the storage of the hook.
+    #[ink(storage)]
+    pub struct ComplianceHook {
+        owner: AccountId,
+        // The asset whose notifications are accounted ↓
+        asset: AccountId,
+        // Largest amount of a single movement, 0 when unlimited ↓
+        max_amount: Balance,
+        blocked: Mapping<AccountId, ()>,
+        // Volume and number of the movements notified by the asset ↓
+        volume: Balance,
+        movements: u64,
+    }
+
+    // Errors
+
+    #[derive(Debug, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        NotOwner,
+    }
+
+    impl ComplianceHook {
+        // Creates a new hook for `asset`, owned by the caller.
+        #[ink(constructor)]
+        pub fn new(asset: AccountId, max_amount: Balance) -> Self {
+            Self {
+                owner: Self::env().caller(),
+                asset,
+                max_amount,
+                blocked: Mapping::default(),
+                volume: 0,
+                movements: 0,
+            }
+        }
+
+        /// Block or unblock `account`
+        // Note: only the owner can block accounts
+        #[ink(message)]
+        pub fn set_blocked(&mut self, account: AccountId, blocked: bool) -> Result<(), Error> {
+            // check if caller is the owner
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+
+            if blocked {
+                self.blocked.insert(account, &());
+            } else {
+                self.blocked.remove(account);
+            }
+            Ok(())
+        }
+
+        /// Set the largest amount of a single movement, 0 for no limit
+        // Note: only the owner can set the limit
+        #[ink(message)]
+        pub fn set_max_amount(&mut self, max_amount: Balance) -> Result<(), Error> {
+            // check if caller is the owner
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+
+            self.max_amount = max_amount;
+            Ok(())
+        }
+
+        /// Returns whether `account` is blocked.
+        #[ink(message)]
+        pub fn is_blocked(&self, account: AccountId) -> bool {
+            self.blocked.contains(account)
+        }
+
+        /// Returns the volume and number of the movements notified by the asset.
+        #[ink(message)]
+        pub fn stats(&self) -> (Balance, u64) {
+            (self.volume, self.movements)
+        }
+    }
+
+    impl ssa::TransferHook for ComplianceHook {
+        #[ink(message)]
+        fn before_transfer(
+            &self,
+            operation: TransferOperation,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), u32> {
+            // let revocations through, e.g. from blocked accounts
+            if operation == TransferOperation::Revoke {
+                return Ok(());
+            }
+
+            if self.is_blocked(from) || self.is_blocked(to) {
+                return Err(BLOCKED);
+            }
+            if self.max_amount > 0 && amount > self.max_amount {
+                return Err(OVER_LIMIT);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn after_transfer(
+            &mut self,
+            _operation: TransferOperation,
+            _from: AccountId,
+            _to: AccountId,
+            amount: Balance,
+        ) {
+            // only account for notifications of the asset
+            if self.env().caller() != self.asset {
+                return;
+            }
+
+            self.volume = self.volume.saturating_add(amount);
+            self.movements = self.movements.saturating_add(1);
+        }
+    }
+
+    // Unit tests
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ssa::TransferHook;
+
+        fn account(byte: u8) -> AccountId {
+            AccountId::from([byte; 32])
+        }
+
+        fn set_caller(caller: AccountId) {
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(caller);
+        }
+
+        // Test if before_transfer rejects blocked accounts and large amounts, but not revokes
+        #[ink::test]
+        fn before_transfer_works() {
+            let owner = account(0x1);
+            let holder = account(0x2);
+            set_caller(owner);
+            let mut hook = ComplianceHook::new(account(0x9), 100);
+            let transfer = TransferOperation::Transfer;
+            assert_eq!(hook.before_transfer(transfer, owner, holder, 100), Ok(()));
+            assert_eq!(
+                hook.before_transfer(transfer, owner, holder, 101),
+                Err(OVER_LIMIT)
+            );
+
+            assert_eq!(hook.set_blocked(holder, true), Ok(()));
+            assert_eq!(
+                hook.before_transfer(transfer, owner, holder, 1),
+                Err(BLOCKED)
+            );
+            assert_eq!(
+                hook.before_transfer(transfer, holder, owner, 1),
+                Err(BLOCKED)
+            );
+            let revoke = TransferOperation::Revoke;
+            assert_eq!(hook.before_transfer(revoke, holder, owner, 1_000), Ok(()));
+
+            assert_eq!(hook.set_max_amount(0), Ok(()));
+            assert_eq!(hook.set_blocked(holder, false), Ok(()));
+            assert_eq!(hook.before_transfer(transfer, owner, holder, 1_000), Ok(()));
+
+            set_caller(holder);
+            assert_eq!(hook.set_blocked(holder, true), Err(Error::NotOwner));
+            assert_eq!(hook.set_max_amount(1), Err(Error::NotOwner));
+        }
+
+        // Test if after_transfer only accounts for notifications of the asset
+        #[ink::test]
+        fn after_transfer_works() {
+            let asset = account(0x9);
+            set_caller(account(0x1));
+            let mut hook = ComplianceHook::new(asset, 0);
+            let operation = TransferOperation::Distribute;
+            hook.after_transfer(operation, account(0x1), account(0x2), 50);
+            assert_eq!(hook.stats(), (0, 0));
+
+            set_caller(asset);
+            hook.after_transfer(operation, account(0x1), account(0x2), 50);
+            hook.after_transfer(operation, account(0x1), account(0x3), 25);
+            assert_eq!(hook.stats(), (75, 2));
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Deploys an asset from arbitrary constructor arguments.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ssa_fuzz::{account, check_invariants, run, AssetParams};
+
+fuzz_target!(|params: AssetParams| {
+    run(|| {
+        let Ok(asset) = params.deploy() else {
+            return;
+        };
+
+        assert_eq!(asset.asset_name(), params.asset_name);
+        assert_eq!(asset.unit_name(), params.unit_name);
+        assert_eq!(asset.total(), params.total);
+        assert_eq!(asset.decimals(), params.decimals);
+        assert_eq!(asset.default_frozen(), params.default_frozen);
+        assert_eq!(asset.url(), params.url);
+        assert_eq!(asset.metadata_hash(), params.metadata_hash);
+
+        // the whole supply starts in the reserve, which defaults to the creator
+        let reserve = account(params.reserve.unwrap_or(params.creator));
+        assert_eq!(asset.reserve_id(), reserve);
+        assert_eq!(asset.balance_of(reserve), Ok(params.total));
+
+        check_invariants(&asset);
+    });
+});
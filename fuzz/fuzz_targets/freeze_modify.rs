@@ -0,0 +1,90 @@
+//! Interleaves freezes, reconfigurations and status changes with every other message.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ssa_fuzz::{check_invariants, run, AssetParams, Call};
+
+fuzz_target!(|input: (AssetParams, Vec<Call>)| {
+    let (params, calls) = input;
+    run(|| {
+        let Ok(mut asset) = params.deploy() else {
+            return;
+        };
+        for call in &calls {
+            let (manager, reserve, freeze_id, clawback_id) = (
+                asset.manager_id(),
+                asset.reserve_id(),
+                asset.freeze_id(),
+                asset.clawback_id(),
+            );
+            let distributor = asset.is_distributor(call.caller());
+            let pending_manager = asset.pending_manager();
+            let verifier = asset.verifier();
+            if call.apply(&mut asset).is_ok() {
+                match call {
+                    Call::CancelOptIn { .. }
+                    | Call::PurgeStaleAccount { .. }
+                    | Call::SetInactivityThreshold { .. }
+                    | Call::ModifyAsset { .. }
+                    | Call::PatchAsset { .. }
+                    | Call::AdminTransfer { .. }
+                    | Call::SetRole { .. }
+                    | Call::ProposeManager { .. }
+                    | Call::CancelManagerProposal { .. }
+                    | Call::SetSpendLimit { .. }
+                    | Call::SetClawbackCap { .. }
+                    | Call::SetSecondaryFreeze { .. }
+                    | Call::SetRoyaltySplits { .. }
+                    | Call::SetVotingFormula { .. }
+                    | Call::SetOptInFee { .. }
+                    | Call::SetSubscriptionParams { .. }
+                    | Call::SetCooldown { .. }
+                    | Call::SetLargeTransferThreshold { .. }
+                    | Call::ApproveLargeTransfer { .. }
+                    | Call::SetTransferFee { .. }
+                    | Call::SetFeeExempt { .. }
+                    | Call::ApplyComplianceDiff { .. }
+                    | Call::SetJurisdiction { .. }
+                    | Call::SetTaxRate { .. }
+                    | Call::PublishReserveProof { .. }
+                    | Call::AddTimeLock { .. }
+                    | Call::Activate { .. }
+                    | Call::Suspend { .. } => {
+                        assert_eq!(call.caller(), manager)
+                    }
+                    Call::AcceptManager { .. } => {
+                        assert_eq!(Some(call.caller()), pending_manager)
+                    }
+                    Call::Freeze { .. }
+                    | Call::FreezeUntil { .. }
+                    | Call::PartialFreeze { .. }
+                    | Call::UnpartialFreeze { .. }
+                    | Call::CreateLien { .. }
+                    | Call::ReleaseLien { .. }
+                    | Call::SetFreezeExempt { .. }
+                    | Call::ResolveAppeal { .. } => {
+                        assert_eq!(call.caller(), freeze_id)
+                    }
+                    Call::Wipe { .. } | Call::RevokeMany { .. } | Call::EnforceLien { .. } => {
+                        assert_eq!(call.caller(), clawback_id)
+                    }
+                    Call::SetVerified { .. } | Call::SetVerificationUri { .. } => {
+                        assert_eq!(Some(call.caller()), verifier)
+                    }
+                    Call::OptInAndFund { .. }
+                    | Call::CreateStream { .. }
+                    | Call::SetDistributor { .. }
+                    | Call::FundRole { .. } => {
+                        assert_eq!(call.caller(), reserve)
+                    }
+                    Call::DistributeFromReserve { .. } => {
+                        assert!(call.caller() == reserve || distributor)
+                    }
+                    _ => {}
+                }
+            }
+            check_invariants(&asset);
+        }
+    });
+});
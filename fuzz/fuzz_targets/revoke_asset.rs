@@ -0,0 +1,40 @@
+//! Distributes the supply from the reserve, then drives arbitrary revocations.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use ssa_fuzz::{account, check_invariants, run, AssetParams, Balance, Call};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    params: AssetParams,
+    /// Holders to opt in and fund from the reserve before revoking.
+    holdings: Vec<(u8, Balance)>,
+    /// Revocations as (caller, receiver, target, amount).
+    revocations: Vec<(u8, u8, u8, Balance)>,
+}
+
+fuzz_target!(|input: Input| {
+    run(|| {
+        let Ok(mut asset) = input.params.deploy() else {
+            return;
+        };
+        let reserve = asset.reserve_id();
+        for &(holder, amount) in &input.holdings {
+            let _ = asset.do_opt_in(account(holder), 0);
+            let _ = asset.do_transfer(reserve, account(holder), amount);
+            check_invariants(&asset);
+        }
+        for &(caller, receiver, target, amount) in &input.revocations {
+            let _ = Call::RevokeAsset {
+                caller,
+                receiver,
+                target,
+                amount,
+            }
+            .apply(&mut asset);
+            check_invariants(&asset);
+        }
+    });
+});
@@ -0,0 +1,53 @@
+//! Drives arbitrary sequences of opt-ins, opt-outs and transfers.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use ssa_fuzz::{check_invariants, run, AssetParams, Balance, Call};
+
+#[derive(Arbitrary, Debug)]
+enum HolderCall {
+    OptIn {
+        caller: u8,
+    },
+    OptOut {
+        caller: u8,
+    },
+    Transfer {
+        caller: u8,
+        receiver: u8,
+        amount: Balance,
+    },
+}
+
+impl From<&HolderCall> for Call {
+    fn from(call: &HolderCall) -> Self {
+        match *call {
+            HolderCall::OptIn { caller } => Call::OptIn { caller, paid: 0 },
+            HolderCall::OptOut { caller } => Call::OptOut { caller },
+            HolderCall::Transfer {
+                caller,
+                receiver,
+                amount,
+            } => Call::Transfer {
+                caller,
+                receiver,
+                amount,
+            },
+        }
+    }
+}
+
+fuzz_target!(|input: (AssetParams, Vec<HolderCall>)| {
+    let (params, calls) = input;
+    run(|| {
+        let Ok(mut asset) = params.deploy() else {
+            return;
+        };
+        for call in &calls {
+            let _ = Call::from(call).apply(&mut asset);
+            check_invariants(&asset);
+        }
+    });
+});
@@ -0,0 +1,926 @@
+//! # Subsa fuzzing harness
+//!
+//! Shared pieces of the cargo-fuzz targets in `fuzz_targets/`.
+//!
+//! Every target deploys a subsa asset from fuzzer-chosen parameters, drives it through the
+//! `do_*` entry points (bypassing the ink! dispatch) and asserts that the contract never panics
+//! and that the storage invariants in [`check_invariants`] hold after every call.
+
+use arbitrary::Arbitrary;
+use ink::env::DefaultEnvironment;
+use ink::primitives::AccountId;
+use ssa::{
+    transfer_commitment, ComplianceDiff, Error, ModifyPatch, Role, RoyaltySplit, Subsa,
+    VotingFormula,
+};
+
+pub type Balance = u128;
+
+/// Number of distinct accounts the fuzzer can address.
+/// Note: keeping this small makes collisions between roles, senders and receivers likely.
+pub const ACCOUNTS: u8 = 6;
+
+/// Maps a fuzzer-chosen index onto one of the `ACCOUNTS` accounts.
+pub fn account(index: u8) -> AccountId {
+    AccountId::from([index % ACCOUNTS + 1; 32])
+}
+
+/// Sets the caller of the next call.
+/// Note: also moves the contract off its default address, `account(0)`, which cannot hold a
+/// role or receive.
+pub fn set_caller(caller: AccountId) {
+    ink::env::test::set_caller::<DefaultEnvironment>(caller);
+    ink::env::test::set_callee::<DefaultEnvironment>(AccountId::from([0xff; 32]));
+}
+
+/// Runs `f` against a freshly reset off-chain environment.
+pub fn run(f: impl FnOnce()) {
+    ink::env::test::run_test::<DefaultEnvironment, _>(|_| {
+        f();
+        Ok(())
+    })
+    .expect("off-chain environment failed");
+}
+
+/// Maps a fuzzer-chosen index onto one of the roles.
+pub fn role(index: u8) -> Role {
+    match index % 4 {
+        0 => Role::Manager,
+        1 => Role::Reserve,
+        2 => Role::Freeze,
+        _ => Role::Clawback,
+    }
+}
+
+/// Returns the voting formula of `index`, wrapping around.
+pub fn voting_formula(index: u8) -> VotingFormula {
+    match index % 3 {
+        0 => VotingFormula::Linear,
+        1 => VotingFormula::Quadratic,
+        _ => VotingFormula::SquareRoot,
+    }
+}
+
+/// Constructor arguments of an asset.
+#[derive(Arbitrary, Debug)]
+pub struct AssetParams {
+    pub creator: u8,
+    pub asset_name: String,
+    pub unit_name: String,
+    pub total: Balance,
+    pub decimals: u32,
+    pub default_frozen: bool,
+    pub url: String,
+    pub metadata_hash: [u8; 4],
+    pub manager: Option<u8>,
+    pub reserve: Option<u8>,
+    pub freeze: Option<u8>,
+    pub clawback: Option<u8>,
+}
+
+impl AssetParams {
+    /// Deploys the asset, with the constructor called by `creator`.
+    pub fn deploy(&self) -> Result<Subsa, Error> {
+        set_caller(account(self.creator));
+        Subsa::new(
+            self.asset_name.clone(),
+            self.unit_name.clone(),
+            self.total,
+            self.decimals,
+            self.default_frozen,
+            self.url.clone(),
+            self.metadata_hash,
+            self.manager.map(account),
+            self.reserve.map(account),
+            self.freeze.map(account),
+            self.clawback.map(account),
+        )
+    }
+}
+
+/// A state-changing call of the subsa message surface.
+#[derive(Arbitrary, Debug)]
+pub enum Call {
+    OptIn {
+        caller: u8,
+        paid: u16,
+    },
+    OptOut {
+        caller: u8,
+    },
+    CancelOptIn {
+        caller: u8,
+        account: u8,
+    },
+    PurgeStaleAccount {
+        caller: u8,
+        account: u8,
+    },
+    SetInactivityThreshold {
+        caller: u8,
+        threshold: u64,
+    },
+    Transfer {
+        caller: u8,
+        receiver: u8,
+        amount: Balance,
+    },
+    Freeze {
+        caller: u8,
+        account: u8,
+        freeze: bool,
+        expiry: Option<u32>,
+    },
+    FreezeUntil {
+        caller: u8,
+        account: u8,
+        expires_at: u64,
+    },
+    ReleaseExpiredFreeze {
+        caller: u8,
+        account: u8,
+    },
+    PartialFreeze {
+        caller: u8,
+        account: u8,
+        amount: Balance,
+    },
+    UnpartialFreeze {
+        caller: u8,
+        account: u8,
+        amount: Balance,
+    },
+    CreateLien {
+        caller: u8,
+        borrower: u8,
+        lender: u8,
+        amount: Balance,
+    },
+    ReleaseLien {
+        caller: u8,
+        borrower: u8,
+        lender: u8,
+        amount: Balance,
+    },
+    EnforceLien {
+        caller: u8,
+        borrower: u8,
+        lender: u8,
+    },
+    SubmitFreezeAppeal {
+        caller: u8,
+        reason_hash: [u8; 32],
+    },
+    ResolveAppeal {
+        caller: u8,
+        account: u8,
+        grant: bool,
+    },
+    ModifyAsset {
+        caller: u8,
+        manager: Option<u8>,
+        reserve: Option<u8>,
+        freeze: Option<u8>,
+        clawback: Option<u8>,
+    },
+    PatchAsset {
+        caller: u8,
+        manager: Option<Option<u8>>,
+        reserve: Option<Option<u8>>,
+        freeze: Option<Option<u8>>,
+        clawback: Option<Option<u8>>,
+    },
+    RevokeAsset {
+        caller: u8,
+        receiver: u8,
+        target: u8,
+        amount: Balance,
+    },
+    RevokeMany {
+        caller: u8,
+        ops: Vec<(u8, Option<Balance>, u8)>,
+    },
+    AdminTransfer {
+        caller: u8,
+        from: u8,
+        to: u8,
+        amount: Balance,
+    },
+    ProposeManager {
+        caller: u8,
+        proposed: u8,
+    },
+    AcceptManager {
+        caller: u8,
+    },
+    CancelManagerProposal {
+        caller: u8,
+    },
+    SetRole {
+        caller: u8,
+        role: u8,
+        /// `None` clears the role.
+        account: Option<u8>,
+    },
+    OptInAndFund {
+        caller: u8,
+        account: u8,
+        amount: Balance,
+    },
+    SetDistributor {
+        caller: u8,
+        distributor: u8,
+        allowed: bool,
+    },
+    FundRole {
+        caller: u8,
+        role: u8,
+        amount: Balance,
+    },
+    DistributeFromReserve {
+        caller: u8,
+        recipients: Vec<(u8, Balance)>,
+    },
+    ElectDrip {
+        caller: u8,
+        enabled: bool,
+    },
+    RestrictTransfers {
+        caller: u8,
+        restricted: bool,
+    },
+    SetAllowedDestination {
+        caller: u8,
+        destination: u8,
+        allowed: bool,
+    },
+    SetSpendLimit {
+        caller: u8,
+        limit: Balance,
+        period: u64,
+    },
+    SetClawbackCap {
+        caller: u8,
+        cap: Balance,
+        period_blocks: u32,
+    },
+    SetSecondaryFreeze {
+        caller: u8,
+        frozen: bool,
+    },
+    SetRoyaltySplits {
+        caller: u8,
+        splits: Vec<(u8, u16)>,
+    },
+    SetVotingFormula {
+        caller: u8,
+        formula: u8,
+    },
+    SetOptInFee {
+        caller: u8,
+        fee: u16,
+    },
+    SetSubscriptionParams {
+        caller: u8,
+        fee: u16,
+        period_blocks: u32,
+    },
+    SetCooldown {
+        caller: u8,
+        blocks: u8,
+    },
+    PaySubscription {
+        caller: u8,
+        paid: u16,
+    },
+    SetTransferFee {
+        caller: u8,
+        fee_bps: u16,
+    },
+    SetFeeExempt {
+        caller: u8,
+        account: u8,
+        exempt: bool,
+    },
+    SetFreezeExempt {
+        caller: u8,
+        account: u8,
+        exempt: bool,
+    },
+    ApplyComplianceDiff {
+        caller: u8,
+        add_whitelist: Vec<u8>,
+        remove_whitelist: Vec<u8>,
+        add_blacklist: Vec<u8>,
+        remove_blacklist: Vec<u8>,
+    },
+    SetJurisdiction {
+        caller: u8,
+        account: u8,
+        code: [u8; 2],
+    },
+    PublishReserveProof {
+        caller: u8,
+        root: [u8; 32],
+    },
+    SetTaxRate {
+        caller: u8,
+        from_code: [u8; 2],
+        to_code: [u8; 2],
+        rate_bps: u16,
+    },
+    AddTimeLock {
+        caller: u8,
+        account: u8,
+        amount: Balance,
+        expires_at: u32,
+    },
+    RemoveExpiredLocks {
+        caller: u8,
+        account: u8,
+    },
+    Activate {
+        caller: u8,
+    },
+    Suspend {
+        caller: u8,
+    },
+    Wipe {
+        caller: u8,
+        target: u8,
+    },
+    SendPending {
+        caller: u8,
+        receiver: u8,
+        amount: Balance,
+        expiry: u64,
+    },
+    ClaimPending {
+        caller: u8,
+        id: u64,
+    },
+    CancelPending {
+        caller: u8,
+        id: u64,
+    },
+    SetVerified {
+        caller: u8,
+        verified: bool,
+    },
+    SetVerificationUri {
+        caller: u8,
+        uri: Vec<u8>,
+    },
+    CreateStream {
+        caller: u8,
+        recipient: u8,
+        deposit: Balance,
+        start: u64,
+        stop: u64,
+    },
+    WithdrawFromStream {
+        caller: u8,
+        id: u64,
+        amount: Balance,
+    },
+    CancelStream {
+        caller: u8,
+        id: u64,
+    },
+    ScheduleTransfer {
+        caller: u8,
+        receiver: u8,
+        amount: Balance,
+        execute_after: u64,
+    },
+    ExecuteScheduled {
+        caller: u8,
+        id: u64,
+    },
+    CancelScheduled {
+        caller: u8,
+        id: u64,
+    },
+    SetLargeTransferThreshold {
+        caller: u8,
+        threshold: Balance,
+    },
+    InitiateLargeTransfer {
+        caller: u8,
+        receiver: u8,
+        amount: Balance,
+    },
+    ApproveLargeTransfer {
+        caller: u8,
+        id: u64,
+    },
+    CancelLargeTransfer {
+        caller: u8,
+        id: u64,
+    },
+    CommitTransfer {
+        caller: u8,
+        receiver: u8,
+        amount: Balance,
+        salt: u8,
+        expiry_block: u32,
+        max_amount: Balance,
+    },
+    RevealTransfer {
+        caller: u8,
+        id: u64,
+        amount: Balance,
+        salt: u8,
+    },
+    CancelCommittedTransfer {
+        caller: u8,
+        id: u64,
+    },
+}
+
+impl Call {
+    /// Returns the account the call is made from.
+    pub fn caller(&self) -> AccountId {
+        match *self {
+            Call::OptIn { caller, .. }
+            | Call::OptOut { caller }
+            | Call::CancelOptIn { caller, .. }
+            | Call::PurgeStaleAccount { caller, .. }
+            | Call::SetInactivityThreshold { caller, .. }
+            | Call::Transfer { caller, .. }
+            | Call::Freeze { caller, .. }
+            | Call::FreezeUntil { caller, .. }
+            | Call::ReleaseExpiredFreeze { caller, .. }
+            | Call::PartialFreeze { caller, .. }
+            | Call::UnpartialFreeze { caller, .. }
+            | Call::CreateLien { caller, .. }
+            | Call::ReleaseLien { caller, .. }
+            | Call::EnforceLien { caller, .. }
+            | Call::SubmitFreezeAppeal { caller, .. }
+            | Call::ResolveAppeal { caller, .. }
+            | Call::ModifyAsset { caller, .. }
+            | Call::PatchAsset { caller, .. }
+            | Call::RevokeAsset { caller, .. }
+            | Call::RevokeMany { caller, .. }
+            | Call::AdminTransfer { caller, .. }
+            | Call::ProposeManager { caller, .. }
+            | Call::AcceptManager { caller }
+            | Call::CancelManagerProposal { caller }
+            | Call::SetRole { caller, .. }
+            | Call::OptInAndFund { caller, .. }
+            | Call::SetDistributor { caller, .. }
+            | Call::FundRole { caller, .. }
+            | Call::DistributeFromReserve { caller, .. }
+            | Call::ElectDrip { caller, .. }
+            | Call::RestrictTransfers { caller, .. }
+            | Call::SetAllowedDestination { caller, .. }
+            | Call::SetSpendLimit { caller, .. }
+            | Call::SetClawbackCap { caller, .. }
+            | Call::SetSecondaryFreeze { caller, .. }
+            | Call::SetRoyaltySplits { caller, .. }
+            | Call::SetVotingFormula { caller, .. }
+            | Call::SetOptInFee { caller, .. }
+            | Call::SetSubscriptionParams { caller, .. }
+            | Call::SetCooldown { caller, .. }
+            | Call::PaySubscription { caller, .. }
+            | Call::SetTransferFee { caller, .. }
+            | Call::SetFeeExempt { caller, .. }
+            | Call::SetFreezeExempt { caller, .. }
+            | Call::ApplyComplianceDiff { caller, .. }
+            | Call::SetJurisdiction { caller, .. }
+            | Call::SetTaxRate { caller, .. }
+            | Call::PublishReserveProof { caller, .. }
+            | Call::AddTimeLock { caller, .. }
+            | Call::RemoveExpiredLocks { caller, .. }
+            | Call::Activate { caller }
+            | Call::Suspend { caller }
+            | Call::Wipe { caller, .. }
+            | Call::SendPending { caller, .. }
+            | Call::ClaimPending { caller, .. }
+            | Call::CancelPending { caller, .. }
+            | Call::SetVerified { caller, .. }
+            | Call::SetVerificationUri { caller, .. }
+            | Call::CreateStream { caller, .. }
+            | Call::WithdrawFromStream { caller, .. }
+            | Call::CancelStream { caller, .. }
+            | Call::ScheduleTransfer { caller, .. }
+            | Call::ExecuteScheduled { caller, .. }
+            | Call::CancelScheduled { caller, .. }
+            | Call::SetLargeTransferThreshold { caller, .. }
+            | Call::InitiateLargeTransfer { caller, .. }
+            | Call::ApproveLargeTransfer { caller, .. }
+            | Call::CancelLargeTransfer { caller, .. }
+            | Call::CommitTransfer { caller, .. }
+            | Call::RevealTransfer { caller, .. }
+            | Call::CancelCommittedTransfer { caller, .. } => account(caller),
+        }
+    }
+
+    /// Applies the call to `asset`.
+    pub fn apply(&self, asset: &mut Subsa) -> Result<(), Error> {
+        match *self {
+            Call::OptIn { caller, paid } => asset.do_opt_in(account(caller), Balance::from(paid)),
+            Call::OptOut { caller } => asset.do_opt_out(account(caller)),
+            Call::CancelOptIn {
+                caller,
+                account: target,
+            } => asset.do_cancel_opt_in(account(caller), account(target)),
+            Call::PurgeStaleAccount {
+                caller,
+                account: target,
+            } => asset.do_purge_stale_account(account(caller), account(target)),
+            Call::SetInactivityThreshold { caller, threshold } => {
+                asset.do_set_inactivity_threshold(account(caller), threshold)
+            }
+            Call::Transfer {
+                caller,
+                receiver,
+                amount,
+            } => asset.do_transfer(account(caller), account(receiver), amount),
+            Call::Freeze {
+                caller,
+                account: target,
+                freeze,
+                expiry,
+            } => asset.do_freeze(account(caller), account(target), freeze, expiry),
+            Call::FreezeUntil {
+                caller,
+                account: target,
+                expires_at,
+            } => asset.do_freeze_until(account(caller), account(target), expires_at),
+            Call::ReleaseExpiredFreeze {
+                account: target, ..
+            } => asset.do_release_expired_freeze(account(target)),
+            Call::PartialFreeze {
+                caller,
+                account: target,
+                amount,
+            } => asset.do_partial_freeze(account(caller), account(target), amount),
+            Call::UnpartialFreeze {
+                caller,
+                account: target,
+                amount,
+            } => asset.do_unpartial_freeze(account(caller), account(target), amount),
+            Call::CreateLien {
+                caller,
+                borrower,
+                lender,
+                amount,
+            } => asset.do_create_lien(account(caller), account(borrower), account(lender), amount),
+            Call::ReleaseLien {
+                caller,
+                borrower,
+                lender,
+                amount,
+            } => asset.do_release_lien(account(caller), account(borrower), account(lender), amount),
+            Call::EnforceLien {
+                caller,
+                borrower,
+                lender,
+            } => asset.do_enforce_lien(account(caller), account(borrower), account(lender)),
+            Call::SubmitFreezeAppeal {
+                caller,
+                reason_hash,
+            } => asset.do_submit_freeze_appeal(account(caller), reason_hash),
+            Call::ResolveAppeal {
+                caller,
+                account: target,
+                grant,
+            } => asset.do_resolve_appeal(account(caller), account(target), grant),
+            Call::ModifyAsset {
+                caller,
+                manager,
+                reserve,
+                freeze,
+                clawback,
+            } => asset.do_modify_asset(
+                account(caller),
+                manager.map(account),
+                reserve.map(account),
+                freeze.map(account),
+                clawback.map(account),
+            ),
+            Call::PatchAsset {
+                caller,
+                manager,
+                reserve,
+                freeze,
+                clawback,
+            } => {
+                let patch = |role: Option<Option<u8>>| role.map(|new| new.map(account));
+                asset.do_patch_asset(
+                    account(caller),
+                    ModifyPatch {
+                        manager: patch(manager),
+                        reserve: patch(reserve),
+                        freeze: patch(freeze),
+                        clawback: patch(clawback),
+                    },
+                )
+            }
+            Call::RevokeAsset {
+                caller,
+                receiver,
+                target,
+                amount,
+            } => asset.do_revoke_asset(account(caller), account(receiver), account(target), amount),
+            Call::RevokeMany { caller, ref ops } => asset.do_revoke_many(
+                account(caller),
+                ops.iter()
+                    .map(|&(target, amount, receiver)| (account(target), amount, account(receiver)))
+                    .collect(),
+            ),
+            Call::AdminTransfer {
+                caller,
+                from,
+                to,
+                amount,
+            } => asset.do_admin_transfer(account(caller), account(from), account(to), amount),
+            Call::ProposeManager { caller, proposed } => {
+                asset.do_propose_manager(account(caller), account(proposed))
+            }
+            Call::AcceptManager { caller } => asset.do_accept_manager(account(caller)),
+            Call::CancelManagerProposal { caller } => {
+                asset.do_cancel_manager_proposal(account(caller))
+            }
+            Call::SetRole {
+                caller,
+                role,
+                account: new,
+            } => {
+                let new = new.map_or(AccountId::from([0x0; 32]), account);
+                asset.do_set_role(account(caller), self::role(role), new)
+            }
+            Call::OptInAndFund {
+                caller,
+                account: target,
+                amount,
+            } => asset.do_opt_in_and_fund(account(caller), account(target), amount),
+            Call::SetDistributor {
+                caller,
+                distributor,
+                allowed,
+            } => asset.do_set_distributor(account(caller), account(distributor), allowed),
+            Call::FundRole {
+                caller,
+                role,
+                amount,
+            } => asset.do_fund_role(account(caller), self::role(role), amount),
+            Call::DistributeFromReserve {
+                caller,
+                ref recipients,
+            } => asset.do_distribute_from_reserve(
+                account(caller),
+                recipients
+                    .iter()
+                    .map(|&(recipient, amount)| (account(recipient), amount))
+                    .collect(),
+            ),
+            Call::ElectDrip { caller, enabled } => asset.do_elect_drip(account(caller), enabled),
+            Call::RestrictTransfers { caller, restricted } => {
+                asset.do_restrict_transfers(account(caller), restricted)
+            }
+            Call::SetAllowedDestination {
+                caller,
+                destination,
+                allowed,
+            } => asset.do_set_allowed_destination(account(caller), account(destination), allowed),
+            Call::SetSpendLimit {
+                caller,
+                limit,
+                period,
+            } => asset.do_set_spend_limit(account(caller), limit, period),
+            Call::SetClawbackCap {
+                caller,
+                cap,
+                period_blocks,
+            } => asset.do_set_clawback_cap(account(caller), cap, period_blocks),
+            Call::SetSecondaryFreeze { caller, frozen } => {
+                asset.do_set_secondary_freeze(account(caller), frozen)
+            }
+            Call::SetRoyaltySplits { caller, ref splits } => asset.do_set_royalty_splits(
+                account(caller),
+                splits
+                    .iter()
+                    .map(|&(recipient, share_bps)| RoyaltySplit {
+                        recipient: account(recipient),
+                        share_bps: u32::from(share_bps),
+                    })
+                    .collect(),
+            ),
+            Call::SetVotingFormula { caller, formula } => {
+                asset.do_set_voting_formula(account(caller), voting_formula(formula))
+            }
+            Call::SetOptInFee { caller, fee } => {
+                asset.do_set_opt_in_fee(account(caller), Balance::from(fee))
+            }
+            Call::SetSubscriptionParams {
+                caller,
+                fee,
+                period_blocks,
+            } => {
+                asset.do_set_subscription_params(account(caller), Balance::from(fee), period_blocks)
+            }
+            Call::SetCooldown { caller, blocks } => {
+                asset.do_set_cooldown(account(caller), u32::from(blocks))
+            }
+            Call::PaySubscription { caller, paid } => {
+                asset.do_pay_subscription(account(caller), Balance::from(paid))
+            }
+            Call::SetTransferFee { caller, fee_bps } => {
+                asset.do_set_transfer_fee(account(caller), fee_bps)
+            }
+            Call::SetFeeExempt {
+                caller,
+                account: target,
+                exempt,
+            } => asset.do_set_fee_exempt(account(caller), account(target), exempt),
+            Call::SetFreezeExempt {
+                caller,
+                account: target,
+                exempt,
+            } => asset.do_set_freeze_exempt(account(caller), account(target), exempt),
+            Call::ApplyComplianceDiff {
+                caller,
+                ref add_whitelist,
+                ref remove_whitelist,
+                ref add_blacklist,
+                ref remove_blacklist,
+            } => {
+                let accounts = |indices: &Vec<u8>| indices.iter().map(|&i| account(i)).collect();
+                asset
+                    .do_apply_compliance_diff(
+                        account(caller),
+                        ComplianceDiff {
+                            add_whitelist: accounts(add_whitelist),
+                            remove_whitelist: accounts(remove_whitelist),
+                            add_blacklist: accounts(add_blacklist),
+                            remove_blacklist: accounts(remove_blacklist),
+                        },
+                    )
+                    .map(|_| ())
+            }
+            Call::SetJurisdiction {
+                caller,
+                account: target,
+                code,
+            } => asset.do_set_jurisdiction(account(caller), account(target), code),
+            Call::PublishReserveProof { caller, root } => {
+                asset.do_publish_reserve_proof(account(caller), root)
+            }
+            Call::SetTaxRate {
+                caller,
+                from_code,
+                to_code,
+                rate_bps,
+            } => asset.do_set_tax_rate(account(caller), from_code, to_code, u32::from(rate_bps)),
+            Call::AddTimeLock {
+                caller,
+                account: target,
+                amount,
+                expires_at,
+            } => asset
+                .do_add_time_lock(account(caller), account(target), amount, expires_at)
+                .map(|_| ()),
+            Call::RemoveExpiredLocks {
+                account: target, ..
+            } => asset.do_remove_expired_locks(account(target)).map(|_| ()),
+            Call::Activate { caller } => asset.do_activate(account(caller)),
+            Call::Suspend { caller } => asset.do_suspend(account(caller)),
+            Call::Wipe { caller, target } => asset.do_wipe(account(caller), account(target)),
+            Call::SendPending {
+                caller,
+                receiver,
+                amount,
+                expiry,
+            } => asset
+                .do_send_pending(account(caller), account(receiver), amount, expiry)
+                .map(|_| ()),
+            // Note: ids are small enough to hit existing pending transfers
+            Call::ClaimPending { caller, id } => asset.do_claim_pending(account(caller), id % 8),
+            Call::CancelPending { caller, id } => asset.do_cancel_pending(account(caller), id % 8),
+            Call::SetVerified { caller, verified } => {
+                asset.do_set_verified(account(caller), verified)
+            }
+            Call::SetVerificationUri { caller, ref uri } => {
+                asset.do_set_verification_uri(account(caller), uri.clone())
+            }
+            Call::CreateStream {
+                caller,
+                recipient,
+                deposit,
+                start,
+                stop,
+            } => asset
+                .do_create_stream(account(caller), account(recipient), deposit, start, stop)
+                .map(|_| ()),
+            Call::WithdrawFromStream { caller, id, amount } => {
+                asset.do_withdraw_from_stream(account(caller), id, amount)
+            }
+            Call::CancelStream { caller, id } => asset.do_cancel_stream(account(caller), id),
+            Call::ScheduleTransfer {
+                caller,
+                receiver,
+                amount,
+                execute_after,
+            } => asset
+                .do_schedule_transfer(account(caller), account(receiver), amount, execute_after)
+                .map(|_| ()),
+            Call::ExecuteScheduled { caller, id } => {
+                asset.do_execute_scheduled(account(caller), id)
+            }
+            Call::CancelScheduled { caller, id } => asset.do_cancel_scheduled(account(caller), id),
+            Call::SetLargeTransferThreshold { caller, threshold } => {
+                asset.do_set_large_transfer_threshold(account(caller), threshold)
+            }
+            Call::InitiateLargeTransfer {
+                caller,
+                receiver,
+                amount,
+            } => asset
+                .do_initiate_large_transfer(account(caller), account(receiver), amount)
+                .map(|_| ()),
+            Call::ApproveLargeTransfer { caller, id } => {
+                asset.do_approve_large_transfer(account(caller), id)
+            }
+            Call::CancelLargeTransfer { caller, id } => {
+                asset.do_cancel_large_transfer(account(caller), id)
+            }
+            Call::CommitTransfer {
+                caller,
+                receiver,
+                amount,
+                salt,
+                expiry_block,
+                max_amount,
+            } => asset
+                .do_commit_transfer(
+                    account(caller),
+                    transfer_commitment(amount, [salt; 32]),
+                    account(receiver),
+                    expiry_block,
+                    max_amount,
+                )
+                .map(|_| ()),
+            // Note: ids are small enough to hit existing committed transfers
+            Call::RevealTransfer {
+                caller,
+                id,
+                amount,
+                salt,
+            } => asset.do_reveal_transfer(account(caller), id % 8, amount, [salt; 32]),
+            Call::CancelCommittedTransfer { caller, id } => {
+                asset.do_cancel_committed_transfer(account(caller), id % 8)
+            }
+        }
+    }
+}
+
+/// Asserts the storage invariants of `asset`:
+/// - the balances of all accounts and the escrowed transfers add up to the current supply
+/// - `balance_of` succeeds exactly for the opted-in accounts, and reports their stored balance
+/// - `opted_in_count` is the number of opted-in accounts
+pub fn check_invariants(asset: &Subsa) {
+    let held = (0..ACCOUNTS)
+        .map(|index| asset.stored_balance(account(index)))
+        .try_fold(
+            asset.pending_supply()
+                + asset.committed_supply()
+                + asset.scheduled_supply()
+                + asset.large_transfer_supply()
+                + asset.streamed_supply(),
+            |sum, balance| sum.checked_add(balance),
+        )
+        .expect("balances overflow the total supply");
+    assert_eq!(held, asset.current_supply(), "supply is not conserved");
+
+    let mut opted_in_count = 0;
+    for index in 0..ACCOUNTS {
+        let holder = account(index);
+        let opted_in = asset.is_opted_in(holder).expect("is_opted_in never fails");
+        opted_in_count += u64::from(opted_in);
+        match asset.balance_of(holder) {
+            Ok(balance) => {
+                assert!(opted_in, "balance_of succeeded for a non-opted-in account");
+                assert_eq!(balance, asset.stored_balance(holder));
+            }
+            Err(Error::NotOptedIn) => assert!(!opted_in),
+            Err(error) => panic!("unexpected balance_of error: {error:?}"),
+        }
+    }
+    assert_eq!(asset.opted_in_count(), opted_in_count);
+
+    let exported = asset.export_holders(0, u64::from(ACCOUNTS));
+    assert_eq!(
+        exported.len() as u64,
+        opted_in_count,
+        "holder index is out of sync"
+    );
+    for (holder, balance, _) in exported {
+        assert_eq!(
+            asset.balance_of(holder),
+            Ok(balance),
+            "holder index lists {holder:?}"
+        );
+    }
+}
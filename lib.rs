@@ -16,15 +16,250 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "std")]
+pub use self::subsa::{
+    compute_fingerprint, decimals_factor, export_full_state, format_amount, from_base_units,
+    reserve_proof_leaf, reserve_proof_parent, to_base_units, transfer_commitment, FullState,
+};
+pub use self::subsa::{
+    AccountStats, AdminAction, AdminActionKind, AssetConfig, AssetId, AssetParams, AssetStatus,
+    BuildInfo, BundleTransfer, CommittedTransfer, ComplianceDiff, Error, FreezeAppeal, FreezeEvent,
+    HolderExport, HolderState, HoldingStatus, LargeTransfer, LockEntry, ModifyPatch,
+    NativeAccounting, PendingTransfer, Role, RoyaltySplit, ScheduledTransfer, Stream, Subsa,
+    SubsaCall, SubsaRef, SupplyEvent, SupplyOperation, SymbolRegistry, TransferHook,
+    TransferOperation, VotingFormula, KYC_APPROVED, KYC_PENDING, KYC_REJECTED, KYC_UNKNOWN,
+};
+
+/// Environment of runtimes with 20-byte account ids and 64-bit balances.
+/// Note: the contract is built against it with the `custom-env` feature, instead of
+/// `DefaultEnvironment`
+#[cfg(feature = "custom-env")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum CustomEnvironment {}
+
+#[cfg(feature = "custom-env")]
+impl ink::env::Environment for CustomEnvironment {
+    const MAX_EVENT_TOPICS: usize =
+        <ink::env::DefaultEnvironment as ink::env::Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = [u8; 20];
+    type Balance = u64;
+    type Hash = <ink::env::DefaultEnvironment as ink::env::Environment>::Hash;
+    type Timestamp = <ink::env::DefaultEnvironment as ink::env::Environment>::Timestamp;
+    type BlockNumber = <ink::env::DefaultEnvironment as ink::env::Environment>::BlockNumber;
+    type ChainExtension = <ink::env::DefaultEnvironment as ink::env::Environment>::ChainExtension;
+}
+
+#[cfg(all(feature = "custom-env", feature = "runtime-mirror"))]
+compile_error!("the `custom-env` and `runtime-mirror` features are mutually exclusive");
+
+/// Chain extension exposing pallet-assets to the contract.
+/// Note: with the `runtime-mirror` feature, the asset registers a mirror asset through it and
+/// replays every balance change on it, so that runtime tooling sees the same balances
+#[cfg(feature = "runtime-mirror")]
+#[ink::chain_extension]
+pub trait AssetsExtension {
+    type ErrorCode = MirrorError;
+
+    /// Registers a new asset, returning its pallet-assets id.
+    #[ink(extension = 0x5300_0001)]
+    fn register_asset(
+        name: ink::prelude::vec::Vec<u8>,
+        symbol: ink::prelude::vec::Vec<u8>,
+        decimals: u32,
+    ) -> u32;
+
+    /// Mints `amount` of the asset `asset` to `to`.
+    #[ink(extension = 0x5300_0002)]
+    fn mint(asset: u32, to: ink::primitives::AccountId, amount: u128);
+
+    /// Burns `amount` of the asset `asset` from `from`.
+    #[ink(extension = 0x5300_0003)]
+    fn burn(asset: u32, from: ink::primitives::AccountId, amount: u128);
+
+    /// Transfers `amount` of the asset `asset` from `from` to `to`.
+    #[ink(extension = 0x5300_0004)]
+    fn transfer(
+        asset: u32,
+        from: ink::primitives::AccountId,
+        to: ink::primitives::AccountId,
+        amount: u128,
+    );
+}
+
+/// Error of an `AssetsExtension` call, from any non-zero status code.
+#[cfg(feature = "runtime-mirror")]
+#[derive(scale::Encode, scale::Decode, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct MirrorError(pub u32);
+
+#[cfg(feature = "runtime-mirror")]
+impl ink::env::chain_extension::FromStatusCode for MirrorError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            code => Err(Self(code)),
+        }
+    }
+}
+
+/// Environment of runtimes exposing pallet-assets through `AssetsExtension`.
+/// Note: the contract is built against it with the `runtime-mirror` feature, instead of
+/// `DefaultEnvironment`
+#[cfg(feature = "runtime-mirror")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum MirrorEnvironment {}
+
+#[cfg(feature = "runtime-mirror")]
+impl ink::env::Environment for MirrorEnvironment {
+    const MAX_EVENT_TOPICS: usize =
+        <ink::env::DefaultEnvironment as ink::env::Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <ink::env::DefaultEnvironment as ink::env::Environment>::AccountId;
+    type Balance = <ink::env::DefaultEnvironment as ink::env::Environment>::Balance;
+    type Hash = <ink::env::DefaultEnvironment as ink::env::Environment>::Hash;
+    type Timestamp = <ink::env::DefaultEnvironment as ink::env::Environment>::Timestamp;
+    type BlockNumber = <ink::env::DefaultEnvironment as ink::env::Environment>::BlockNumber;
+    type ChainExtension = AssetsExtension;
+}
+
 /// The subsa smart contract
-#[ink::contract]
+#[cfg_attr(
+    not(any(feature = "custom-env", feature = "runtime-mirror")),
+    ink::contract
+)]
+#[cfg_attr(feature = "custom-env", ink::contract(env = crate::CustomEnvironment))]
+#[cfg_attr(
+    feature = "runtime-mirror",
+    ink::contract(env = crate::MirrorEnvironment)
+)]
 mod subsa {
-    use ink::storage::Mapping;
+    use ink::codegen::{EmitEvent, Env};
+    use ink::env::call::{build_call, build_create, ExecutionInput, Selector};
+    use ink::env::hash::Blake2x256;
+    use ink::env::CallFlags;
+    use ink::prelude::{format, string::String, vec::Vec};
+    use ink::storage::{Lazy, Mapping};
 
     use scale::{Decode, Encode};
 
     pub type AssetId = AccountId;
 
+    /// Checks whether `url` is a valid asset URL: with `strict`, it must be empty or have one of
+    /// the allowed schemes.
+    fn check_url(url: &str, strict: bool) -> Result<(), Error> {
+        if strict
+            && !url.is_empty()
+            && !scheme_of(url).is_some_and(|scheme| ALLOWED_URL_SCHEMES.contains(&scheme))
+        {
+            return Err(Error::InvalidUrl);
+        }
+        Ok(())
+    }
+
+    /// Checks whether the manager, freeze and clawback addresses in `roles` are held by distinct
+    /// accounts, cleared roles aside, when separation is `enforced`.
+    fn check_role_separation(roles: [AccountId; 3], enforced: bool) -> Result<(), Error> {
+        let [manager, freeze, clawback] = roles;
+        let shared = |a: AccountId, b: AccountId| a == b && a != zero_account();
+        if enforced
+            && (shared(manager, freeze) || shared(manager, clawback) || shared(freeze, clawback))
+        {
+            return Err(Error::RoleSeparationViolated);
+        }
+        Ok(())
+    }
+
+    /// Returns whether `code` is a well-formed ISO 3166-1 alpha-2 code, i.e. two uppercase
+    /// letters.
+    fn is_jurisdiction_code(code: [u8; 2]) -> bool {
+        code.iter().all(u8::is_ascii_uppercase)
+    }
+
+    /// Returns the zero address, used for unset and cleared roles.
+    // Note: decoded from zeroed bytes rather than built from a `[u8; 32]`, so that its size
+    // follows the account id type of the environment (up to 32 bytes)
+    fn zero_account() -> AccountId {
+        AccountId::decode(&mut &[0x0; 32][..]).expect("account ids are at most 32 bytes")
+    }
+
+    /// Returns 10^`decimals`, the number of base units in a whole unit.
+    /// Note: fails with InvalidDecimals if 10^decimals does not fit in a Balance.
+    pub fn decimals_factor(decimals: u32) -> Result<Balance, Error> {
+        Balance::checked_pow(10, decimals).ok_or(Error::InvalidDecimals)
+    }
+
+    /// Returns `whole` units plus `fractional` base units in base units, with `decimals`.
+    /// Note: fails with InvalidFractionalUnits if `fractional` is a whole unit or more, and with
+    /// UnitConversionOverflow if the result does not fit in a Balance.
+    // Note: the conversion is only useless when `Balance` is `u128`
+    #[allow(clippy::useless_conversion)]
+    pub fn to_base_units(decimals: u32, whole: u128, fractional: u128) -> Result<Balance, Error> {
+        let factor = u128::from(decimals_factor(decimals)?);
+        if fractional >= factor {
+            return Err(Error::InvalidFractionalUnits);
+        }
+
+        whole
+            .checked_mul(factor)
+            .and_then(|units| units.checked_add(fractional))
+            .and_then(|units| Balance::try_from(units).ok())
+            .ok_or(Error::UnitConversionOverflow)
+    }
+
+    /// Splits `amount` base units into whole units and fractional base units, with `decimals`.
+    /// Note: when 10^decimals does not fit in a Balance, every amount is a fraction of a unit.
+    // Note: the conversion is only useless when `Balance` is `u128`
+    #[allow(clippy::useless_conversion)]
+    pub fn from_base_units(decimals: u32, amount: Balance) -> (u128, u128) {
+        match decimals_factor(decimals) {
+            Ok(factor) => (u128::from(amount / factor), u128::from(amount % factor)),
+            Err(_) => (0, u128::from(amount)),
+        }
+    }
+
+    /// Formats `amount` base units for display, with `decimals` and the unit name, e.g.
+    /// "12.340000 TSSA" for 12_340_000 base units with 6 decimals.
+    /// Note: trailing zeros are never trimmed, so the fraction always has `decimals` digits and
+    /// shows the precision of the asset; with 0 decimals, there is no decimal point.
+    pub fn format_amount(amount: Balance, decimals: u32, unit_name: &str) -> String {
+        let (whole, fractional) = from_base_units(decimals, amount);
+        if decimals == 0 {
+            return format!("{whole} {unit_name}");
+        }
+
+        let width = decimals as usize;
+        format!("{whole}.{fractional:0width$} {unit_name}")
+    }
+
+    /// Returns the commitment to a transfer of `amount` for `commit_transfer`, i.e. the
+    /// Blake2x256 hash of the SCALE-encoded (`amount`, `salt`).
+    pub fn transfer_commitment(amount: Balance, salt: [u8; 32]) -> [u8; 32] {
+        let mut commitment = [0x0; 32];
+        ink::env::hash_encoded::<Blake2x256, _>(&(amount, salt), &mut commitment);
+        commitment
+    }
+
+    /// Returns the leaf of (`account`, `amount`) in a reserve proof tree, i.e. the Blake2x256
+    /// hash of the SCALE-encoded (`account`, `amount`).
+    pub fn reserve_proof_leaf(account: AccountId, amount: Balance) -> [u8; 32] {
+        let mut leaf = [0x0; 32];
+        ink::env::hash_encoded::<Blake2x256, _>(&(account, amount), &mut leaf);
+        leaf
+    }
+
+    /// Returns the parent of two nodes in a reserve proof tree, i.e. the Blake2x256 hash of the
+    /// smaller node followed by the larger one.
+    /// Note: sorting the pair lets proofs leave out on which side each sibling is.
+    pub fn reserve_proof_parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let pair = if a <= b { (a, b) } else { (b, a) };
+        let mut parent = [0x0; 32];
+        ink::env::hash_encoded::<Blake2x256, _>(&pair, &mut parent);
+        parent
+    }
+
     /// Defines the storage of your contract.
     #[ink(storage)]
     pub struct Subsa {
@@ -37,17 +272,885 @@ mod subsa {
         default_frozen: bool,
         url: String,
         metadata_hash: [u8; 4],
+        fingerprint: [u8; 32],
+        min_opt_in_balance: Balance,
+        wipe_requires_freeze: bool,
+        strict_url: bool,
+        // Whether the manager can change the url, the one exception to the immutable params ↓
+        mutable_url: bool,
+        verifier: Option<AccountId>,
+        asset_symbol: [u8; 12],
+        // Time after which the clawback address can no longer revoke, as if cleared ↓
+        clawback_sunset: Option<Timestamp>,
+        source_commitment: Option<[u8; 32]>,
+        enforce_role_separation: bool,
         // Mutable asset params ↓
         manager_id: AccountId,
         reserve_id: AccountId,
         freeze_id: AccountId,
         clawback_id: AccountId,
-        balances: Mapping<AccountId, Balance>,
-        accounts_opted_in: Mapping<AccountId, bool>,
-        frozen_holders: Mapping<AccountId, bool>,
+        // pallet-assets id of the mirror asset, with the `runtime-mirror` feature ↓
+        mirror_asset_id: Option<u32>,
+        // Manager proposed by the current one, until it accepts ↓
+        pending_manager: Option<AccountId>,
+        // Attestation of the asset by the verifier, which the manager cannot change ↓
+        verified: bool,
+        verification_uri: Vec<u8>,
+        // Contract called around every balance movement, set by the manager ↓
+        transfer_hook: Option<AccountId>,
+        // Set while a message runs with a transfer hook, written through to block reentry ↓
+        entered: Lazy<bool>,
+        // Incremented on every change of the mutable asset params
+        asset_version: u32,
+        asset_status: AssetStatus,
+        // Cap on what an account can transfer out per period, 0 when disabled ↓
+        spend_limit: Balance,
+        spend_period: Timestamp,
+        // Whether only the reserve can transfer, e.g. during a lock-up, set by the manager ↓
+        secondary_transfers_frozen: bool,
+        // How `voting_power` weighs balances, set by the manager ↓
+        voting_formula: VotingFormula,
+        // Number of assets deployed by `clone_asset`, used to salt their addresses ↓
+        clone_count: u32,
+        // Merkle root of the reserve asset balances backing the asset, published by the manager,
+        // and the block it was published at ↓
+        reserve_proof: Option<[u8; 32]>,
+        reserve_proof_block: BlockNumber,
+        // Time without activity after which the manager can purge an empty account, 0 when
+        // disabled ↓
+        inactivity_threshold: Timestamp,
+        // Fee on transfers in basis points, paid to the reserve, 0 when disabled ↓
+        transfer_fee_bps: u16,
+        // Share of transfers burnt in basis points, fixed at creation, and the units burnt so
+        // far ↓
+        burn_on_transfer_bps: u16,
+        burned: Balance,
+        // PSP22 token the transfer fee is paid in instead of this asset, if any ↓
+        fee_token: Option<AccountId>,
+        fee_exempt: Mapping<AccountId, bool>,
+        // Compliance lists set by the manager: blacklisted accounts neither send nor receive,
+        // and while the whitelist has entries, only whitelisted accounts receive ↓
+        whitelist: Mapping<AccountId, ()>,
+        whitelist_count: u32,
+        blacklist: Mapping<AccountId, ()>,
+        // ISO 3166-1 alpha-2 country code of each account, set by the manager ↓
+        jurisdiction_codes: Mapping<AccountId, [u8; 2]>,
+        // Tax on transfers in basis points, by sender and receiver jurisdiction, paid to the
+        // reserve ↓
+        tax_rates: Mapping<JurisdictionPair, u32>,
+        // Recipients of a share of every transfer, set by the manager ↓
+        royalty_splits: Vec<RoyaltySplit>,
+        // Native value an account pays to opt in, kept by the contract; 0 when disabled ↓
+        opt_in_fee: Balance,
+        // Native value of the opt-in and subscription fees kept by the contract, until the
+        // manager withdraws it ↓
+        opt_in_fees_collected: Balance,
+        // Native value an account pays to renew its subscription for a period of blocks, and
+        // the block up to which each account can transfer; disabled when the period is 0 ↓
+        subscription_fee: Balance,
+        subscription_period_blocks: BlockNumber,
+        subscription_expiry: Mapping<AccountId, BlockNumber>,
+        // Blocks an account must wait between transfers, 0 when disabled, and the block of
+        // each account's last transfer ↓
+        cooldown_blocks: BlockNumber,
+        last_sent_block: Mapping<AccountId, BlockNumber>,
+        // Cap on what the clawback address can revoke per period of blocks, 0 when disabled ↓
+        clawback_period_cap: Balance,
+        clawback_period_blocks: BlockNumber,
+        clawback_used_this_period: Balance,
+        clawback_period_start: BlockNumber,
+        // Balance, opt-in and freeze state of every account ↓
+        holders: Mapping<AccountId, HolderState>,
+        opted_in_count: u64,
+        // Index of the opted-in accounts, by position, and the position of each ↓
+        holder_accounts: Mapping<u64, AccountId>,
+        holder_positions: Mapping<AccountId, u64>,
+        // Number of balance movements ever written, pending transfers included ↓
+        lifetime_transfers: u64,
+        // Log of the movements that changed the circulating supply, and revokes, by index ↓
+        supply_events: Mapping<u32, SupplyEvent>,
+        supply_event_count: u32,
+        // Append-only log of the actions of the role addresses, by index ↓
+        admin_actions: Mapping<u64, AdminAction>,
+        admin_action_count: u64,
+        // Sponsored opt-ins: the next nonce each account signs, and who sponsored it ↓
+        opt_in_nonces: Mapping<AccountId, u64>,
+        sponsors: Mapping<AccountId, AccountId>,
+        // Lifetime sent and received amounts, never cleaned up ↓
+        lifetime_totals: Mapping<AccountId, LifetimeTotals>,
+        freeze_history: Mapping<AccountId, Vec<FreezeEvent>>,
+        // Block from which a time-limited freeze can be released, per frozen account ↓
+        freeze_expiries: Mapping<AccountId, BlockNumber>,
+        // Time from which a freeze no longer applies, per frozen account, lifted lazily ↓
+        freeze_deadlines: Mapping<AccountId, Timestamp>,
+        // Part of the balance of an account frozen by the freeze address ↓
+        partially_frozen: Mapping<AccountId, Balance>,
+        // Balance of a borrower encumbered as collateral, per lender and in total ↓
+        liens: Mapping<LienKey, Balance>,
+        liened: Mapping<AccountId, Balance>,
+        // Accounts the freeze address exempts from account and secondary freezes, e.g. a DEX
+        // pool or a bridge escrow ↓
+        freeze_exempt: Mapping<AccountId, bool>,
+        // Accounts electing to reinvest dividends, set by the accounts themselves ↓
+        drip_election: Mapping<AccountId, bool>,
+        // Latest appeal of every account against its freeze ↓
+        appeals: Mapping<AccountId, FreezeAppeal>,
+        // Destinations a restricted account can send to, set by the account itself ↓
+        allowed_destinations: Mapping<AccountId, Vec<AccountId>>,
+        // Manager-placed locks, each releasing its amount at a block ↓
+        lock_entries: Mapping<AccountId, Vec<LockEntry>>,
+        next_lock_id: u64,
+        // Transfers escrowed until their receiver claims them, and their total ↓
+        pending_transfers: Mapping<u64, PendingTransfer>,
+        pending_ids: Mapping<AccountId, Vec<u64>>,
+        next_pending_id: u64,
+        pending_total: Balance,
+        // Transfers escrowed with a hidden amount until their sender reveals it, and their total ↓
+        committed_transfers: Mapping<u64, CommittedTransfer>,
+        next_commitment_id: u64,
+        committed_total: Balance,
+        // Transfers escrowed until anyone executes them after their time, and their total ↓
+        scheduled_transfers: Mapping<u64, ScheduledTransfer>,
+        next_scheduled_id: u64,
+        scheduled_total: Balance,
+        // Transfers above the threshold, 0 when disabled, escrowed until the manager approves
+        // them, and their total ↓
+        large_transfer_threshold: Balance,
+        large_transfer_pending: Mapping<u64, LargeTransfer>,
+        next_large_transfer_id: u64,
+        large_transfer_total: Balance,
+        // Streams from the reserve accruing linearly to their recipient, and what they escrow ↓
+        streams: Mapping<u64, Stream>,
+        next_stream_id: u64,
+        streamed_total: Balance,
+        // KYC ↓
+        kyc_required: bool,
+        kyc_status: Mapping<AccountId, u8>,
+        // Holder-provided metadata, keyed by account and field hash ↓
+        account_metadata: Mapping<AccountMetadataKey, Vec<u8>>,
+        // Accounts each reserve authorized to distribute from it ↓
+        distributors: Mapping<DistributorKey, ()>,
+        // Holder of the single unit when total == 1 ↓
+        nft_owner: Option<AccountId>,
+    }
+
+    /// KYC status of an account that has never been reviewed.
+    pub const KYC_UNKNOWN: u8 = 0;
+    /// KYC status of an account under review.
+    pub const KYC_PENDING: u8 = 1;
+    /// KYC status of an approved account.
+    pub const KYC_APPROVED: u8 = 2;
+    /// KYC status of a rejected account.
+    pub const KYC_REJECTED: u8 = 3;
+
+    /// Parameters of a new asset, as taken by the `new_with_config` constructor.
+    /// Note: the roles are named fields, so they cannot be swapped by accident.
+    /// Note: `None` roles follow the same defaults as in `new`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone, Default)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AssetConfig {
+        /// The full name of this asset.
+        pub asset_name: String,
+        /// The name of a single unit of this asset.
+        pub unit_name: String,
+        /// The total number of units of this asset.
+        pub total: Balance,
+        /// The number of digits to use after the decimal point when displaying this asset.
+        pub decimals: u32,
+        /// Whether the asset is frozen by default.
+        pub default_frozen: bool,
+        /// A URL where more information about the asset can be retrieved.
+        pub url: String,
+        /// A commitment to some unspecified asset metadata.
+        pub metadata_hash: [u8; 4],
+        /// The address that can change the reserve, freeze, clawback, and manager addresses.
+        pub manager: Option<AccountId>,
+        /// The address that holds reserve (non-minted) units of the asset.
+        pub reserve: Option<AccountId>,
+        /// The address that can freeze or unfreeze user asset holdings.
+        pub freeze: Option<AccountId>,
+        /// The address that can revoke user asset holdings and send them to other addresses.
+        pub clawback: Option<AccountId>,
+        /// Whether accounts need an approved KYC status to opt in.
+        pub kyc_required: bool,
+        /// Whether the asset starts `Pending`, until the manager activates it.
+        pub start_suspended: bool,
+        /// The minimum amount `opt_in_and_fund` can fund a new account with.
+        pub min_opt_in_balance: Balance,
+        /// Whether `wipe` only applies to frozen accounts.
+        pub wipe_requires_freeze: bool,
+        /// Whether a non-empty `url` must start with one of the allowed schemes.
+        pub strict_url: bool,
+        /// Whether the manager can change the url later on, e.g. after a domain change.
+        pub mutable_url: bool,
+        /// The registry authority that can attest the asset as verified, if any.
+        pub verifier: Option<AccountId>,
+        /// A human-readable identifier of the asset, zero-padded; all zeroes for none.
+        pub asset_symbol: [u8; 12],
+        /// The time after which the clawback role expires for good, if any.
+        pub clawback_sunset: Option<Timestamp>,
+        /// A commitment to the source revision the contract was built from, e.g. a git commit
+        /// hash, if any.
+        pub source_commitment: Option<[u8; 32]>,
+        /// Whether the manager, freeze and clawback roles must be held by distinct accounts.
+        pub enforce_role_separation: bool,
+        /// Accounts opted in and funded from the reserve at creation, at most
+        /// `MAX_STATUS_BATCH`, with their amounts.
+        pub initial_distribution: Vec<(AccountId, Balance)>,
+        /// Share of every transfer destroyed for good, in basis points, at most
+        /// `MAX_BURN_ON_TRANSFER_BPS`; 0 disables it.
+        pub burn_on_transfer_bps: u16,
+    }
+
+    /// Storage key of an account metadata field: the account and the hash of the field name.
+    type AccountMetadataKey = (AccountId, [u8; 32]);
+
+    /// Storage key of a tax rate: the sender and the receiver jurisdiction codes.
+    type JurisdictionPair = ([u8; 2], [u8; 2]);
+
+    /// Storage key of a distributor authorization: the reserve and the distributor.
+    type DistributorKey = (AccountId, AccountId);
+
+    /// Storage key of a lien: the borrower and the lender.
+    type LienKey = (AccountId, AccountId);
+
+    /// A large transfer awaiting the manager's approval: the sender, the receiver and the
+    /// escrowed amount.
+    pub type LargeTransfer = (AccountId, AccountId, Balance);
+
+    /// Maximum size, in bytes, of a single account metadata value.
+    const MAX_ACCOUNT_METADATA_SIZE: usize = 1024;
+
+    /// Maximum size, in bytes, of the verification URI.
+    const MAX_VERIFICATION_URI_SIZE: usize = 256;
+
+    /// Maximum number of entries kept in the freeze history of an account.
+    const FREEZE_HISTORY_CAPACITY: usize = 20;
+
+    /// URL schemes allowed for the asset URL of a `strict_url` asset.
+    const ALLOWED_URL_SCHEMES: [&str; 3] = ["https", "ipfs", "ar"];
+
+    /// Returns the integer square root of `value`, by Newton's method.
+    fn integer_sqrt(value: Balance) -> Balance {
+        if value < 2 {
+            return value;
+        }
+
+        // start above the root, and stop once the estimate no longer decreases
+        let mut root = value / 2 + 1;
+        let mut next = (root + value / root) / 2;
+        while next < root {
+            root = next;
+            next = (root + value / root) / 2;
+        }
+        root
+    }
+
+    /// Returns the scheme of `url`, i.e. what precedes "://", if any.
+    fn scheme_of(url: &str) -> Option<&str> {
+        url.split_once("://").map(|(scheme, _)| scheme)
+    }
+
+    /// Per-account state of a holder.
+    /// Note: kept in a single entry, so that tracking activity adds no storage writes.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct HolderState {
+        /// The balance of the account.
+        pub balance: Balance,
+        /// Whether the account has opted in to the asset.
+        pub opted_in: bool,
+        /// Whether the account is frozen.
+        pub frozen: bool,
+        /// Sum of `balance * blocks held`, up to `last_update_block`.
+        pub time_weighted_balance: u128,
+        /// The block of the last balance change.
+        pub last_update_block: BlockNumber,
+        /// When the account first opted in.
+        pub opted_in_at: Option<Timestamp>,
+        /// When the account last opted in, sent, received, or got revoked or (un)frozen.
+        pub last_activity: Option<Timestamp>,
+        /// The amount sent in the current spend limit window.
+        pub spent_in_window: Balance,
+        /// When the current spend limit window started.
+        pub window_start: Timestamp,
+        /// Whether the account can only send to its allowed destinations.
+        pub transfers_restricted: bool,
+    }
+
+    /// Amounts an account has ever sent and received.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct LifetimeTotals {
+        pub received: Balance,
+        pub sent: Balance,
+    }
+
+    /// Split of the native balance of the contract, as returned by `native_accounting`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct NativeAccounting {
+        /// The native balance of the contract.
+        pub contract_balance: Balance,
+        /// The opt-in and subscription fees collected and not withdrawn yet.
+        pub opt_in_fees: Balance,
+        /// The part of the balance no bucket accounts for, e.g. its existential deposit.
+        pub unattributed: Balance,
+        /// Whether the buckets exceed the balance, in which case `unattributed` is 0.
+        pub overdrawn: bool,
+    }
+
+    /// Lifetime statistics of an account, as returned by `account_stats`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AccountStats {
+        /// The amount the account has ever received, including revocations to it.
+        pub total_received: Balance,
+        /// The amount the account has ever sent, including revocations from it.
+        pub total_sent: Balance,
+        /// The current balance of the account.
+        pub balance: Balance,
+    }
+
+    /// Role changes applied at once by `patch_asset`: `None` keeps a role, `Some(None)` clears
+    /// it and `Some(Some(account))` hands it to `account`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, Default)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ModifyPatch {
+        /// The new manager address, if it changes.
+        pub manager: Option<Option<AccountId>>,
+        /// The new reserve address, if it changes.
+        pub reserve: Option<Option<AccountId>>,
+        /// The new freeze address, if it changes.
+        pub freeze: Option<Option<AccountId>>,
+        /// The new clawback address, if it changes.
+        pub clawback: Option<Option<AccountId>>,
+    }
+
+    /// Changes to the compliance lists, as applied at once by `apply_compliance_diff`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone, Default)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ComplianceDiff {
+        /// The accounts to add to the whitelist.
+        pub add_whitelist: Vec<AccountId>,
+        /// The accounts to remove from the whitelist.
+        pub remove_whitelist: Vec<AccountId>,
+        /// The accounts to add to the blacklist.
+        pub add_blacklist: Vec<AccountId>,
+        /// The accounts to remove from the blacklist.
+        pub remove_blacklist: Vec<AccountId>,
+    }
+
+    /// Provenance of the deployed contract, as returned by `build_info`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct BuildInfo {
+        /// The version of the crate the contract was compiled from.
+        pub crate_version: String,
+        /// The version of the storage layout.
+        pub storage_version: u32,
+        /// The major version of ink! the contract was compiled with.
+        pub ink_major_version: u32,
+        /// The commitment to the source revision given at creation, if any.
+        pub source_commitment: Option<[u8; 32]>,
+    }
+
+    /// Parameters and counters of an asset, as returned by `export_config`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AssetParams {
+        /// The account that created this asset.
+        pub creator: AccountId,
+        /// The full name of this asset.
+        pub asset_name: String,
+        /// The name of a single unit of this asset.
+        pub unit_name: String,
+        /// The total number of units of this asset.
+        pub total: Balance,
+        /// The number of digits to use after the decimal point when displaying this asset.
+        pub decimals: u32,
+        /// Whether the asset is frozen by default.
+        pub default_frozen: bool,
+        /// A URL where more information about the asset can be retrieved.
+        pub url: String,
+        /// A commitment to some unspecified asset metadata.
+        pub metadata_hash: [u8; 4],
+        /// The manager address, the zero address if cleared.
+        pub manager: AccountId,
+        /// The reserve address, the zero address if cleared.
+        pub reserve: AccountId,
+        /// The freeze address, the zero address if cleared.
+        pub freeze: AccountId,
+        /// The clawback address, the zero address if cleared.
+        pub clawback: AccountId,
+        /// The lifecycle state of the asset.
+        pub asset_status: AssetStatus,
+        /// The number of balance movements ever made.
+        pub lifetime_transfers: u64,
+        /// The number of opted-in accounts, i.e. of entries `export_holders` pages through.
+        pub opted_in_count: u64,
+        /// The block the export was taken at.
+        pub block: BlockNumber,
+    }
+
+    /// Exported state of an opted-in account: the account, its balance and whether it is frozen.
+    pub type HolderExport = (AccountId, Balance, bool);
+
+    /// Full state of an asset, as reassembled by `export_full_state`.
+    #[cfg(feature = "std")]
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct FullState {
+        /// The parameters and counters of the asset.
+        pub config: AssetParams,
+        /// Every opted-in account, in index order.
+        pub holders: Vec<HolderExport>,
+    }
+
+    /// Reassembles the full state of an asset from its `config` and the pages of
+    /// `export_holders`, fetched `page_size` accounts at a time.
+    /// Note: the index reorders when accounts opt out, so `config` and every page must be read
+    /// at the same block, e.g. by pinning its block hash in the RPC calls.
+    #[cfg(feature = "std")]
+    pub fn export_full_state(
+        config: AssetParams,
+        page_size: u64,
+        mut export_holders: impl FnMut(u64, u64) -> Vec<HolderExport>,
+    ) -> FullState {
+        let mut holders = Vec::new();
+        while (holders.len() as u64) < config.opted_in_count {
+            let page = export_holders(holders.len() as u64, page_size);
+            if page.is_empty() {
+                break;
+            }
+            holders.extend(page);
+        }
+        FullState { config, holders }
+    }
+
+    /// Returns the fingerprint of the asset exported in `params`, as `fingerprint` returns it
+    /// on-chain, i.e. the Blake2x256 hash of the SCALE-encoded (`creator`, `asset_name`,
+    /// `unit_name`, `total`, `decimals`, `default_frozen`, `url`, `metadata_hash`).
+    /// Note: the fingerprint commits to the creation url, so it differs once the url changes.
+    #[cfg(feature = "std")]
+    pub fn compute_fingerprint(params: &AssetParams) -> [u8; 32] {
+        let mut fingerprint = [0x0; 32];
+        ink::env::hash_encoded::<Blake2x256, _>(
+            &(
+                params.creator,
+                &params.asset_name,
+                &params.unit_name,
+                params.total,
+                params.decimals,
+                params.default_frozen,
+                &params.url,
+                params.metadata_hash,
+            ),
+            &mut fingerprint,
+        );
+        fingerprint
+    }
+
+    /// Returns the build info of this contract with `source_commitment`.
+    fn build_info(source_commitment: Option<[u8; 32]>) -> BuildInfo {
+        BuildInfo {
+            crate_version: env!("CARGO_PKG_VERSION").into(),
+            storage_version: STORAGE_VERSION,
+            ink_major_version: INK_MAJOR_VERSION,
+            source_commitment,
+        }
+    }
+
+    /// Holding status of an account, as returned by `holding_status`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum HoldingStatus {
+        NotOptedIn,
+        OptedIn { balance: Balance, frozen: bool },
+    }
+
+    /// Version of the storage layout, to bump on every incompatible change of `Subsa`.
+    const STORAGE_VERSION: u32 = 1;
+
+    /// Major version of the ink! dependency, to bump along with it.
+    const INK_MAJOR_VERSION: u32 = 4;
+
+    /// Maximum number of accounts of a `statuses_of` query.
+    const MAX_STATUS_BATCH: usize = 256;
+
+    /// Maximum number of holders of a `top_holders` query.
+    const MAX_RANKING: u32 = 100;
+
+    /// Maximum number of allowed destinations of an account.
+    const MAX_ALLOWED_DESTINATIONS: usize = 50;
+
+    /// Maximum number of locks an account can have at once.
+    const MAX_LOCKS_PER_ACCOUNT: usize = 20;
+
+    /// Maximum transfer fee, in basis points.
+    const MAX_TRANSFER_FEE_BPS: u16 = 1_000;
+
+    /// Maximum share of a transfer that can be burnt, in basis points.
+    const MAX_BURN_ON_TRANSFER_BPS: u16 = 1_000;
+
+    /// Maximum number of royalty recipients.
+    const MAX_ROYALTY_SPLITS: usize = 10;
+
+    /// A recipient of a share of every transfer, e.g. a rights holder of the asset.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RoyaltySplit {
+        /// The account receiving the share.
+        pub recipient: AccountId,
+        /// The share of each transfer, in basis points.
+        pub share_bps: u32,
+    }
+
+    /// A time lock on part of the balance of an account.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct LockEntry {
+        /// The id of the lock, as returned by `add_time_lock`.
+        pub id: u64,
+        /// The locked amount.
+        pub amount: Balance,
+        /// The block from which the amount is no longer locked.
+        pub expires_at: BlockNumber,
     }
 
-    /// Errors
+    /// Maximum number of pending transfers an account can receive at once.
+    const MAX_PENDING_PER_RECEIVER: usize = 50;
+
+    /// Appeal of a frozen account against its freeze.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct FreezeAppeal {
+        /// The frozen account.
+        pub appellant: AccountId,
+        /// The hash of the reason of the appeal, kept off-chain.
+        pub reason_hash: [u8; 32],
+        /// The block in which the appeal was submitted.
+        pub submitted_block: BlockNumber,
+        /// Whether the freeze address granted or rejected the appeal.
+        pub resolved: bool,
+    }
+
+    /// A transfer escrowed by `send_pending` until its receiver claims it.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingTransfer {
+        /// The id of the pending transfer, as returned by `send_pending`.
+        pub id: u64,
+        /// The account the tokens are refunded to when cancelled.
+        pub sender: AccountId,
+        /// The account that can claim the tokens.
+        pub receiver: AccountId,
+        /// The escrowed amount.
+        pub amount: Balance,
+        /// The time from which the tokens can no longer be claimed, and anyone can cancel.
+        pub expiry: Timestamp,
+    }
+
+    /// Transfer whose amount stays hidden behind a commitment until its sender reveals it.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct CommittedTransfer {
+        /// The id of the committed transfer, as returned by `commit_transfer`.
+        pub id: u64,
+        /// The account that can reveal the transfer, and gets the excess back.
+        pub sender: AccountId,
+        /// The account receiving the revealed amount.
+        pub receiver: AccountId,
+        /// The `transfer_commitment` of the amount and a salt.
+        pub commitment: [u8; 32],
+        /// The escrowed upper bound of the amount.
+        pub locked: Balance,
+        /// The block from which the transfer can no longer be revealed, and anyone can cancel.
+        pub expiry_block: BlockNumber,
+    }
+
+    /// Transfer escrowed until anyone executes it, once its time has come.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ScheduledTransfer {
+        /// The id of the scheduled transfer, as returned by `schedule_transfer`.
+        pub id: u64,
+        /// The account the tokens are refunded to when cancelled.
+        pub sender: AccountId,
+        /// The account receiving the tokens.
+        pub receiver: AccountId,
+        /// The escrowed amount.
+        pub amount: Balance,
+        /// The time from which anyone can execute the transfer.
+        pub execute_after: Timestamp,
+    }
+
+    /// Payment stream from the reserve, accruing linearly to its recipient from `start` to `stop`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Stream {
+        /// The id of the stream, as returned by `create_stream`.
+        pub id: u64,
+        /// The account the stream accrues to.
+        pub recipient: AccountId,
+        /// The amount escrowed from the reserve, fully accrued at `stop`.
+        pub deposit: Balance,
+        /// The time the stream starts accruing.
+        pub start: Timestamp,
+        /// The time the stream is fully accrued.
+        pub stop: Timestamp,
+        /// The amount the recipient already withdrew.
+        pub withdrawn: Balance,
+    }
+
+    /// Maximum number of calls of a `multicall`.
+    const MAX_MULTICALL_CALLS: usize = 16;
+
+    /// Maximum number of revocations of a `revoke_many`.
+    const MAX_REVOKE_BATCH: usize = 50;
+
+    /// A message of this contract, with its arguments, as executed by `multicall`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum SubsaCall {
+        OptIn,
+        OptOut,
+        Transfer {
+            receiver: AccountId,
+            amount: Balance,
+        },
+        ClaimPending {
+            id: u64,
+        },
+    }
+
+    /// A transfer of a `bundle_transfer`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct BundleTransfer {
+        /// The asset contract to transfer from; only this contract is supported.
+        pub asset_contract: AccountId,
+        /// The account receiving the tokens.
+        pub receiver: AccountId,
+        /// The transferred amount.
+        pub amount: Balance,
+    }
+
+    /// Entry of the freeze history of an account.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FreezeEvent {
+        /// The block in which the account was frozen or unfrozen.
+        pub block: BlockNumber,
+        /// Whether the account was frozen (true) or unfrozen (false).
+        pub frozen: bool,
+        /// The freeze address that made the change.
+        pub freeze_id: AccountId,
+    }
+
+    /// The kind of entry of the supply audit trail.
+    /// Note: the reserve holds the unminted supply, so moving units out of it mints them
+    /// into circulation, and moving units into it burns them.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum SupplyOperation {
+        /// A movement out of the current reserve.
+        Mint,
+        /// A movement into the current reserve.
+        Burn,
+        /// A revocation by the clawback address, including wipes, wherever it sends the units.
+        Revoke,
+    }
+
+    /// Entry of the supply audit trail.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SupplyEvent {
+        /// The block in which the units moved.
+        pub block: BlockNumber,
+        /// The kind of movement.
+        pub operation: SupplyOperation,
+        /// The moved amount.
+        pub amount: Balance,
+        /// The circulating supply after the movement.
+        pub total_after: Balance,
+    }
+
+    /// The kind of entry of the admin log.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum AdminActionKind {
+        /// A role handed to the account, or cleared without one.
+        RoleChange(Role),
+        /// A freeze of the account.
+        Freeze,
+        /// An unfreeze of the account.
+        Unfreeze,
+        /// A freeze of part of the balance of the account, by the value.
+        PartialFreeze,
+        /// An unfreeze of part of the balance of the account, by the value.
+        PartialUnfreeze,
+        /// A freeze of secondary transfers.
+        SecondaryFreeze,
+        /// An unfreeze of secondary transfers.
+        SecondaryUnfreeze,
+        /// A change of the asset status, e.g. a pause.
+        StatusChange(AssetStatus),
+        /// A revoke of the value from the account, wipes included.
+        Clawback,
+        /// A change of the asset url.
+        UrlChange,
+    }
+
+    /// Entry of the admin log.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct AdminAction {
+        /// The role address that took the action.
+        pub actor: AccountId,
+        /// The kind of action.
+        pub kind: AdminActionKind,
+        /// The affected account, if any.
+        pub account: Option<AccountId>,
+        /// The affected amount, 0 if none.
+        pub value: Balance,
+        /// The time of the block in which the action was taken.
+        pub timestamp: Timestamp,
+    }
+
+    /// The mutable roles of an asset.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Role {
+        Manager,
+        Reserve,
+        Freeze,
+        Clawback,
+    }
+
+    /// The kind of balance movement reported to the transfer hook.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum TransferOperation {
+        /// A transfer by the sender, including opt-in funding and role funding.
+        Transfer,
+        /// A transfer between two accounts by the manager.
+        AdminTransfer,
+        /// A revocation by the clawback address, including wipes.
+        Revoke,
+        /// A distribution from the reserve.
+        Distribute,
+        /// A move into a pending transfer, held by this contract.
+        Escrow,
+        /// A move out of a pending transfer, claimed or cancelled.
+        Release,
+    }
+
+    /// Contract observing, and possibly vetoing, the balance movements of an asset.
+    /// Note: a hook can call back into the asset to read it, but calling a state-changing
+    /// message fails with `Error::ReentrancyDetected`
+    #[ink::trait_definition]
+    pub trait TransferHook {
+        /// Checks a movement of `amount` from `from` to `to`, before the asset writes it.
+        /// Note: returning `Err(code)` aborts the movement with `Error::HookRejected(code)`.
+        #[ink(message)]
+        fn before_transfer(
+            &self,
+            operation: TransferOperation,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), u32>;
+
+        /// Notifies of a movement of `amount` from `from` to `to`, once the asset wrote it.
+        #[ink(message)]
+        fn after_transfer(
+            &mut self,
+            operation: TransferOperation,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        );
+    }
+
+    /// Contract mapping asset symbols to the asset contracts that registered them.
+    #[ink::trait_definition]
+    pub trait SymbolRegistry {
+        /// Registers the caller as the asset of `symbol`.
+        /// Note: fails with SymbolTaken if `symbol` is already registered.
+        #[ink(message)]
+        fn register_asset(&mut self, symbol: [u8; 12]) -> Result<(), Error>;
+    }
+
+    /// How `voting_power` weighs the balance of an account.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum VotingFormula {
+        /// The balance itself.
+        Linear,
+        /// The square of the balance, in units of the asset: balance^2 / 10^decimals.
+        Quadratic,
+        /// The integer square root of the balance.
+        SquareRoot,
+    }
+
+    /// The lifecycle state of an asset.
+    /// Note: transfers and opt-ins are only allowed while the asset is `Active`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum AssetStatus {
+        /// Created with `start_suspended`, and not activated yet.
+        Pending,
+        Active,
+        Suspended,
+        Destroyed,
+    }
+
+    // Errors
 
     /// Error types
     #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
@@ -66,9 +1169,122 @@ mod subsa {
         NotEnoughBalance,
         NotAllAssetsOwnedByManager,
         ZeroAmount,
+        InvalidKycStatus,
+        KycNotApproved,
+        InvalidDecimals,
+        RoleDisabled,
+        MetadataTooLarge,
+        AssetNotActive,
+        InvalidStatusTransition,
+        BelowMinimumOptInBalance,
+        CannotWipeReserve,
+        StatsOverflow,
+        BalanceLocked,
+        InvalidLockExpiry,
+        TooManyLocks,
+        /// The transfer would exceed the spend limit of the sender; the window resets at
+        /// `retry_after`.
+        SpendLimitExceeded {
+            retry_after: Timestamp,
+        },
+        DestinationNotAllowed,
+        TooManyDestinations,
+        ClawbackCapExceeded,
+        InvalidSignature,
+        UnsupportedAssetContract,
+        PendingTransferNotFound,
+        NotPendingReceiver,
+        PendingTransferExpired,
+        CannotCancelPending,
+        InvalidPendingExpiry,
+        TooManyPendingTransfers,
+        AppealPending,
+        NoPendingAppeal,
+        TooManyCalls,
+        NotPendingManager,
+        NoManagerProposal,
+        RuntimeMirrorFailed,
+        NonZeroBalance,
+        /// The transfer hook rejected the movement with the given code.
+        HookRejected(u32),
+        HookCallFailed,
+        ReentrancyDetected,
+        InvalidFreezeExpiry,
+        FreezeNotExpired,
+        NotPartiallyFrozen,
+        TransferToZeroAddress,
+        InvalidTransferFee,
+        InsufficientOptInFee,
+        OptInFeeRefundFailed,
+        InvalidFractionalUnits,
+        UnitConversionOverflow,
+        InvalidUrl,
+        CommittedTransferNotFound,
+        NotCommitmentSender,
+        CommittedTransferExpired,
+        CannotCancelCommitment,
+        InvalidCommitmentExpiry,
+        CommitmentMismatch,
+        CommitmentExceedsLock,
+        VerifierDisabled,
+        NotVerifier,
+        VerificationUriTooLarge,
+        SecondaryMarketFrozen,
+        ScheduledTransferNotFound,
+        InvalidScheduleTime,
+        ScheduledTransferNotDue,
+        NotScheduleSender,
+        InvalidSymbol,
+        SymbolTaken,
+        AssetAlreadyRegistered,
+        RegistryCallFailed,
+        StreamNotFound,
+        InvalidStreamTime,
+        NotStreamRecipient,
+        NotStreamParty,
+        StreamBalanceExceeded,
+        /// An NFT can only be transferred whole, i.e. with an amount of 1.
+        NFTPartialTransferNotAllowed,
+        /// The supply of an NFT cannot change, so it cannot be minted or split.
+        NFTMintingNotAllowed,
+        TooManyRevocations,
+        TooManyAccounts,
+        AccountStillActive,
+        InsufficientCollectedFees,
+        NativeTransferFailed,
+        TooManyRoyaltySplits,
+        RoyaltySharesMismatch,
+        InsufficientUnattributedBalance,
+        CloneFailed,
+        CannotRecoverOwnToken,
+        TokenRecoveryFailed,
+        InvalidJurisdiction,
+        InvalidTaxRate,
+        ImmutableField,
+        NoReserveProof,
+        SubscriptionExpired,
+        InsufficientSubscriptionFee,
+        SubscriptionFeeRefundFailed,
+        ClawbackExpired,
+        InsufficientLien,
+        FeeTokenTransferFailed,
+        Blacklisted,
+        NotWhitelisted,
+        RequestedRankingTooLarge,
+        CooldownNotExpired,
+        SelfAsRole,
+        TransferToAssetContract,
+        LargeTransferRequiresApproval,
+        NotLargeTransfer,
+        LargeTransferNotFound,
+        NotLargeTransferSender,
+        RoleSeparationViolated,
+        InitialDistributionExceedsTotal,
+        ImportClosed,
+        InvalidBurnRate,
     }
 
-    /// Events
+    // Events
 
     /// Event emitted when a token transfer occurs.
     #[ink(event)]
@@ -81,6 +1297,18 @@ mod subsa {
         asset_id: AssetId,
         #[ink(topic)]
         amount: Option<Balance>,
+        timestamp: Timestamp,
+    }
+
+    /// Event emitted instead of `Transfer` when the NFT changes hands.
+    #[ink(event)]
+    pub struct NFTTransfer {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        #[ink(topic)]
+        asset_id: AssetId,
     }
 
     /// Event emitted when an asset is created.
@@ -94,6 +1322,23 @@ mod subsa {
         creator: AccountId,
         #[ink(topic)]
         total: Balance,
+        asset_symbol: [u8; 12],
+        build_info: BuildInfo,
+    }
+
+    /// Event emitted when the manager purges a stale account, opting it out.
+    #[ink(event)]
+    pub struct AccountPurged {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        manager_id: AccountId,
+    }
+
+    /// Event emitted when the manager changes the inactivity threshold.
+    #[ink(event)]
+    pub struct InactivityThresholdSet {
+        threshold: Timestamp,
     }
 
     /// Event emitted when an asset is frozen.
@@ -108,734 +1353,12848 @@ mod subsa {
         freeze_id: AccountId,
         #[ink(topic)]
         freeze: bool,
+        timestamp: Timestamp,
     }
 
-    /// Event emitted when an asset is reconfigured.
-    /// Note: only the manager can reconfigure an asset.
-    /// Note: the manager can change the reserve, freeze, and clawback addresses.
+    /// Event emitted when a frozen account appeals against its freeze.
     #[ink(event)]
-    pub struct Modify {
-        #[ink(topic)]
-        manager_id: AccountId,
-        #[ink(topic)]
-        reserve_id: AccountId,
-        #[ink(topic)]
-        freeze_id: AccountId,
+    pub struct AppealSubmitted {
         #[ink(topic)]
-        clawback_id: AccountId,
+        account: AccountId,
+        reason_hash: [u8; 32],
     }
 
-    /// Event emitted when an account opts in to receive an asset.
+    /// Event emitted when anyone releases an expired freeze.
+    /// Note: follows the Freeze event of the unfreeze.
     #[ink(event)]
-    pub struct OptIn {
-        #[ink(topic)]
-        asset_id: AssetId,
+    pub struct FreezeExpired {
         #[ink(topic)]
         account: AccountId,
+        released_at: BlockNumber,
     }
 
-    /// Event emitted when an account opts out of receiving an asset.
-    /// Note: only accounts that have opted in can opt out.
+    /// Event emitted when the freeze address freezes part of the balance of an account.
     #[ink(event)]
-    pub struct OptOut {
+    pub struct PartialFreeze {
         #[ink(topic)]
-        asset_id: AssetId,
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the freeze address unfreezes part of a partially frozen balance.
+    #[ink(event)]
+    pub struct PartialUnfreeze {
         #[ink(topic)]
         account: AccountId,
+        amount: Balance,
     }
 
-    /// Event emitted when an asset is revoked.
-    /// Note: only the manager address can revoke an asset.
+    /// Event emitted when the freeze address encumbers part of a borrower's balance for a lender.
     #[ink(event)]
-    pub struct Revoke {
+    pub struct LienCreated {
         #[ink(topic)]
-        asset_id: AssetId,
+        borrower: AccountId,
         #[ink(topic)]
-        from: AccountId,
+        lender: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the freeze address releases part of a lien.
+    #[ink(event)]
+    pub struct LienReleased {
         #[ink(topic)]
-        clawback_id: AccountId,
+        borrower: AccountId,
         #[ink(topic)]
-        amount: Option<Balance>,
+        lender: AccountId,
+        amount: Balance,
     }
 
-    /// Event emitted when an asset is destroyed.
-    /// Note: this can only happen if there are no remaining asset holdings.
-    /// Note: only the manager can destroy an asset.
+    /// Event emitted when the clawback address enforces a lien.
+    /// Note: follows the Revoke event of the encumbered tokens.
     #[ink(event)]
-    pub struct Destruction {
+    pub struct LienEnforced {
         #[ink(topic)]
-        asset_id: AssetId,
+        borrower: AccountId,
         #[ink(topic)]
-        destroyer: AccountId,
+        lender: AccountId,
+        amount: Balance,
     }
 
-    /// Implementation of the subsa smart contract
-    impl Subsa {
+    /// Event emitted when the freeze address resolves an appeal.
+    /// Note: a granted appeal follows the Freeze event of the unfreeze.
+    #[ink(event)]
+    pub struct AppealResolved {
+        #[ink(topic)]
+        account: AccountId,
+        granted: bool,
+    }
+
+    /// Event emitted when an asset is reconfigured.
+    /// Note: only the manager can reconfigure an asset.
+    /// Note: the manager can change the reserve, freeze, and clawback addresses.
+    #[ink(event)]
+    pub struct Modify {
+        #[ink(topic)]
+        manager_id: AccountId,
+        #[ink(topic)]
+        reserve_id: AccountId,
+        #[ink(topic)]
+        freeze_id: AccountId,
+        #[ink(topic)]
+        clawback_id: AccountId,
+        asset_version: u32,
+        timestamp: Timestamp,
+    }
+
+    /// Event emitted when the manager imports a batch of holdings into a pending asset.
+    #[ink(event)]
+    pub struct HoldingsImported {
+        count: u32,
+        amount: Balance,
+    }
+
+    /// Event emitted when the manager patches an asset, with a bit per role that changed:
+    /// 1 for the manager, 2 for the reserve, 4 for the freeze and 8 for the clawback address.
+    #[ink(event)]
+    pub struct AssetPatched {
+        changed_fields: u8,
+        asset_version: u32,
+    }
+
+    /// Event emitted when a single role of an asset changes hands.
+    /// Note: `None` stands for a cleared (zero address) role.
+    #[ink(event)]
+    pub struct RoleChanged {
+        #[ink(topic)]
+        role: Role,
+        old: Option<AccountId>,
+        new: Option<AccountId>,
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    /// Event emitted when the lifecycle state of an asset changes.
+    #[ink(event)]
+    pub struct StatusChanged {
+        old: AssetStatus,
+        #[ink(topic)]
+        new: AssetStatus,
+    }
+
+    /// Event emitted when an account opts in to receive an asset.
+    /// Note: `frozen` tells whether the holding starts frozen, e.g. for an account frozen before
+    /// it opted out; `forced` whether the reserve opted in the account with `opt_in_and_fund`.
+    /// Note: `deposit` is reserved for opt-in deposits, and is 0 as there are none yet.
+    #[ink(event)]
+    pub struct OptIn {
+        #[ink(topic)]
+        asset_id: AssetId,
+        #[ink(topic)]
+        account: AccountId,
+        timestamp: Timestamp,
+        frozen: bool,
+        deposit: Balance,
+        forced: bool,
+    }
+
+    /// Event emitted when an account opts out of receiving an asset.
+    /// Note: only accounts that have opted in can opt out.
+    /// Note: `remaining_balance` is left in the account, which gets it back when it opts in
+    /// again; `closed_to` and `deposit_refunded` are reserved for close-outs and opt-in
+    /// deposits, and are `None` and 0 on a plain opt out.
+    #[ink(event)]
+    pub struct OptOut {
+        #[ink(topic)]
+        asset_id: AssetId,
+        #[ink(topic)]
+        account: AccountId,
+        timestamp: Timestamp,
+        remaining_balance: Balance,
+        closed_to: Option<AccountId>,
+        deposit_refunded: Balance,
+    }
+
+    /// Event emitted when the verifier changes the verified status or verification URI.
+    #[ink(event)]
+    pub struct VerificationChanged {
+        #[ink(topic)]
+        verifier: AccountId,
+        verified: bool,
+        verification_uri: Vec<u8>,
+    }
+
+    /// Event emitted when the manager changes the opt-in fee.
+    #[ink(event)]
+    pub struct OptInFeeSet {
+        fee: Balance,
+    }
+
+    /// Event emitted when an opt-in pays its fee.
+    /// Note: follows the OptIn event of the account.
+    #[ink(event)]
+    pub struct OptInFeeCollected {
+        #[ink(topic)]
+        account: AccountId,
+        fee: Balance,
+    }
+
+    /// Event emitted when the manager changes the transfer cooldown.
+    #[ink(event)]
+    pub struct CooldownSet {
+        blocks: BlockNumber,
+    }
+
+    /// Event emitted when the manager changes the subscription fee or period.
+    #[ink(event)]
+    pub struct SubscriptionParamsSet {
+        fee: Balance,
+        period_blocks: BlockNumber,
+    }
+
+    /// Event emitted when an account pays its subscription fee.
+    #[ink(event)]
+    pub struct SubscriptionRenewed {
+        #[ink(topic)]
+        account: AccountId,
+        new_expiry: BlockNumber,
+    }
+
+    /// Event emitted when the manager withdraws collected opt-in fees.
+    #[ink(event)]
+    pub struct OptInFeesWithdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the manager returns native value sent to the contract unsolicited.
+    #[ink(event)]
+    pub struct NativeRecovered {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the manager forwards PSP22 tokens stranded at the contract.
+    #[ink(event)]
+    pub struct TokenRecovered {
+        #[ink(topic)]
+        token: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when a third party opts in an account on its behalf.
+    /// Note: follows the OptIn event of the account.
+    #[ink(event)]
+    pub struct OptInSponsored {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        sponsor: AccountId,
+        nonce: u64,
+    }
+
+    /// Event emitted when the manager cancels the opt-in of an account.
+    /// Note: only accounts without a balance can have their opt-in cancelled.
+    #[ink(event)]
+    pub struct OptInCancelled {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        manager_id: AccountId,
+    }
+
+    /// Event emitted when an asset is revoked.
+    /// Note: only the manager address can revoke an asset.
+    #[ink(event)]
+    pub struct Revoke {
+        #[ink(topic)]
+        asset_id: AssetId,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        #[ink(topic)]
+        clawback_id: AccountId,
+        amount: Balance,
+        from_balance: Balance,
+        to_balance: Balance,
+        timestamp: Timestamp,
+    }
+
+    /// Event emitted when an account restricts or unrestricts its outbound transfers.
+    #[ink(event)]
+    pub struct TransfersRestricted {
+        #[ink(topic)]
+        account: AccountId,
+        restricted: bool,
+    }
+
+    /// Event emitted when an account elects or revokes dividend reinvestment.
+    #[ink(event)]
+    pub struct DripElectionChanged {
+        #[ink(topic)]
+        account: AccountId,
+        enabled: bool,
+    }
+
+    /// Event emitted when an account adds or removes one of its allowed destinations.
+    #[ink(event)]
+    pub struct AllowedDestinationSet {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        destination: AccountId,
+        allowed: bool,
+    }
+
+    /// Event emitted when the manager changes the spend limit.
+    #[ink(event)]
+    pub struct SpendLimitSet {
+        limit: Balance,
+        period: Timestamp,
+    }
+
+    /// Event emitted when the manager freezes or unfreezes secondary transfers.
+    #[ink(event)]
+    pub struct SecondaryFreezeSet {
+        frozen: bool,
+    }
+
+    /// Event emitted when the manager deploys a clone of the asset.
+    #[ink(event)]
+    pub struct AssetCloned {
+        #[ink(topic)]
+        original: AssetId,
+        #[ink(topic)]
+        clone: AssetId,
+    }
+
+    /// Event emitted when the manager changes the voting formula.
+    #[ink(event)]
+    pub struct VotingFormulaSet {
+        formula: VotingFormula,
+    }
+
+    /// Event emitted when the manager changes the transfer fee.
+    #[ink(event)]
+    pub struct TransferFeeSet {
+        fee_bps: u16,
+    }
+
+    /// Event emitted when the manager changes the token the transfer fee is paid in.
+    #[ink(event)]
+    pub struct FeeTokenSet {
+        token: Option<AccountId>,
+    }
+
+    /// Event emitted when the manager exempts an account from transfer fees, or not.
+    #[ink(event)]
+    pub struct FeeExemptionSet {
+        #[ink(topic)]
+        account: AccountId,
+        exempt: bool,
+    }
+
+    /// Event emitted when the manager applies a compliance diff, with the number of accounts
+    /// each list actually gained or lost.
+    #[ink(event)]
+    pub struct ComplianceDiffApplied {
+        added_whitelist: u32,
+        removed_whitelist: u32,
+        added_blacklist: u32,
+        removed_blacklist: u32,
+    }
+
+    /// Event emitted when the freeze address exempts an account from freezes, or not.
+    #[ink(event)]
+    pub struct FreezeExemptionChanged {
+        #[ink(topic)]
+        account: AccountId,
+        exempt: bool,
+    }
+
+    /// Event emitted when the manager publishes a new reserve proof.
+    #[ink(event)]
+    pub struct ReserveProofPublished {
+        root: [u8; 32],
+        block: BlockNumber,
+    }
+
+    /// Event emitted when the manager changes the url of a `mutable_url` asset.
+    #[ink(event)]
+    pub struct UrlChanged {
+        old: String,
+        new: String,
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    /// Event emitted when the manager sets the jurisdiction of an account, or clears it.
+    #[ink(event)]
+    pub struct JurisdictionSet {
+        #[ink(topic)]
+        account: AccountId,
+        code: Option<[u8; 2]>,
+    }
+
+    /// Event emitted when the manager sets the tax rate between two jurisdictions.
+    #[ink(event)]
+    pub struct TaxRateSet {
+        jurisdiction: JurisdictionPair,
+        rate_bps: u32,
+    }
+
+    /// Event emitted when a transfer between two jurisdictions pays tax to the reserve.
+    /// Note: follows the Transfer event of the net amount.
+    #[ink(event)]
+    pub struct TaxCollected {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+        jurisdiction: JurisdictionPair,
+    }
+
+    /// Event emitted when a transfer pays its fee to the reserve.
+    /// Note: follows the Transfer event of the net amount.
+    #[ink(event)]
+    pub struct TransferFeeCollected {
+        #[ink(topic)]
+        sender: AccountId,
+        fee: Balance,
+    }
+
+    /// Event emitted when a transfer burns its share of the amount for good.
+    /// Note: follows the Transfer event of the net amount.
+    #[ink(event)]
+    pub struct Burn {
+        #[ink(topic)]
+        from: AccountId,
+        amount: Balance,
+        current_supply: Balance,
+    }
+
+    /// Event emitted when a transfer pays royalties to the royalty recipients.
+    /// Note: follows the Transfer event of the net amount.
+    #[ink(event)]
+    pub struct RoyaltySplitPaid {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        splits_count: u32,
+        total_royalty: Balance,
+    }
+
+    /// Event emitted when the manager changes the royalty recipients.
+    #[ink(event)]
+    pub struct RoyaltySplitsSet {
+        splits: Vec<RoyaltySplit>,
+    }
+
+    /// Event emitted when the manager changes the clawback cap.
+    #[ink(event)]
+    pub struct ClawbackCapSet {
+        cap: Balance,
+        period_blocks: BlockNumber,
+    }
+
+    /// Event emitted when the manager sets or clears the transfer hook.
+    #[ink(event)]
+    pub struct TransferHookSet {
+        hook: Option<AccountId>,
+    }
+
+    /// Event emitted when the manager locks part of the balance of an account.
+    #[ink(event)]
+    pub struct LockAdded {
+        #[ink(topic)]
+        account: AccountId,
+        lock_id: u64,
+        amount: Balance,
+        expires_at: BlockNumber,
+    }
+
+    /// Event emitted when an expired lock is removed.
+    #[ink(event)]
+    pub struct LockExpired {
+        #[ink(topic)]
+        account: AccountId,
+        lock_id: u64,
+        amount: Balance,
+    }
+
+    /// Event emitted when tokens are escrowed for a receiver.
+    #[ink(event)]
+    pub struct PendingCreated {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+        expiry: Timestamp,
+    }
+
+    /// Event emitted when the receiver claims a pending transfer.
+    #[ink(event)]
+    pub struct PendingClaimed {
+        id: u64,
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when tokens are escrowed for a transfer of a hidden amount.
+    #[ink(event)]
+    pub struct TransferCommitted {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        receiver: AccountId,
+        commitment: [u8; 32],
+    }
+
+    /// Event emitted when the sender reveals a committed transfer, executing it.
+    /// Note: follows the balance movements to the receiver and of the excess back to the sender.
+    #[ink(event)]
+    pub struct TransferRevealed {
+        id: u64,
+        amount: Balance,
+    }
+
+    /// Event emitted when a committed transfer is cancelled and refunded to its sender.
+    #[ink(event)]
+    pub struct CommitmentCancelled {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        amount: Balance,
+        by: AccountId,
+    }
+
+    /// Event emitted when tokens are escrowed for a transfer executed later.
+    #[ink(event)]
+    pub struct TransferScheduled {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+        execute_after: Timestamp,
+    }
+
+    /// Event emitted when a scheduled transfer is executed, crediting its receiver.
+    #[ink(event)]
+    pub struct ScheduledExecuted {
+        id: u64,
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+        executor: AccountId,
+    }
+
+    /// Event emitted when the sender cancels a scheduled transfer, refunding itself.
+    #[ink(event)]
+    pub struct ScheduledCancelled {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the manager changes the large transfer threshold.
+    #[ink(event)]
+    pub struct LargeTransferThresholdSet {
+        threshold: Balance,
+    }
+
+    /// Event emitted when tokens are escrowed for a large transfer awaiting approval.
+    #[ink(event)]
+    pub struct LargeTransferInitiated {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the manager approves a large transfer, crediting its receiver.
+    #[ink(event)]
+    pub struct LargeTransferApproved {
+        id: u64,
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the sender cancels a large transfer, refunding itself.
+    #[ink(event)]
+    pub struct LargeTransferCancelled {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the reserve escrows a deposit into a stream.
+    #[ink(event)]
+    pub struct StreamCreated {
+        id: u64,
+        #[ink(topic)]
+        recipient: AccountId,
+        deposit: Balance,
+        start: Timestamp,
+        stop: Timestamp,
+    }
+
+    /// Event emitted when the recipient of a stream withdraws from it.
+    #[ink(event)]
+    pub struct StreamWithdrawn {
+        id: u64,
+        #[ink(topic)]
+        recipient: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when a stream is cancelled, splitting what is left of its deposit.
+    #[ink(event)]
+    pub struct StreamCancelled {
+        id: u64,
+        #[ink(topic)]
+        recipient: AccountId,
+        recipient_amount: Balance,
+        reserve_amount: Balance,
+        by: AccountId,
+    }
+
+    /// Event emitted when a pending transfer is cancelled and refunded to its sender.
+    #[ink(event)]
+    pub struct PendingCancelled {
+        id: u64,
+        #[ink(topic)]
+        sender: AccountId,
+        amount: Balance,
+        by: AccountId,
+    }
+
+    /// Event emitted when the manager proposes a new manager.
+    #[ink(event)]
+    pub struct ManagerProposed {
+        #[ink(topic)]
+        proposed: AccountId,
+    }
+
+    /// Event emitted when the proposed manager accepts the role.
+    /// Note: follows the RoleChanged event of the manager.
+    #[ink(event)]
+    pub struct ManagerAccepted {
+        #[ink(topic)]
+        new_manager: AccountId,
+    }
+
+    /// Event emitted when the manager cancels its proposal.
+    #[ink(event)]
+    pub struct ManagerProposalCancelled {
+        #[ink(topic)]
+        proposed: AccountId,
+    }
+
+    /// Event emitted when the reserve funds the address holding a role.
+    /// Note: follows the Transfer event of the funding.
+    #[ink(event)]
+    pub struct RoleFunded {
+        #[ink(topic)]
+        role: Role,
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the reserve authorizes a distributor, or revokes it.
+    #[ink(event)]
+    pub struct DistributorSet {
+        #[ink(topic)]
+        reserve_id: AccountId,
+        #[ink(topic)]
+        distributor: AccountId,
+        allowed: bool,
+    }
+
+    /// Event emitted when the reserve distributes tokens to several accounts at once.
+    /// Note: replaces the individual Transfer events of the distribution.
+    #[ink(event)]
+    pub struct ReserveDistribution {
+        #[ink(topic)]
+        reserve_id: AccountId,
+        /// The reserve itself, or the distributor that made the distribution.
+        #[ink(topic)]
+        by: AccountId,
+        recipient_count: u32,
+        total_distributed: Balance,
+    }
+
+    /// Event emitted when a bundle of transfers has been executed.
+    /// Note: follows the Transfer events of the bundle.
+    #[ink(event)]
+    pub struct BundleTransferExecuted {
+        #[ink(topic)]
+        sender: AccountId,
+        count: u32,
+    }
+
+    /// Event emitted when the clawback address wipes an account.
+    /// Note: follows the Revoke event of the moved balance.
+    #[ink(event)]
+    pub struct Wiped {
+        #[ink(topic)]
+        target: AccountId,
+        amount: Balance,
+        #[ink(topic)]
+        clawback_id: AccountId,
+    }
+
+    /// Event emitted when the manager moves tokens between two accounts.
+    /// Note: only the manager can execute an admin transfer.
+    #[ink(event)]
+    pub struct AdminTransfer {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+        #[ink(topic)]
+        manager_id: AccountId,
+    }
+
+    /// Event emitted when the KYC status of an account is updated.
+    /// Note: only the manager can update KYC statuses.
+    #[ink(event)]
+    pub struct KycStatusUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        status: u8,
+    }
+
+    /// Event emitted when an account sets one of its metadata fields.
+    #[ink(event)]
+    pub struct AccountMetadataSet {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        key: [u8; 32],
+    }
+
+    /// Event emitted when an account clears one of its metadata fields.
+    #[ink(event)]
+    pub struct AccountMetadataCleared {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        key: [u8; 32],
+    }
+
+    /// Event emitted when an asset is destroyed.
+    /// Note: this can only happen if there are no remaining asset holdings.
+    /// Note: only the manager can destroy an asset.
+    #[ink(event)]
+    /// Note: carries the final statistics of the asset, for indexers to archive it.
+    pub struct Destruction {
+        #[ink(topic)]
+        asset_id: AssetId,
+        #[ink(topic)]
+        destroyer: AccountId,
+        timestamp: Timestamp,
+        beneficiary: AccountId,
+        total_supply: Balance,
+        lifetime_transfers: u64,
+        holder_count_at_destroy: u64,
+    }
+
+    /// Implementation of the subsa smart contract
+    impl Subsa {
         // Creates a new asset.
         #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
         pub fn new(
             asset_name: String,
             unit_name: String,
             total: Balance,
             decimals: u32,
-            default_frozen: bool,
-            url: String,
-            metadata_hash: [u8; 4],
+            default_frozen: bool,
+            url: String,
+            metadata_hash: [u8; 4],
+            manager: Option<AccountId>,
+            reserve: Option<AccountId>,
+            freeze: Option<AccountId>,
+            clawback: Option<AccountId>,
+        ) -> Result<Self, Error> {
+            Self::new_with_config(AssetConfig {
+                asset_name,
+                unit_name,
+                total,
+                decimals,
+                default_frozen,
+                url,
+                metadata_hash,
+                manager,
+                reserve,
+                freeze,
+                clawback,
+                kyc_required: false,
+                start_suspended: false,
+                min_opt_in_balance: 0,
+                wipe_requires_freeze: false,
+                strict_url: false,
+                mutable_url: false,
+                verifier: None,
+                asset_symbol: [0; 12],
+                clawback_sunset: None,
+                source_commitment: None,
+                enforce_role_separation: false,
+                initial_distribution: Vec::new(),
+                burn_on_transfer_bps: 0,
+            })
+        }
+
+        // Creates a new NFT: an asset with total = 1 and decimals = 0.
+        // Note: the creator is the reserve, so it receives the NFT.
+        #[ink(constructor)]
+        pub fn new_nft(
+            asset_name: String,
+            unit_name: String,
+            url: String,
+            metadata_hash: [u8; 4],
+            manager: Option<AccountId>,
+            freeze: Option<AccountId>,
+            clawback: Option<AccountId>,
+        ) -> Result<Self, Error> {
+            Self::new_with_config(AssetConfig {
+                asset_name,
+                unit_name,
+                total: 1,
+                decimals: 0,
+                url,
+                metadata_hash,
+                manager,
+                freeze,
+                clawback,
+                ..Default::default()
+            })
+        }
+
+        // Creates a new fractional NFT: an asset with total = 10^decimals.
+        // Note: fails with InvalidDecimals if 10^decimals does not fit in a Balance.
+        // Note: the creator is the reserve, so it receives all the fractions.
+        #[ink(constructor)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_fractional_nft(
+            asset_name: String,
+            unit_name: String,
+            url: String,
+            metadata_hash: [u8; 4],
+            manager: Option<AccountId>,
+            freeze: Option<AccountId>,
+            clawback: Option<AccountId>,
+            decimals: u32,
+        ) -> Result<Self, Error> {
+            let total = Balance::checked_pow(10, decimals).ok_or(Error::InvalidDecimals)?;
+            Self::new_with_config(AssetConfig {
+                asset_name,
+                unit_name,
+                total,
+                decimals,
+                url,
+                metadata_hash,
+                manager,
+                freeze,
+                clawback,
+                ..Default::default()
+            })
+        }
+
+        // Creates a new asset from an `AssetConfig`.
+        // Note: `new` funnels into this constructor, so both share the same validation.
+        #[ink(constructor)]
+        pub fn new_with_config(config: AssetConfig) -> Result<Self, Error> {
+            let AssetConfig {
+                asset_name,
+                unit_name,
+                total,
+                decimals,
+                default_frozen,
+                url,
+                metadata_hash,
+                manager,
+                reserve,
+                freeze,
+                clawback,
+                kyc_required,
+                start_suspended,
+                min_opt_in_balance,
+                wipe_requires_freeze,
+                strict_url,
+                mutable_url,
+                verifier,
+                asset_symbol,
+                clawback_sunset,
+                source_commitment,
+                enforce_role_separation,
+                initial_distribution,
+                burn_on_transfer_bps,
+            } = config;
+
+            // check if the url has an allowed scheme, when required
+            check_url(&url, strict_url)?;
+
+            // check if no role is the contract itself, which can never act as an origin
+            if [manager, reserve, freeze, clawback].contains(&Some(Self::env().account_id())) {
+                return Err(Error::SelfAsRole);
+            }
+
+            // check if the roles are held by distinct accounts, when required
+            let zero = zero_account();
+            check_role_separation(
+                [manager, freeze, clawback].map(|role| role.unwrap_or(zero)),
+                enforce_role_separation,
+            )?;
+
+            // check if the initial distribution is within the limit and the total supply
+            if initial_distribution.len() > MAX_STATUS_BATCH {
+                return Err(Error::TooManyAccounts);
+            }
+            let distributed = initial_distribution
+                .iter()
+                .try_fold(0 as Balance, |sum, &(_, amount)| sum.checked_add(amount));
+            if distributed.is_none_or(|distributed| distributed > total) {
+                return Err(Error::InitialDistributionExceedsTotal);
+            }
+
+            // check if the burn rate is under the maximum
+            if burn_on_transfer_bps > MAX_BURN_ON_TRANSFER_BPS {
+                return Err(Error::InvalidBurnRate);
+            }
+
+            // commit to the creation params, see `compute_fingerprint`
+            let mut fingerprint = [0x0; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(
+                &(
+                    Self::env().caller(),
+                    &asset_name,
+                    &unit_name,
+                    total,
+                    decimals,
+                    default_frozen,
+                    &url,
+                    metadata_hash,
+                ),
+                &mut fingerprint,
+            );
+
+            // Emits creation event
+            Self::env().emit_event(Creation {
+                asset_id: Self::env().account_id(),
+                asset_name: asset_name.clone(),
+                creator: Self::env().caller(),
+                total,
+                asset_symbol,
+                build_info: build_info(source_commitment),
+            });
+
+            // handle balance of creator and reserve address
+            // if reserve address is not provided, creator is the reserve address
+            let reserve_id = reserve.unwrap_or_else(|| Self::env().caller());
+
+            let now = Some(Self::env().block_timestamp());
+            let opted_in = HolderState {
+                opted_in: true,
+                opted_in_at: now,
+                last_activity: now,
+                ..Default::default()
+            };
+
+            // the reserve starts accruing its time-weighted balance from the creation block
+            let mut holders = Mapping::default();
+            holders.insert(
+                reserve_id,
+                &HolderState {
+                    balance: total,
+                    last_update_block: Self::env().block_number(),
+                    ..opted_in
+                },
+            );
+            let mut holder_accounts = Mapping::default();
+            let mut holder_positions = Mapping::default();
+            let mut opted_in_count: u64 = 0;
+            holder_accounts.insert(opted_in_count, &reserve_id);
+            holder_positions.insert(reserve_id, &opted_in_count);
+            opted_in_count += 1;
+
+            // opt in the creator too, so it can receive the supply back (see is_destroyable)
+            let creator = Self::env().caller();
+            if creator != reserve_id {
+                holders.insert(creator, &opted_in);
+                holder_accounts.insert(opted_in_count, &creator);
+                holder_positions.insert(creator, &opted_in_count);
+                opted_in_count += 1;
+            }
+
+            // register the mirror asset, holding the supply in the reserve like this one
+            let mirror_asset_id =
+                Self::register_mirror(&asset_name, &unit_name, decimals, reserve_id, total)?;
+
+            // initialize asset params
+            let mut asset = Self {
+                creator: Self::env().caller(),
+                asset_name,
+                unit_name,
+                total,
+                decimals,
+                default_frozen,
+                url,
+                metadata_hash,
+                fingerprint,
+                min_opt_in_balance,
+                wipe_requires_freeze,
+                strict_url,
+                mutable_url,
+                verifier,
+                asset_symbol,
+                clawback_sunset,
+                source_commitment,
+                enforce_role_separation,
+                manager_id: manager.unwrap_or_else(zero_account),
+                reserve_id,
+                freeze_id: freeze.unwrap_or_else(zero_account),
+                clawback_id: clawback.unwrap_or_else(zero_account),
+                mirror_asset_id,
+                pending_manager: None,
+                verified: false,
+                verification_uri: Vec::new(),
+                transfer_hook: None,
+                entered: Lazy::default(),
+                asset_version: 0,
+                spend_limit: 0,
+                secondary_transfers_frozen: false,
+                voting_formula: VotingFormula::Linear,
+                clone_count: 0,
+                reserve_proof: None,
+                reserve_proof_block: 0,
+                inactivity_threshold: 0,
+                transfer_fee_bps: 0,
+                burn_on_transfer_bps,
+                burned: 0,
+                fee_token: None,
+                fee_exempt: Mapping::default(),
+                whitelist: Mapping::default(),
+                whitelist_count: 0,
+                blacklist: Mapping::default(),
+                jurisdiction_codes: Mapping::default(),
+                tax_rates: Mapping::default(),
+                royalty_splits: Vec::new(),
+                opt_in_fee: 0,
+                opt_in_fees_collected: 0,
+                subscription_fee: 0,
+                subscription_period_blocks: 0,
+                subscription_expiry: Mapping::default(),
+                cooldown_blocks: 0,
+                last_sent_block: Mapping::default(),
+                spend_period: 0,
+                clawback_period_cap: 0,
+                clawback_period_blocks: 0,
+                clawback_used_this_period: 0,
+                clawback_period_start: 0,
+                asset_status: if start_suspended {
+                    AssetStatus::Pending
+                } else {
+                    AssetStatus::Active
+                },
+                holders,
+                opted_in_count,
+                holder_accounts,
+                holder_positions,
+                lifetime_transfers: 0,
+                supply_events: Mapping::default(),
+                supply_event_count: 0,
+                admin_actions: Mapping::default(),
+                admin_action_count: 0,
+                opt_in_nonces: Mapping::default(),
+                sponsors: Mapping::default(),
+                lifetime_totals: Mapping::default(),
+                freeze_history: Mapping::default(),
+                freeze_expiries: Mapping::default(),
+                freeze_deadlines: Mapping::default(),
+                partially_frozen: Mapping::default(),
+                liens: Mapping::default(),
+                liened: Mapping::default(),
+                freeze_exempt: Mapping::default(),
+                drip_election: Mapping::default(),
+                appeals: Mapping::default(),
+                allowed_destinations: Mapping::default(),
+                lock_entries: Mapping::default(),
+                next_lock_id: 0,
+                pending_transfers: Mapping::default(),
+                pending_ids: Mapping::default(),
+                next_pending_id: 0,
+                pending_total: 0,
+                committed_transfers: Mapping::default(),
+                next_commitment_id: 0,
+                committed_total: 0,
+                scheduled_transfers: Mapping::default(),
+                next_scheduled_id: 0,
+                scheduled_total: 0,
+                large_transfer_threshold: 0,
+                large_transfer_pending: Mapping::default(),
+                next_large_transfer_id: 0,
+                large_transfer_total: 0,
+                streams: Mapping::default(),
+                next_stream_id: 0,
+                streamed_total: 0,
+                kyc_required,
+                kyc_status: Mapping::default(),
+                account_metadata: Mapping::default(),
+                distributors: Mapping::default(),
+                nft_owner: (total == 1).then_some(reserve_id),
+            };
+
+            // seed the initial distribution from the reserve
+            asset.seed_holdings(&initial_distribution)?;
+
+            Ok(asset)
+        }
+
+        /// Returns the asset name.
+        #[ink(message)]
+        pub fn asset_name(&self) -> String {
+            self.asset_name.clone()
+        }
+
+        /// Returns the asset unit name.
+        #[ink(message)]
+        pub fn unit_name(&self) -> String {
+            self.unit_name.clone()
+        }
+
+        /// Returns the balance of `account` formatted for display, e.g. "12.340000 TSSA".
+        /// Note: None if the account has not opted in, or if 10^decimals does not fit in a
+        /// Balance; meant for UIs, integrators should format balance_of themselves.
+        #[ink(message)]
+        pub fn display_balance_of(&self, account: AccountId) -> Option<String> {
+            let holder = self.holder(account);
+            if !holder.opted_in || decimals_factor(self.decimals).is_err() {
+                return None;
+            }
+
+            Some(format_amount(
+                holder.balance,
+                self.decimals,
+                &self.unit_name,
+            ))
+        }
+
+        /// Returns the total supply of the asset.
+        #[ink(message)]
+        pub fn total(&self) -> Balance {
+            self.total
+        }
+
+        /// Returns the current supply of the asset, i.e. the total supply less the units burnt
+        /// on transfers.
+        #[ink(message)]
+        pub fn current_supply(&self) -> Balance {
+            self.total - self.burned
+        }
+
+        /// Returns the units burnt on transfers so far.
+        #[ink(message)]
+        pub fn burned(&self) -> Balance {
+            self.burned
+        }
+
+        /// Returns the share of every transfer that is burnt, in basis points; 0 means none.
+        #[ink(message)]
+        pub fn burn_on_transfer_bps(&self) -> u16 {
+            self.burn_on_transfer_bps
+        }
+
+        /// Returns the number of decimals used to display the asset.
+        #[ink(message)]
+        pub fn decimals(&self) -> u32 {
+            self.decimals
+        }
+
+        /// Returns 10^decimals, the number of base units in a whole unit of the asset.
+        #[ink(message)]
+        pub fn decimals_factor(&self) -> Result<Balance, Error> {
+            decimals_factor(self.decimals)
+        }
+
+        /// Returns `whole` units plus `fractional` base units of the asset in base units.
+        /// Note: e.g. 2 units and 500_000 base units are 2_500_000 base units with 6 decimals.
+        #[ink(message)]
+        pub fn to_base_units(&self, whole: u128, fractional: u128) -> Result<Balance, Error> {
+            to_base_units(self.decimals, whole, fractional)
+        }
+
+        /// Splits `amount` base units of the asset into whole units and fractional base units.
+        // Note: it reads the decimals of the asset, despite the `from_` prefix
+        #[allow(clippy::wrong_self_convention)]
+        #[ink(message)]
+        pub fn from_base_units(&self, amount: Balance) -> (u128, u128) {
+            from_base_units(self.decimals, amount)
+        }
+
+        /// Returns whether the asset is frozen by default.
+        #[ink(message)]
+        pub fn default_frozen(&self) -> bool {
+            self.default_frozen
+        }
+
+        /// Returns the URL of the asset.
+        #[ink(message)]
+        pub fn url(&self) -> String {
+            self.url.clone()
+        }
+
+        /// Returns the scheme of the asset URL, e.g. "https", if it has one.
+        #[ink(message)]
+        pub fn url_scheme(&self) -> Option<String> {
+            scheme_of(&self.url).map(String::from)
+        }
+
+        /// Returns whether the asset URL must have one of the allowed schemes.
+        #[ink(message)]
+        pub fn strict_url(&self) -> bool {
+            self.strict_url
+        }
+
+        /// Returns whether the manager can change the asset URL.
+        #[ink(message)]
+        pub fn mutable_url(&self) -> bool {
+            self.mutable_url
+        }
+
+        /// Returns the latest reserve proof root and the block it was published at, if any.
+        #[ink(message)]
+        pub fn reserve_proof(&self) -> Option<([u8; 32], BlockNumber)> {
+            self.reserve_proof
+                .map(|root| (root, self.reserve_proof_block))
+        }
+
+        /// Returns whether `proof` proves that the reserve proof includes (`account`, `amount`).
+        /// Note: `proof` lists the sibling of each node from the leaf up to the root, see
+        /// `reserve_proof_leaf` and `reserve_proof_parent`.
+        #[ink(message)]
+        pub fn verify_reserve_proof(
+            &self,
+            account: AccountId,
+            amount: Balance,
+            proof: Vec<[u8; 32]>,
+        ) -> Result<bool, Error> {
+            let root = self.reserve_proof.ok_or(Error::NoReserveProof)?;
+            let node = proof
+                .into_iter()
+                .fold(reserve_proof_leaf(account, amount), reserve_proof_parent);
+            Ok(node == root)
+        }
+
+        /// Returns the registry authority that can attest the asset, if any.
+        #[ink(message)]
+        pub fn verifier(&self) -> Option<AccountId> {
+            self.verifier
+        }
+
+        /// Returns whether the verifier attests the asset as verified.
+        #[ink(message)]
+        pub fn is_verified(&self) -> bool {
+            self.verified
+        }
+
+        /// Returns the URI of the verifier's attestation, empty if unset.
+        #[ink(message)]
+        pub fn verification_uri(&self) -> Vec<u8> {
+            self.verification_uri.clone()
+        }
+
+        /// Returns the metadata hash of the asset.
+        #[ink(message)]
+        pub fn metadata_hash(&self) -> [u8; 4] {
+            self.metadata_hash
+        }
+
+        /// Returns the zero-padded symbol of the asset, all zeroes if it has none.
+        #[ink(message)]
+        pub fn asset_symbol(&self) -> [u8; 12] {
+            self.asset_symbol
+        }
+
+        /// Returns the asset ID.
+        /// Note: the asset ID is the address of the contract.
+        #[ink(message)]
+        pub fn asset_id(&self) -> AccountId {
+            self.env().account_id()
+        }
+
+        /// Returns the version of the mutable asset params.
+        /// Note: starts at 0 and is incremented on every change of the manager, reserve,
+        /// freeze or clawback address, so off-chain caches can detect stale configurations.
+        #[ink(message)]
+        pub fn asset_version(&self) -> u32 {
+            self.asset_version
+        }
+
+        // Returns the creator address.
+        #[ink(message)]
+        pub fn creator_id(&self) -> AccountId {
+            self.creator
+        }
+
+        /// Returns the crate, storage and ink! versions the contract was built with, and the
+        /// commitment to its source revision given at creation, if any.
+        #[ink(message)]
+        pub fn build_info(&self) -> BuildInfo {
+            build_info(self.source_commitment)
+        }
+
+        /// Returns the fingerprint of the creation params, to tell deployments of an asset apart.
+        /// Note: computed once by the constructor, see `compute_fingerprint`.
+        #[ink(message)]
+        pub fn fingerprint(&self) -> [u8; 32] {
+            self.fingerprint
+        }
+
+        /// Returns the manager address.
+        #[ink(message)]
+        pub fn manager_id(&self) -> AccountId {
+            self.manager_id
+        }
+
+        /// Returns the pallet-assets id of the mirror asset, with the `runtime-mirror` feature.
+        #[ink(message)]
+        pub fn mirror_asset_id(&self) -> Option<u32> {
+            self.mirror_asset_id
+        }
+
+        /// Returns the manager proposed by the current one, if any.
+        #[ink(message)]
+        pub fn pending_manager(&self) -> Option<AccountId> {
+            self.pending_manager
+        }
+
+        /// Returns the reserve address.
+        #[ink(message)]
+        pub fn reserve_id(&self) -> AccountId {
+            self.reserve_id
+        }
+
+        /// Returns the freeze address.
+        #[ink(message)]
+        pub fn freeze_id(&self) -> AccountId {
+            self.freeze_id
+        }
+
+        /// Returns the clawback address.
+        #[ink(message)]
+        pub fn clawback_id(&self) -> AccountId {
+            self.clawback_id
+        }
+
+        /// Returns whether the manager, freeze and clawback roles must be held by distinct
+        /// accounts.
+        #[ink(message)]
+        pub fn role_policy(&self) -> bool {
+            self.enforce_role_separation
+        }
+
+        /// Returns the time after which the clawback role expires, if any.
+        #[ink(message)]
+        pub fn clawback_sunset(&self) -> Option<Timestamp> {
+            self.clawback_sunset
+        }
+
+        /// Returns whether the clawback address can still revoke, i.e. is set and not past its
+        /// sunset.
+        #[ink(message)]
+        pub fn is_clawback_active(&self) -> bool {
+            !self.role_cleared(Role::Clawback)
+        }
+
+        /// Returns whether `wipe` only applies to frozen accounts.
+        #[ink(message)]
+        pub fn wipe_requires_freeze(&self) -> bool {
+            self.wipe_requires_freeze
+        }
+
+        /// Returns the minimum amount `opt_in_and_fund` can fund a new account with.
+        #[ink(message)]
+        pub fn min_opt_in_balance(&self) -> Balance {
+            self.min_opt_in_balance
+        }
+
+        /// Returns the lifecycle state of the asset.
+        #[ink(message)]
+        pub fn asset_status(&self) -> AssetStatus {
+            self.asset_status
+        }
+
+        /// Returns whether the asset is active, i.e. transfers and opt-ins are allowed.
+        #[ink(message)]
+        pub fn is_active(&self) -> bool {
+            self.asset_status == AssetStatus::Active
+        }
+
+        /// Returns the unminted supply, i.e. the balance of the current reserve address.
+        /// Note: when the reserve changes, the old reserve keeps its balance (as in Algorand),
+        /// so this only counts what the current reserve holds.
+        #[ink(message)]
+        pub fn unminted_supply(&self) -> Balance {
+            self.holder(self.reserve_id).balance
+        }
+
+        /// Returns the circulating supply, i.e. the units neither held by the current reserve nor
+        /// burnt.
+        #[ink(message)]
+        pub fn circulating_supply(&self) -> Balance {
+            self.current_supply() - self.unminted_supply()
+        }
+
+        /// Returns whether the current reserve has released all of the supply.
+        #[ink(message)]
+        pub fn is_fully_distributed(&self) -> bool {
+            self.unminted_supply() == 0
+        }
+
+        /// Returns the share of the supply still held by the current reserve, in basis points.
+        /// Note: rounds down, and is 0 for an asset without supply.
+        #[ink(message)]
+        pub fn reserve_fraction_bps(&self) -> u32 {
+            let (mut reserve, mut total) = (self.unminted_supply(), self.total);
+            if total == 0 {
+                return 0;
+            }
+            // scale both down when the product could overflow
+            if total > Balance::MAX / 10_000 {
+                (reserve, total) = (reserve / 10_000, total / 10_000);
+            }
+            (reserve * 10_000 / total) as u32
+        }
+
+        /// Returns the balance of `account`.
+        /// Note: if the account has not opted in to this asset, NotOptedIn is returned.
+        #[ink(message)]
+        pub fn balance_of(&self, account: AccountId) -> Result<Balance, Error> {
+            let holder = self.holder(account);
+            if !holder.opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            Ok(holder.balance)
+        }
+
+        /// Returns whether `account` holds at least `min_balance`.
+        /// Note: accounts that have not opted in are treated as holding 0, so that callers
+        /// such as bridge validators need not handle NotOptedIn.
+        #[ink(message)]
+        pub fn verify_balance(
+            &self,
+            account: AccountId,
+            min_balance: Balance,
+        ) -> Result<bool, Error> {
+            Ok(self.balance_of(account).unwrap_or(0) >= min_balance)
+        }
+
+        /// Returns whether `account` can only send to its allowed destinations.
+        #[ink(message)]
+        pub fn transfers_restricted(&self, account: AccountId) -> bool {
+            self.holder(account).transfers_restricted
+        }
+
+        /// Returns whether `account` elected to reinvest its dividends.
+        #[ink(message)]
+        pub fn drip_elected(&self, account: AccountId) -> bool {
+            self.drip_election.get(account).unwrap_or(false)
+        }
+
+        /// Returns up to `limit` allowed destinations of `account`, starting at `offset`.
+        #[ink(message)]
+        pub fn allowed_destinations(
+            &self,
+            account: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<AccountId> {
+            self.allowed_destinations
+                .get(account)
+                .unwrap_or_default()
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        /// Returns the spend limit and its period; a limit of 0 means there is none.
+        #[ink(message)]
+        pub fn spend_limit(&self) -> (Balance, Timestamp) {
+            (self.spend_limit, self.spend_period)
+        }
+
+        /// Returns whether only the reserve can transfer.
+        #[ink(message)]
+        pub fn secondary_transfers_frozen(&self) -> bool {
+            self.secondary_transfers_frozen
+        }
+
+        /// Returns how `voting_power` weighs balances.
+        #[ink(message)]
+        pub fn voting_formula(&self) -> VotingFormula {
+            self.voting_formula
+        }
+
+        /// Returns the voting power of `account`: its balance, weighed by the voting formula.
+        /// Note: 0 for an account that has not opted in; escrowed tokens do not count.
+        #[ink(message)]
+        pub fn voting_power(&self, account: AccountId) -> Balance {
+            let balance = self.holder(account).balance;
+            match self.voting_formula {
+                VotingFormula::Linear => balance,
+                VotingFormula::Quadratic => {
+                    // balance * balance / base, split so that the products only saturate when
+                    // the result does not fit either
+                    let base = decimals_factor(self.decimals).unwrap_or(Balance::MAX);
+                    (balance / base)
+                        .saturating_mul(balance)
+                        .saturating_add((balance % base).saturating_mul(balance) / base)
+                }
+                VotingFormula::SquareRoot => integer_sqrt(balance),
+            }
+        }
+
+        /// Returns the transfer fee in basis points; a fee of 0 means there is none.
+        #[ink(message)]
+        pub fn transfer_fee_bps(&self) -> u16 {
+            self.transfer_fee_bps
+        }
+
+        /// Returns the PSP22 token the transfer fee is paid in, if not this asset.
+        #[ink(message)]
+        pub fn fee_token(&self) -> Option<AccountId> {
+            self.fee_token
+        }
+
+        /// Returns the recipients of a share of every transfer.
+        #[ink(message)]
+        pub fn royalty_splits(&self) -> Vec<RoyaltySplit> {
+            self.royalty_splits.clone()
+        }
+
+        /// Returns the jurisdiction code of `account`, if any.
+        #[ink(message)]
+        pub fn jurisdiction_of(&self, account: AccountId) -> Option<[u8; 2]> {
+            self.jurisdiction_codes.get(account)
+        }
+
+        /// Returns the tax rate on transfers from the `from_code` to the `to_code` jurisdiction,
+        /// in basis points.
+        #[ink(message)]
+        pub fn tax_rate(&self, from_code: [u8; 2], to_code: [u8; 2]) -> u32 {
+            self.tax_rates.get((from_code, to_code)).unwrap_or(0)
+        }
+
+        /// Returns whether `account` is exempt from transfer fees.
+        #[ink(message)]
+        pub fn is_fee_exempt(&self, account: AccountId) -> bool {
+            self.fee_exempt.get(account).unwrap_or(false)
+        }
+
+        /// Returns whether `account` is on the whitelist.
+        #[ink(message)]
+        pub fn is_whitelisted(&self, account: AccountId) -> bool {
+            self.whitelist.contains(account)
+        }
+
+        /// Returns whether `account` is on the blacklist.
+        #[ink(message)]
+        pub fn is_blacklisted(&self, account: AccountId) -> bool {
+            self.blacklist.contains(account)
+        }
+
+        /// Returns whether `account` is exempt from account and secondary freezes.
+        #[ink(message)]
+        pub fn is_freeze_exempt(&self, account: AccountId) -> bool {
+            self.freeze_exempt.get(account).unwrap_or(false)
+        }
+
+        /// Returns the clawback cap and its period in blocks; a cap of 0 means there is none.
+        #[ink(message)]
+        pub fn clawback_cap(&self) -> (Balance, BlockNumber) {
+            (self.clawback_period_cap, self.clawback_period_blocks)
+        }
+
+        /// Returns the transfer hook, if any.
+        #[ink(message)]
+        pub fn transfer_hook(&self) -> Option<AccountId> {
+            self.transfer_hook
+        }
+
+        /// Returns the time locks of `account`, including expired ones not removed yet.
+        #[ink(message)]
+        pub fn locks_of(&self, account: AccountId) -> Vec<LockEntry> {
+            self.lock_entries.get(account).unwrap_or_default()
+        }
+
+        /// Returns the pending transfer `id`, if it is neither claimed nor cancelled.
+        #[ink(message)]
+        pub fn pending(&self, id: u64) -> Option<PendingTransfer> {
+            self.pending_transfers.get(id)
+        }
+
+        /// Returns a page of the pending transfers to `receiver`, oldest first.
+        #[ink(message)]
+        pub fn pending_for(
+            &self,
+            receiver: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<PendingTransfer> {
+            self.pending_ids
+                .get(receiver)
+                .unwrap_or_default()
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .filter_map(|id| self.pending_transfers.get(id))
+                .collect()
+        }
+
+        /// Returns whether `account` can distribute from the current reserve.
+        #[ink(message)]
+        pub fn is_distributor(&self, account: AccountId) -> bool {
+            self.distributors.contains((self.reserve_id, account))
+        }
+
+        /// Returns the amount escrowed by pending transfers.
+        #[ink(message)]
+        pub fn pending_supply(&self) -> Balance {
+            self.pending_total
+        }
+
+        /// Returns the committed transfer `id`, if it is neither revealed nor cancelled.
+        #[ink(message)]
+        pub fn committed_transfer(&self, id: u64) -> Option<CommittedTransfer> {
+            self.committed_transfers.get(id)
+        }
+
+        /// Returns the amount escrowed by committed transfers.
+        #[ink(message)]
+        pub fn committed_supply(&self) -> Balance {
+            self.committed_total
+        }
+
+        /// Returns the scheduled transfer `id`, if it is neither executed nor cancelled.
+        #[ink(message)]
+        pub fn scheduled_transfer(&self, id: u64) -> Option<ScheduledTransfer> {
+            self.scheduled_transfers.get(id)
+        }
+
+        /// Returns the amount escrowed by scheduled transfers.
+        #[ink(message)]
+        pub fn scheduled_supply(&self) -> Balance {
+            self.scheduled_total
+        }
+
+        /// Returns the amount above which transfers need the manager's approval, 0 if none.
+        #[ink(message)]
+        pub fn large_transfer_threshold(&self) -> Balance {
+            self.large_transfer_threshold
+        }
+
+        /// Returns the large transfer `id`, if it is neither approved nor cancelled.
+        #[ink(message)]
+        pub fn large_transfer(&self, id: u64) -> Option<LargeTransfer> {
+            self.large_transfer_pending.get(id)
+        }
+
+        /// Returns the amount escrowed by large transfers.
+        #[ink(message)]
+        pub fn large_transfer_supply(&self) -> Balance {
+            self.large_transfer_total
+        }
+
+        /// Returns the stream `id`, if it is neither cancelled nor fully withdrawn.
+        #[ink(message)]
+        pub fn stream(&self, id: u64) -> Option<Stream> {
+            self.streams.get(id)
+        }
+
+        /// Returns the amount of the stream `id` currently due to `who`: what accrued and is not
+        /// withdrawn yet for its recipient, what did not accrue yet for the reserve.
+        /// Note: 0 for any other account, or if the stream does not exist.
+        #[ink(message)]
+        pub fn balance_in_stream(&self, id: u64, who: AccountId) -> Balance {
+            let Some(stream) = self.streams.get(id) else {
+                return 0;
+            };
+            let accrued = self.accrued_in_stream(&stream);
+            if who == stream.recipient {
+                accrued - stream.withdrawn
+            } else if who == self.reserve_id {
+                stream.deposit - accrued
+            } else {
+                0
+            }
+        }
+
+        /// Returns the amount escrowed by streams.
+        #[ink(message)]
+        pub fn streamed_supply(&self) -> Balance {
+            self.streamed_total
+        }
+
+        /// Returns the balance of `account` that is neither under an unexpired time lock,
+        /// partially frozen, nor under a lien.
+        #[ink(message)]
+        pub fn available_balance(&self, account: AccountId) -> Balance {
+            let now = self.env().block_number();
+            let locked = self
+                .locks_of(account)
+                .iter()
+                .filter(|lock| lock.expires_at > now)
+                .fold(0 as Balance, |sum, lock| sum.saturating_add(lock.amount));
+            self.holder(account)
+                .balance
+                .saturating_sub(locked)
+                .saturating_sub(self.partially_frozen_balance_of(account))
+                .saturating_sub(self.liened_balance_of(account))
+        }
+
+        /// Returns the part of the balance of `account` frozen by the freeze address.
+        #[ink(message)]
+        pub fn partially_frozen_balance_of(&self, account: AccountId) -> Balance {
+            self.partially_frozen.get(account).unwrap_or(0)
+        }
+
+        /// Returns the balance of `borrower` encumbered for `lender`.
+        #[ink(message)]
+        pub fn lien(&self, borrower: AccountId, lender: AccountId) -> Balance {
+            self.liens.get((borrower, lender)).unwrap_or(0)
+        }
+
+        /// Returns the balance of `account` encumbered by liens, for all lenders.
+        #[ink(message)]
+        pub fn liened_balance_of(&self, account: AccountId) -> Balance {
+            self.liened.get(account).unwrap_or(0)
+        }
+
+        /// Returns whether `account` has opted in and, if so, its balance and frozen status.
+        /// Note: answers is_opted_in, balance_of and is_frozen in a single call.
+        #[ink(message)]
+        pub fn holding_status(&self, account: AccountId) -> HoldingStatus {
+            let holder = self.holder(account);
+            if !holder.opted_in {
+                return HoldingStatus::NotOptedIn;
+            }
+
+            HoldingStatus::OptedIn {
+                balance: holder.balance,
+                frozen: self.effectively_frozen(account, &holder),
+            }
+        }
+
+        /// Returns whether `account` is frozen.
+        /// Note: a freeze whose `freeze_until` time has passed no longer counts.
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> Result<bool, Error> {
+            Ok(self.effectively_frozen(account, &self.holder(account)))
+        }
+
+        /// Returns the freeze history of `account`, oldest entry first.
+        /// Note: only the last 20 freezes and unfreezes are kept.
+        #[ink(message)]
+        pub fn freeze_history_of(&self, account: AccountId) -> Vec<FreezeEvent> {
+            self.freeze_history.get(account).unwrap_or_default()
+        }
+
+        /// Returns the block from which the freeze of `account` can be released, if it has one.
+        #[ink(message)]
+        pub fn freeze_expiry(&self, account: AccountId) -> Option<BlockNumber> {
+            self.freeze_expiries.get(account)
+        }
+
+        /// Returns the time from which the freeze of `account` no longer applies, if it has one.
+        #[ink(message)]
+        pub fn freeze_expires_at(&self, account: AccountId) -> Option<Timestamp> {
+            self.freeze_deadlines.get(account)
+        }
+
+        /// Returns the latest appeal of `account` against its freeze, resolved or not.
+        #[ink(message)]
+        pub fn appeal_of(&self, account: AccountId) -> Option<FreezeAppeal> {
+            self.appeals.get(account)
+        }
+
+        /// Returns the average balance of `account` over the blocks since `since_block`.
+        /// Note: the accumulated part covers the whole holding history of `account`, so
+        /// `since_block` is expected to be at or before its first balance change.
+        #[ink(message)]
+        pub fn time_weighted_avg_balance(
+            &self,
+            account: AccountId,
+            since_block: BlockNumber,
+        ) -> Balance {
+            let now = self.env().block_number();
+            let holder = self.holder(account);
+            if since_block >= now {
+                return holder.balance;
+            }
+
+            let average = self.time_weighted_balance(&holder) / u128::from(now - since_block);
+            Balance::try_from(average).unwrap_or(Balance::MAX)
+        }
+
+        /// Returns the metadata field `key` of `account`, if set.
+        /// Note: `key` is a hash of the field name, and the value is SCALE-encoded data.
+        #[ink(message)]
+        pub fn get_account_metadata(&self, account: AccountId, key: [u8; 32]) -> Option<Vec<u8>> {
+            self.account_metadata.get((account, key))
+        }
+
+        /// Returns the lifetime sent and received amounts of `account`, with its balance.
+        /// Note: returns None if the account has never opted in.
+        #[ink(message)]
+        pub fn account_stats(&self, account: AccountId) -> Option<AccountStats> {
+            let holder = self.holder(account);
+            holder.opted_in_at?;
+            let totals = self.lifetime_totals.get(account).unwrap_or_default();
+            Some(AccountStats {
+                total_received: totals.received,
+                total_sent: totals.sent,
+                balance: holder.balance,
+            })
+        }
+
+        /// Returns when `account` last opted in, sent, received, or got revoked or (un)frozen.
+        #[ink(message)]
+        pub fn last_activity_of(&self, account: AccountId) -> Option<Timestamp> {
+            self.holder(account).last_activity
+        }
+
+        /// Returns the time without activity after which an empty account can be purged; a
+        /// threshold of 0 means purging is disabled.
+        #[ink(message)]
+        pub fn inactivity_threshold(&self) -> Timestamp {
+            self.inactivity_threshold
+        }
+
+        /// Returns the nonce `account` has to sign to be opted in through `opt_in_for`.
+        #[ink(message)]
+        pub fn opt_in_nonce(&self, account: AccountId) -> u64 {
+            self.opt_in_nonces.get(account).unwrap_or_default()
+        }
+
+        /// Returns who opted in `account` through `opt_in_for`, while it stays opted in.
+        #[ink(message)]
+        pub fn sponsor_of(&self, account: AccountId) -> Option<AccountId> {
+            self.sponsors.get(account)
+        }
+
+        /// Returns when `account` first opted in to this asset.
+        /// Note: opting out and in again keeps the original timestamp.
+        #[ink(message)]
+        pub fn opted_in_at(&self, account: AccountId) -> Option<Timestamp> {
+            self.holder(account).opted_in_at
+        }
+
+        /// Returns whether `account` has opted in to this asset.
+        #[ink(message)]
+        pub fn is_opted_in(&self, account: AccountId) -> Result<bool, Error> {
+            Ok(self.holder(account).opted_in)
+        }
+
+        /// Returns whether accounts need an approved KYC status to opt in.
+        #[ink(message)]
+        pub fn kyc_required(&self) -> bool {
+            self.kyc_required
+        }
+
+        /// Returns the KYC status of `account`.
+        /// Note: one of KYC_UNKNOWN, KYC_PENDING, KYC_APPROVED or KYC_REJECTED.
+        #[ink(message)]
+        pub fn kyc_status_of(&self, account: AccountId) -> u8 {
+            self.kyc_status.get(account).unwrap_or(KYC_UNKNOWN)
+        }
+
+        /// Returns whether the asset is an NFT, i.e. total = 1 and decimals = 0.
+        #[ink(message)]
+        pub fn is_nft(&self) -> bool {
+            self.total == 1 && self.decimals == 0
+        }
+
+        /// Returns whether the asset is a fractional NFT, i.e. total = 10^decimals with decimals > 0.
+        #[ink(message)]
+        pub fn is_fractional_nft(&self) -> bool {
+            self.decimals > 0 && Balance::checked_pow(10, self.decimals) == Some(self.total)
+        }
+
+        /// Returns the holder of the single unit of the asset.
+        /// Note: None if total != 1.
+        #[ink(message)]
+        pub fn owner_of(&self) -> Option<AccountId> {
+            self.nft_owner
+        }
+
+        /// Returns the native value an account pays to opt in; a fee of 0 means there is none.
+        #[ink(message)]
+        pub fn opt_in_fee(&self) -> Balance {
+            self.opt_in_fee
+        }
+
+        /// Returns the subscription fee and its period in blocks; a period of 0 means there is
+        /// no subscription.
+        #[ink(message)]
+        pub fn subscription_params(&self) -> (Balance, BlockNumber) {
+            (self.subscription_fee, self.subscription_period_blocks)
+        }
+
+        /// Returns the number of blocks an account must wait between transfers, 0 if none.
+        #[ink(message)]
+        pub fn cooldown_blocks(&self) -> BlockNumber {
+            self.cooldown_blocks
+        }
+
+        /// Returns the block up to which `account` can transfer, 0 if it never subscribed.
+        #[ink(message)]
+        pub fn subscription_expiry(&self, account: AccountId) -> BlockNumber {
+            self.subscription_expiry.get(account).unwrap_or(0)
+        }
+
+        /// Returns how the native balance of the contract splits into its buckets.
+        #[ink(message)]
+        pub fn native_accounting(&self) -> NativeAccounting {
+            let contract_balance = self.env().balance();
+            NativeAccounting {
+                contract_balance,
+                opt_in_fees: self.opt_in_fees_collected,
+                unattributed: contract_balance.saturating_sub(self.opt_in_fees_collected),
+                overdrawn: contract_balance < self.opt_in_fees_collected,
+            }
+        }
+
+        /// Returns the number of accounts currently opted in to this asset.
+        #[ink(message)]
+        pub fn opted_in_count(&self) -> u64 {
+            self.opted_in_count
+        }
+
+        /// Returns up to `limit` opted-in accounts, at most `MAX_STATUS_BATCH`, starting at
+        /// `offset` in the holder index, with their balance and frozen status.
+        /// Note: opting out moves the last indexed account into the freed position, so pages
+        /// are only consistent with each other, and with `export_config`, within a single
+        /// block; callers should pin a block hash while paging, see `export_full_state`.
+        #[ink(message)]
+        pub fn export_holders(&self, offset: u64, limit: u64) -> Vec<HolderExport> {
+            let end = offset
+                .saturating_add(limit.min(MAX_STATUS_BATCH as u64))
+                .min(self.opted_in_count);
+            (offset..end)
+                .filter_map(|position| self.holder_accounts.get(position))
+                .map(|account| {
+                    let holder = self.holder(account);
+                    let frozen = self.effectively_frozen(account, &holder);
+                    (account, holder.balance, frozen)
+                })
+                .collect()
+        }
+
+        /// Returns the `n` largest holders, at most `MAX_RANKING`, by balance descending, ties
+        /// broken by account.
+        /// Note: this reads every opted-in account, so its gas cost grows linearly with
+        /// `opted_in_count` whatever `n` is; prefer `export_holders` off-chain for large assets.
+        #[ink(message)]
+        pub fn top_holders(&self, n: u32) -> Result<Vec<(AccountId, Balance)>, Error> {
+            // check if the ranking is within the limit
+            if n > MAX_RANKING {
+                return Err(Error::RequestedRankingTooLarge);
+            }
+
+            let mut ranking: Vec<(AccountId, Balance)> = (0..self.opted_in_count)
+                .filter_map(|position| self.holder_accounts.get(position))
+                .map(|account| (account, self.holder(account).balance))
+                .collect();
+            let by_rank = |a: &(AccountId, Balance), b: &(AccountId, Balance)| {
+                b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))
+            };
+
+            // select the top `n` first, so only those get sorted
+            let n = n as usize;
+            if n < ranking.len() {
+                if n > 0 {
+                    ranking.select_nth_unstable_by(n - 1, by_rank);
+                }
+                ranking.truncate(n);
+            }
+            ranking.sort_unstable_by(by_rank);
+            Ok(ranking)
+        }
+
+        /// Returns the parameters and counters of the asset, at the current block.
+        #[ink(message)]
+        pub fn export_config(&self) -> AssetParams {
+            AssetParams {
+                creator: self.creator,
+                asset_name: self.asset_name.clone(),
+                unit_name: self.unit_name.clone(),
+                total: self.total,
+                decimals: self.decimals,
+                default_frozen: self.default_frozen,
+                url: self.url.clone(),
+                metadata_hash: self.metadata_hash,
+                manager: self.manager_id,
+                reserve: self.reserve_id,
+                freeze: self.freeze_id,
+                clawback: self.clawback_id,
+                asset_status: self.asset_status,
+                lifetime_transfers: self.lifetime_transfers,
+                opted_in_count: self.opted_in_count,
+                block: self.env().block_number(),
+            }
+        }
+
+        /// Returns the number of balance movements ever made, pending transfers included.
+        /// Note: each recipient of a distribution counts as a movement.
+        #[ink(message)]
+        pub fn lifetime_transfers(&self) -> u64 {
+            self.lifetime_transfers
+        }
+
+        /// Returns the number of entries of the supply audit trail.
+        #[ink(message)]
+        pub fn supply_event_count(&self) -> u32 {
+            self.supply_event_count
+        }
+
+        /// Returns the entry of the supply audit trail at `index`, oldest first.
+        #[ink(message)]
+        pub fn get_supply_event(&self, index: u32) -> Option<SupplyEvent> {
+            self.supply_events.get(index)
+        }
+
+        /// Returns the number of entries of the admin log.
+        #[ink(message)]
+        pub fn admin_action_count(&self) -> u64 {
+            self.admin_action_count
+        }
+
+        /// Returns up to `limit` entries of the admin log, oldest first, starting at `offset`.
+        #[ink(message)]
+        pub fn admin_actions(&self, offset: u64, limit: u64) -> Vec<AdminAction> {
+            let end = offset.saturating_add(limit).min(self.admin_action_count);
+            (offset..end)
+                .filter_map(|index| self.admin_actions.get(index))
+                .collect()
+        }
+
+        /// Returns, for each of `accounts`, whether it has opted in to this asset.
+        /// Note: the result has the same length and order as `accounts`.
+        #[ink(message)]
+        pub fn is_opted_in_batch(&self, accounts: Vec<AccountId>) -> Vec<bool> {
+            accounts
+                .into_iter()
+                .map(|account| self.holder(account).opted_in)
+                .collect()
+        }
+
+        /// Returns, for each of `accounts`, whether it is frozen.
+        /// Note: the result has the same length and order as `accounts`.
+        #[ink(message)]
+        pub fn is_frozen_batch(&self, accounts: Vec<AccountId>) -> Vec<bool> {
+            accounts
+                .into_iter()
+                .map(|account| self.effectively_frozen(account, &self.holder(account)))
+                .collect()
+        }
+
+        /// Returns the holding status of each of `accounts`, as `holding_status` would.
+        /// Note: the result has the same length and order as `accounts`; fails with
+        /// TooManyAccounts above `MAX_STATUS_BATCH` accounts.
+        #[ink(message)]
+        pub fn statuses_of(&self, accounts: Vec<AccountId>) -> Result<Vec<HoldingStatus>, Error> {
+            if accounts.len() > MAX_STATUS_BATCH {
+                return Err(Error::TooManyAccounts);
+            }
+
+            Ok(accounts
+                .into_iter()
+                .map(|account| self.holding_status(account))
+                .collect())
+        }
+
+        /// Returns wheter `creator's balance is equal to total supply.
+        /// Note: an asset can only be destroyed if the creator's balance is equal to the total supply.
+        #[ink(message)]
+        pub fn is_destroyable(&self) -> bool {
+            self.holder(self.creator).balance == self.total
+        }
+
+        /// Transfer `amount` of tokens from `sender` to `receiver`.
+        #[ink(message)]
+        pub fn transfer(&mut self, receiver: AccountId, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_transfer(caller, receiver, amount))
+        }
+
+        /// Escrow `amount` of tokens for `receiver` until `expiry`, returning the pending transfer id
+        // Note: the receiver does not have to be opted in, only to claim the tokens
+        // Note: transfer checks apply as for a transfer to `receiver`
+        #[ink(message)]
+        pub fn send_pending(
+            &mut self,
+            receiver: AccountId,
+            amount: Balance,
+            expiry: Timestamp,
+        ) -> Result<u64, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_send_pending(caller, receiver, amount, expiry))
+        }
+
+        /// Claim the pending transfer `id`
+        // Note: only its receiver can claim it, once opted in and before it expires
+        #[ink(message)]
+        pub fn claim_pending(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_claim_pending(caller, id))
+        }
+
+        /// Cancel the pending transfer `id`, refunding its sender
+        // Note: its sender can cancel it at any time, anyone else once it expired
+        #[ink(message)]
+        pub fn cancel_pending(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_cancel_pending(caller, id))
+        }
+
+        /// Escrow `amount` of tokens for a transfer to `receiver` that anyone can execute from
+        /// `execute_after`, returning the scheduled transfer id
+        // Note: the receiver does not have to be opted in, only when the transfer executes
+        // Note: transfer checks apply as for a transfer to `receiver`
+        #[ink(message)]
+        pub fn schedule_transfer(
+            &mut self,
+            receiver: AccountId,
+            amount: Balance,
+            execute_after: Timestamp,
+        ) -> Result<u64, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| {
+                asset.do_schedule_transfer(caller, receiver, amount, execute_after)
+            })
+        }
+
+        /// Escrow `deposit` from the reserve into a stream to `recipient`, accruing linearly
+        /// from `start` to `stop`, returning the stream id
+        // Note: only the reserve can create streams, to opted-in recipients
+        #[ink(message)]
+        pub fn create_stream(
+            &mut self,
+            recipient: AccountId,
+            deposit: Balance,
+            start: Timestamp,
+            stop: Timestamp,
+        ) -> Result<u64, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| {
+                asset.do_create_stream(caller, recipient, deposit, start, stop)
+            })
+        }
+
+        /// Withdraw `amount` of what accrued in the stream `id`
+        // Note: only the recipient can withdraw, while it is not frozen
+        #[ink(message)]
+        pub fn withdraw_from_stream(&mut self, id: u64, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_withdraw_from_stream(caller, id, amount))
+        }
+
+        /// Cancel the stream `id`, sending what accrued to its recipient and the rest back to
+        /// the reserve
+        // Note: only the reserve or the recipient can cancel a stream
+        #[ink(message)]
+        pub fn cancel_stream(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_cancel_stream(caller, id))
+        }
+
+        /// Execute the scheduled transfer `id`, crediting its receiver
+        // Note: anyone can execute it once its time has come, if the receiver has opted in
+        // and is not frozen
+        #[ink(message)]
+        pub fn execute_scheduled(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_execute_scheduled(caller, id))
+        }
+
+        /// Cancel the scheduled transfer `id`, refunding its sender
+        // Note: only its sender can cancel it, until it is executed
+        #[ink(message)]
+        pub fn cancel_scheduled(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_cancel_scheduled(caller, id))
+        }
+
+        /// Require the manager's approval for transfers above `threshold`
+        // Note: only the manager can set the threshold, and 0 disables it
+        // Note: the reserve is not subject to the threshold
+        #[ink(message)]
+        pub fn set_large_transfer_threshold(&mut self, threshold: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_large_transfer_threshold(caller, threshold))
+        }
+
+        /// Escrow `amount` of tokens, above the threshold, for a transfer to `to` that the
+        /// manager has to approve, returning the large transfer id
+        // Note: transfer checks apply as for a transfer to `to`
+        #[ink(message)]
+        pub fn initiate_large_transfer(
+            &mut self,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<u64, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_initiate_large_transfer(caller, to, amount))
+        }
+
+        /// Approve the large transfer `id`, crediting its receiver
+        // Note: only the manager can approve it, if the receiver is opted in and not frozen
+        #[ink(message)]
+        pub fn approve_large_transfer(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_approve_large_transfer(caller, id))
+        }
+
+        /// Cancel the large transfer `id`, refunding its sender
+        // Note: only its sender can cancel it, until it is approved
+        #[ink(message)]
+        pub fn cancel_large_transfer(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_cancel_large_transfer(caller, id))
+        }
+
+        /// Escrow up to `max_amount` of tokens for a transfer to `receiver` whose amount only
+        /// `commitment` reveals, until `expiry_block`, returning the committed transfer id
+        // Note: `commitment` is the `transfer_commitment` of the amount and a secret salt
+        // Note: transfer checks apply as for a transfer of `max_amount` to `receiver`
+        #[ink(message)]
+        pub fn commit_transfer(
+            &mut self,
+            commitment: [u8; 32],
+            receiver: AccountId,
+            expiry_block: BlockNumber,
+            max_amount: Balance,
+        ) -> Result<u64, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| {
+                asset.do_commit_transfer(caller, commitment, receiver, expiry_block, max_amount)
+            })
+        }
+
+        /// Reveal the committed transfer `id`, sending `amount` to its receiver and the excess
+        /// back to the sender
+        // Note: only its sender can reveal it, before it expires, and the receiver must have
+        // opted in
+        #[ink(message)]
+        pub fn reveal_transfer(
+            &mut self,
+            id: u64,
+            amount: Balance,
+            salt: [u8; 32],
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_reveal_transfer(caller, id, amount, salt))
+        }
+
+        /// Cancel the committed transfer `id`, refunding its sender
+        // Note: its sender can cancel it at any time, anyone else once it expired
+        #[ink(message)]
+        pub fn cancel_committed_transfer(&mut self, id: u64) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_cancel_committed_transfer(caller, id))
+        }
+
+        /// Execute several messages in order on behalf of the caller, all or nothing
+        // Note: at most 16 calls; the error of the first failing call is returned
+        // Note: the message reverts on an error, undoing the calls already made
+        #[ink(message)]
+        pub fn multicall(&mut self, calls: Vec<SubsaCall>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_multicall(caller, calls))
+        }
+
+        /// Transfer tokens from `sender` to several receivers, all or nothing
+        // Note: every transfer must be of this asset; a called asset contract would see this
+        // contract as the sender, and cannot spend the sender's tokens on its behalf
+        // Note: the message reverts on an error, undoing the transfers already made
+        #[ink(message)]
+        pub fn bundle_transfer(&mut self, transfers: Vec<BundleTransfer>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_bundle_transfer(caller, transfers))
+        }
+
+        /// OptIn to receive an asset
+        // Note: the transferred value must cover `opt_in_fee`, the excess is refunded
+        #[ink(message, payable)]
+        pub fn opt_in(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let paid = self.env().transferred_value();
+            self.non_reentrant(|asset| asset.do_opt_in(caller, paid))
+        }
+
+        /// OptIn `account` to receive an asset on its behalf
+        // Note: `signature` is the account's 65-byte ECDSA signature over the Blake2x256 hash of
+        // the SCALE-encoded (contract id, "opt_in", `opt_in_nonce(account)`); the account id is
+        // the Blake2x256 hash of the compressed public key
+        // Note: the caller is recorded as the sponsor of the account until it opts out
+        // Note: the caller pays the opt-in fee, the excess is refunded to it
+        #[ink(message, payable)]
+        pub fn opt_in_for(&mut self, account: AccountId, signature: Vec<u8>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let paid = self.env().transferred_value();
+            self.non_reentrant(|asset| asset.do_opt_in_for(caller, account, signature, paid))
+        }
+
+        /// Renew the subscription of the caller for another period
+        // Note: the transferred value must cover `subscription_fee`, the excess is refunded
+        // Note: a lapsed subscription restarts from the current block
+        #[ink(message, payable)]
+        pub fn pay_subscription(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let paid = self.env().transferred_value();
+            self.non_reentrant(|asset| asset.do_pay_subscription(caller, paid))
+        }
+
+        /// OptOut of receiving an asset
+        #[ink(message)]
+        pub fn opt_out(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_opt_out(caller))
+        }
+
+        /// Cancel the opt-in of an account without a balance
+        #[ink(message)]
+        pub fn cancel_opt_in(&mut self, account: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_cancel_opt_in(caller, account))
+        }
+
+        /// Purge a stale account: opt out an account without a balance, not frozen, and
+        /// inactive for longer than the inactivity threshold, clearing its holder record
+        // Note: only the manager can purge accounts; the account keeps its lifetime totals
+        #[ink(message)]
+        pub fn purge_stale_account(&mut self, account: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_purge_stale_account(caller, account))
+        }
+
+        /// Set the time without activity after which an empty account can be purged, or 0 to
+        /// disable purging
+        // Note: only the manager can set the inactivity threshold
+        #[ink(message)]
+        pub fn set_inactivity_threshold(&mut self, threshold: Timestamp) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_inactivity_threshold(caller, threshold))
+        }
+
+        /// Freeze an account, until block `expiry` if given
+        // Note: anyone can release an expired freeze with `release_expired_freeze`
+        #[ink(message)]
+        pub fn freeze(
+            &mut self,
+            account: AccountId,
+            freeze: bool,
+            expiry: Option<BlockNumber>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_freeze(caller, account, freeze, expiry))
+        }
+
+        /// Freeze an account until `expires_at`, after which it no longer applies
+        // Note: only the freeze address can freeze, and the freeze lifts without any unfreeze
+        #[ink(message)]
+        pub fn freeze_until(
+            &mut self,
+            account: AccountId,
+            expires_at: Timestamp,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_freeze_until(caller, account, expires_at))
+        }
+
+        /// Unfreeze an account whose freeze expired
+        #[ink(message)]
+        pub fn release_expired_freeze(&mut self, account: AccountId) -> Result<(), Error> {
+            self.non_reentrant(|asset| asset.do_release_expired_freeze(account))
+        }
+
+        /// Freeze `amount` of the balance of an account
+        // Note: only the freeze address can partially freeze, and only available balance
+        // Note: partially frozen tokens cannot be transferred, but can still be revoked
+        #[ink(message)]
+        pub fn partial_freeze(&mut self, account: AccountId, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_partial_freeze(caller, account, amount))
+        }
+
+        /// Unfreeze `amount` of the partially frozen balance of an account
+        #[ink(message)]
+        pub fn unpartial_freeze(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_unpartial_freeze(caller, account, amount))
+        }
+
+        /// Encumber `amount` of the balance of a borrower as collateral for a lender
+        // Note: only the freeze address can create liens, and only on available balance
+        // Note: liened tokens cannot be transferred, until released or enforced
+        #[ink(message)]
+        pub fn create_lien(
+            &mut self,
+            borrower: AccountId,
+            lender: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_create_lien(caller, borrower, lender, amount))
+        }
+
+        /// Release `amount` of the lien of a borrower for a lender
+        // Note: only the freeze address can release liens, e.g. once the loan is repaid
+        #[ink(message)]
+        pub fn release_lien(
+            &mut self,
+            borrower: AccountId,
+            lender: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_release_lien(caller, borrower, lender, amount))
+        }
+
+        /// Send the tokens encumbered by the lien of a borrower to its lender
+        // Note: only the clawback address can enforce liens, as a revoke of at most the
+        // borrower's balance, so the clawback cap and sunset apply
+        #[ink(message)]
+        pub fn enforce_lien(
+            &mut self,
+            borrower: AccountId,
+            lender: AccountId,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_enforce_lien(caller, borrower, lender))
+        }
+
+        /// Appeal against the freeze of the caller's account
+        // Note: only frozen accounts can appeal, and only once their previous appeal is resolved
+        #[ink(message)]
+        pub fn submit_freeze_appeal(&mut self, reason_hash: [u8; 32]) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_submit_freeze_appeal(caller, reason_hash))
+        }
+
+        /// Resolve the appeal of `account`, unfreezing it if `grant`
+        // Note: only the freeze address can resolve appeals
+        #[ink(message)]
+        pub fn resolve_appeal(&mut self, account: AccountId, grant: bool) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_resolve_appeal(caller, account, grant))
+        }
+
+        /// Modify/Reconfigure an asset
+        // Note: only the manager can modify an asset
+        // Note: only mutable asset params can be modified
+        // List of mutable asset params:
+        // - managerId, reserveId, freezeId, clawbackId
+        // Note: `None` clears a role, and a cleared role cannot be set again
+        #[ink(message)]
+        pub fn modify_asset(
+            &mut self,
+            manager: Option<AccountId>,
+            reserve: Option<AccountId>,
+            freeze: Option<AccountId>,
+            clawback: Option<AccountId>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| {
+                asset.do_modify_asset(caller, manager, reserve, freeze, clawback)
+            })
+        }
+
+        /// Change only the roles given in `patch`, at once
+        // Note: only the manager can patch an asset, with the same checks as `modify_asset`
+        // Note: unlike `modify_asset`, roles left out of `patch` are kept, not cleared
+        #[ink(message)]
+        pub fn patch_asset(&mut self, patch: ModifyPatch) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_patch_asset(caller, patch))
+        }
+
+        /// Propose `new_manager` as the manager, which takes over once it accepts
+        // Note: only the manager can propose, replacing any earlier proposal
+        #[ink(message)]
+        pub fn propose_manager(&mut self, new_manager: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_propose_manager(caller, new_manager))
+        }
+
+        /// Accept the manager role proposed to the caller
+        #[ink(message)]
+        pub fn accept_manager(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_accept_manager(caller))
+        }
+
+        /// Cancel the pending manager proposal
+        // Note: only the manager can cancel its proposal
+        #[ink(message)]
+        pub fn cancel_manager_proposal(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_cancel_manager_proposal(caller))
+        }
+
+        /// Publish the Merkle root of the reserve asset balances backing the asset
+        // Note: only the manager can publish, and each proof replaces the previous one
+        #[ink(message)]
+        pub fn publish_reserve_proof(&mut self, root: [u8; 32]) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_publish_reserve_proof(caller, root))
+        }
+
+        /// Set the asset URL
+        // Note: only the manager can change the url, of an asset created with `mutable_url`,
+        // and a `strict_url` asset still requires one of the allowed schemes
+        #[ink(message)]
+        pub fn set_url(&mut self, new_url: String) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_url(caller, new_url))
+        }
+
+        /// Set the manager address
+        // Note: only the manager can change roles, and a cleared role cannot be set again
+        // Note: passing the zero address clears the role
+        #[ink(message)]
+        pub fn set_manager(&mut self, manager: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_role(caller, Role::Manager, manager))
+        }
+
+        /// Set the reserve address
+        // Note: only the manager can change roles, and a cleared role cannot be set again
+        // Note: the new reserve must have opted in; the old reserve keeps its balance
+        #[ink(message)]
+        pub fn set_reserve(&mut self, reserve: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_role(caller, Role::Reserve, reserve))
+        }
+
+        /// Set the freeze address
+        // Note: only the manager can change roles, and a cleared role cannot be set again
+        #[ink(message)]
+        pub fn set_freeze(&mut self, freeze: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_role(caller, Role::Freeze, freeze))
+        }
+
+        /// Set the clawback address
+        // Note: only the manager can change roles, and a cleared role cannot be set again
+        #[ink(message)]
+        pub fn set_clawback(&mut self, clawback: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_role(caller, Role::Clawback, clawback))
+        }
+
+        /// Revoke an asset
+        // Note: only the clawback address can revoke an asset
+        // Note: must specify amount, revocation target id, and receiver
+        #[ink(message)]
+        pub fn revoke_asset(
+            &mut self,
+            receiver: AccountId,
+            recovation_target: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| {
+                asset.do_revoke_asset(caller, receiver, recovation_target, amount)
+            })
+        }
+
+        /// Revoke from several targets in one call, each `(target, amount, receiver)` revoking
+        /// `amount`, or the whole balance of the target if None, and sending it to `receiver`
+        // Note: only the clawback address can revoke, up to `MAX_REVOKE_BATCH` entries
+        // Note: all revocations apply or none does, with one Revoke event per entry
+        #[ink(message)]
+        pub fn revoke_many(
+            &mut self,
+            ops: Vec<(AccountId, Option<Balance>, AccountId)>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_revoke_many(caller, ops))
+        }
+
+        /// Wipe an account: revoke its whole balance to the reserve and clear its holder record
+        // Note: only the clawback address can wipe an account, and only a frozen one if the
+        // asset was created with `wipe_requires_freeze`
+        // Note: the account is opted out, but keeps its lifetime totals
+        #[ink(message)]
+        pub fn wipe(&mut self, target: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_wipe(caller, target))
+        }
+
+        /// Set the KYC status of an account
+        // Note: only the manager can set KYC statuses
+        #[ink(message)]
+        pub fn set_kyc_status(&mut self, account: AccountId, status: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_kyc_status(caller, account, status))
+        }
+
+        /// Set a metadata field of the caller's account
+        // Note: only opted-in accounts can set metadata, and only for themselves
+        // Note: values are limited to 1024 bytes
+        #[ink(message)]
+        pub fn set_account_metadata(&mut self, key: [u8; 32], value: Vec<u8>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_account_metadata(caller, key, value))
+        }
+
+        /// Clear a metadata field of the caller's account
+        // Note: does not require the caller to be opted in, so that storage can be freed after
+        // opting out
+        #[ink(message)]
+        pub fn clear_account_metadata(&mut self, key: [u8; 32]) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_clear_account_metadata(caller, key))
+        }
+
+        /// Elect to receive dividends as more tokens instead of their cash equivalent, or not
+        // Note: only opted-in accounts can elect, for dividend distributors to read
+        #[ink(message)]
+        pub fn elect_drip(&mut self, enabled: bool) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_elect_drip(caller, enabled))
+        }
+
+        /// Restrict the caller's outbound transfers to its allowed destinations, or lift it
+        // Note: revokes and admin transfers are not restricted
+        #[ink(message)]
+        pub fn restrict_transfers(&mut self, restricted: bool) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_restrict_transfers(caller, restricted))
+        }
+
+        /// Add `destination` to the caller's allowed destinations, or remove it
+        // Note: an account can have at most 50 allowed destinations
+        #[ink(message)]
+        pub fn set_allowed_destination(
+            &mut self,
+            destination: AccountId,
+            allowed: bool,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| {
+                asset.do_set_allowed_destination(caller, destination, allowed)
+            })
+        }
+
+        /// Cap what an account can transfer out to `limit` per `period` milliseconds
+        // Note: only the manager can set the spend limit, and a limit of 0 disables it
+        // Note: the reserve and the creator are exempt, as are revokes and admin transfers
+        #[ink(message)]
+        pub fn set_spend_limit(&mut self, limit: Balance, period: Timestamp) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_spend_limit(caller, limit, period))
+        }
+
+        /// Attest the asset as verified, or not
+        // Note: only the verifier can change it, not even the manager
+        #[ink(message)]
+        pub fn set_verified(&mut self, verified: bool) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_verified(caller, verified))
+        }
+
+        /// Set the URI of the verifier's attestation, e.g. its registry entry
+        // Note: only the verifier can change it, up to 256 bytes
+        #[ink(message)]
+        pub fn set_verification_uri(&mut self, uri: Vec<u8>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_verification_uri(caller, uri))
+        }
+
+        /// Freeze or unfreeze secondary transfers, i.e. transfers not sent by the reserve
+        // Note: only the manager can freeze them, e.g. during a lock-up period
+        // Note: unlike suspending the asset or freezing accounts, reserve distributions and
+        // clawback revokes keep working
+        #[ink(message)]
+        pub fn set_secondary_freeze(&mut self, frozen: bool) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_secondary_freeze(caller, frozen))
+        }
+
+        /// Set how `voting_power` weighs balances
+        // Note: only the manager can set the voting formula
+        #[ink(message)]
+        pub fn set_voting_formula(&mut self, formula: VotingFormula) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_voting_formula(caller, formula))
+        }
+
+        /// Charge a native fee of `fee` to opt in, kept by the contract
+        // Note: only the manager can set the fee, and a fee of 0 disables it
+        // Note: opt-ins made by the reserve with opt_in_and_fund pay no fee
+        #[ink(message)]
+        pub fn set_opt_in_fee(&mut self, fee: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_opt_in_fee(caller, fee))
+        }
+
+        /// Require a native fee of `fee` per `period` blocks for accounts to transfer
+        // Note: only the manager can set the parameters, and a period of 0 disables them
+        // Note: the fees are collected with the opt-in fees, and the reserve never pays them
+        #[ink(message)]
+        pub fn set_subscription_params(
+            &mut self,
+            fee: Balance,
+            period: BlockNumber,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_subscription_params(caller, fee, period))
+        }
+
+        /// Require accounts to wait `blocks` blocks between transfers
+        // Note: only the manager can set the cooldown, and 0 disables it
+        // Note: the reserve is not subject to the cooldown, so it can distribute freely
+        #[ink(message)]
+        pub fn set_cooldown(&mut self, blocks: BlockNumber) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_cooldown(caller, blocks))
+        }
+
+        /// Withdraw `amount` of the collected opt-in fees to `to`
+        // Note: only the manager can withdraw, and only from the collected fees
+        #[ink(message)]
+        pub fn withdraw_opt_in_fees(
+            &mut self,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_withdraw_opt_in_fees(caller, to, amount))
+        }
+
+        /// Return `amount` of native value sent to the contract unsolicited to `to`
+        // Note: only the manager can recover, and only from the unattributed balance, so the
+        // collected opt-in fees stay covered
+        #[ink(message)]
+        pub fn recover_native(&mut self, to: AccountId, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_recover_native(caller, to, amount))
+        }
+
+        /// Forward `amount` of the PSP22 `token` sent to the contract by mistake to `to`
+        // Note: only the manager can recover tokens, and never this asset itself, which would
+        // bypass its own transfer rules
+        #[ink(message)]
+        pub fn recover_token(
+            &mut self,
+            token: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_recover_token(caller, token, to, amount))
+        }
+
+        /// Charge a fee of `fee_bps` basis points on transfers, paid to the reserve
+        // Note: only the manager can set the fee, up to 10%, and a fee of 0 disables it
+        // Note: the reserve and fee-exempt senders pay no fee, nor do revokes, admin transfers,
+        // distributions and pending transfers
+        #[ink(message)]
+        pub fn set_transfer_fee(&mut self, fee_bps: u16) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_transfer_fee(caller, fee_bps))
+        }
+
+        /// Collect the transfer fee in the PSP22 `token` instead of this asset, or not
+        // Note: only the manager can set the token
+        // Note: the sender then pays the fee, in units of the token, out of an allowance given
+        // to this contract, and the receiver gets the whole amount
+        #[ink(message)]
+        pub fn set_fee_token(&mut self, token: Option<AccountId>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_fee_token(caller, token))
+        }
+
+        /// Set the recipients of a share of every transfer, or clear them with an empty list
+        // Note: only the manager can set royalties, for up to 10 opted-in recipients whose
+        // shares add up to at most 100%
+        // Note: royalties are taken from transfers net of the transfer fee and tax; the reserve pays
+        // none, nor do revokes, admin transfers, distributions and pending transfers
+        #[ink(message)]
+        pub fn set_royalty_splits(&mut self, splits: Vec<RoyaltySplit>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_royalty_splits(caller, splits))
+        }
+
+        /// Exempt an account from transfer fees, or not
+        // Note: only the manager can exempt accounts, e.g. protocol-owned ones
+        #[ink(message)]
+        pub fn set_fee_exempt(&mut self, account: AccountId, exempt: bool) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_fee_exempt(caller, account, exempt))
+        }
+
+        /// Apply all the changes of `diff` to the compliance lists at once, returning how many
+        /// accounts were actually added or removed
+        // Note: only the manager can change the lists, up to `MAX_STATUS_BATCH` accounts per
+        // diff, and removals apply before additions
+        // Note: listing an account twice, or adding a listed one, changes nothing, so applying
+        // the same diff again returns 0
+        #[ink(message)]
+        pub fn apply_compliance_diff(&mut self, diff: ComplianceDiff) -> Result<u32, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_apply_compliance_diff(caller, diff))
+        }
+
+        /// Exempt an account from freezes, or not
+        // Note: only the freeze address can exempt accounts, e.g. a DEX pool or a bridge escrow
+        // settling in-flight operations during an incident
+        // Note: an exempt account can send and receive while frozen or while secondary transfers
+        // are frozen, but clawback and compliance checks still apply to it
+        #[ink(message)]
+        pub fn set_freeze_exempt(&mut self, account: AccountId, exempt: bool) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_freeze_exempt(caller, account, exempt))
+        }
+
+        /// Set the jurisdiction of an account to an ISO 3166-1 alpha-2 code, or clear it with
+        /// a zero code
+        // Note: only the manager can set jurisdictions, and codes are two uppercase letters
+        #[ink(message)]
+        pub fn set_jurisdiction(&mut self, account: AccountId, code: [u8; 2]) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_jurisdiction(caller, account, code))
+        }
+
+        /// Set the tax rate on transfers from the `from_code` to the `to_code` jurisdiction, in
+        /// basis points
+        // Note: only the manager can set tax rates, of up to 100%
+        // Note: the tax is taken from transfers net of the transfer fee between two accounts
+        // with a jurisdiction, and paid to the reserve; the reserve pays none, nor do revokes,
+        // admin transfers, distributions and pending transfers
+        #[ink(message)]
+        pub fn set_tax_rate(
+            &mut self,
+            from_code: [u8; 2],
+            to_code: [u8; 2],
+            rate_bps: u32,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_tax_rate(caller, from_code, to_code, rate_bps))
+        }
+
+        /// Cap what the clawback address can revoke to `cap` per `period_blocks` blocks
+        // Note: only the manager can set the clawback cap, and a cap of 0 disables it
+        // Note: wipes count against the cap too
+        #[ink(message)]
+        pub fn set_clawback_cap(
+            &mut self,
+            cap: Balance,
+            period_blocks: BlockNumber,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_clawback_cap(caller, cap, period_blocks))
+        }
+
+        /// Set the contract called around every balance movement, or clear it with `None`
+        // Note: only the manager can set the transfer hook
+        #[ink(message)]
+        pub fn set_transfer_hook(&mut self, hook: Option<AccountId>) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_transfer_hook(caller, hook))
+        }
+
+        /// Deploy a new asset with the parameters of this one, returning its address
+        // Note: only the manager can clone the asset, optionally with another total and manager
+        // Note: the clone keeps the reserve, freeze and clawback addresses, but not the symbol,
+        // which has to stay unique, nor any holder state
+        #[ink(message)]
+        pub fn clone_asset(
+            &mut self,
+            override_total: Option<Balance>,
+            override_manager: Option<AccountId>,
+        ) -> Result<AccountId, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| {
+                asset.do_clone_asset(caller, override_total, override_manager)
+            })
+        }
+
+        /// Register the asset symbol with the `SymbolRegistry` contract `registry`
+        // Note: only the manager can register the asset, once it has a symbol
+        #[ink(message)]
+        pub fn register_symbol(&mut self, registry: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_register_symbol(caller, registry))
+        }
+
+        /// Lock `amount` of the balance of `account` until block `expires_at`, returning the lock id
+        // Note: only the manager can add time locks, and only on unlocked balance
+        // Note: locked tokens cannot be transferred, but can still be revoked
+        #[ink(message)]
+        pub fn add_time_lock(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+            expires_at: BlockNumber,
+        ) -> Result<u64, Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_add_time_lock(caller, account, amount, expires_at))
+        }
+
+        /// Remove the expired time locks of `account`, returning the amount they released
+        // Note: anyone can remove expired locks
+        #[ink(message)]
+        pub fn remove_expired_locks(&mut self, account: AccountId) -> Result<Balance, Error> {
+            self.non_reentrant(|asset| asset.do_remove_expired_locks(account))
+        }
+
+        /// Opt in `account` and fund it with `amount` from the reserve, in a single call
+        // Note: only the reserve can opt in other accounts, and emits both OptIn and Transfer
+        // Note: `amount` must be at least `min_opt_in_balance`
+        #[ink(message)]
+        pub fn opt_in_and_fund(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_opt_in_and_fund(caller, account, amount))
+        }
+
+        /// Distribute tokens from the reserve to each of the `(recipient, amount)` pairs
+        // Note: only the reserve and its distributors can distribute, and all recipients must
+        // have opted in
+        // Note: emits one ReserveDistribution event instead of a Transfer per recipient
+        #[ink(message)]
+        pub fn distribute_from_reserve(
+            &mut self,
+            recipients: Vec<(AccountId, Balance)>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_distribute_from_reserve(caller, recipients))
+        }
+
+        /// Authorize `distributor` to distribute from the reserve, or revoke it
+        // Note: only the reserve can set distributors; they do not carry over to a new reserve
+        #[ink(message)]
+        pub fn set_distributor(
+            &mut self,
+            distributor: AccountId,
+            allowed: bool,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_set_distributor(caller, distributor, allowed))
+        }
+
+        /// Fund the clawback address with `amount` from the reserve
+        // Note: only the reserve can fund a role, which is opted in first if needed
+        #[ink(message)]
+        pub fn fund_clawback(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_fund_role(caller, Role::Clawback, amount))
+        }
+
+        /// Fund the freeze address with `amount` from the reserve
+        // Note: only the reserve can fund a role, which is opted in first if needed
+        #[ink(message)]
+        pub fn fund_freeze(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_fund_role(caller, Role::Freeze, amount))
+        }
+
+        /// Fund the manager with `amount` from the reserve
+        // Note: only the reserve can fund a role, which is opted in first if needed
+        #[ink(message)]
+        pub fn fund_manager(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_fund_role(caller, Role::Manager, amount))
+        }
+
+        /// Import a batch of holdings from a snapshot, e.g. of an Algorand ASA, funding each
+        /// account from the reserve and setting its frozen flag
+        // Note: only the manager can import, at most `MAX_STATUS_BATCH` entries per batch, and
+        // only into an asset created with `start_suspended` that was never activated
+        // Note: accounts are opted in as by `opt_in_and_fund`, and frozen flags need a
+        // freezable asset
+        #[ink(message)]
+        pub fn import_holdings(
+            &mut self,
+            entries: Vec<(AccountId, Balance, bool)>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_import_holdings(caller, entries))
+        }
+
+        /// Activate the asset, allowing transfers and opt-ins
+        // Note: only the manager can activate a pending or suspended asset
+        #[ink(message)]
+        pub fn activate(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_activate(caller))
+        }
+
+        /// Suspend the asset, blocking transfers and opt-ins until it is activated again
+        // Note: only the manager can suspend an active asset
+        #[ink(message)]
+        pub fn suspend(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_suspend(caller))
+        }
+
+        /// Transfer `amount` of tokens from `from` to `to` without the holder's consent
+        // Note: only the manager can execute an admin transfer
+        // Note: balance and opt-in checks still apply, and frozen status is not considered
+        // Security: this lets the manager move any holding at will. Unlike revoke_asset, it is
+        // not tied to the clawback role, so assets meant to be trustless should clear the
+        // manager (modify_asset with manager = None) to disable it.
+        #[ink(message)]
+        pub fn admin_transfer(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.non_reentrant(|asset| asset.do_admin_transfer(caller, from, to, amount))
+        }
+
+        /// Destroy an asset
+        // Note: only the manager can destroy an asset
+        // Note: all asset holdings are transferred to the manager
+        #[ink(message)]
+        pub fn destroy_asset(&mut self) -> Result<(), Error> {
+            self.check_not_entered()?;
+            let caller = self.env().caller();
+
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if manager balance is equal to total supply
+            let manager_balance = self.holder(self.manager_id).balance;
+            if manager_balance != self.total {
+                return Err(Error::NotAllAssetsOwnedByManager);
+            }
+
+            self.mirror_burn(self.manager_id, self.total)?;
+            self.set_status(AssetStatus::Destroyed);
+            self.log_admin(
+                caller,
+                AdminActionKind::StatusChange(AssetStatus::Destroyed),
+                None,
+                0,
+            );
+
+            // emit destroy asset event
+            self.env().emit_event(Destruction {
+                asset_id: self.asset_id(),
+                destroyer: self.manager_id,
+                timestamp: self.env().block_timestamp(),
+                beneficiary: self.manager_id,
+                total_supply: self.total,
+                lifetime_transfers: self.lifetime_transfers,
+                holder_count_at_destroy: self.opted_in_count,
+            });
+
+            // terminate contract
+            self.env().terminate_contract(self.manager_id);
+        }
+    }
+
+    /// Internal entry points of the subsa smart contract
+    // Note: every state-changing message is a thin wrapper that passes `self.env().caller()`
+    // to one of the `do_*` functions below, under the reentrancy guard, so that they can be
+    // driven directly (e.g. by the fuzz targets in `fuzz/`) without going through the ink!
+    // dispatch.
+    impl Subsa {
+        /// Transfer `amount` of tokens from `sender` to `receiver`.
+        pub fn do_transfer(
+            &mut self,
+            sender: AccountId,
+            receiver: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if receiver is not the zero address, where the tokens would be lost
+            if receiver == zero_account() {
+                return Err(Error::TransferToZeroAddress);
+            }
+
+            // check if an NFT is transferred whole
+            if self.is_nft() && amount != 1 {
+                return Err(Error::NFTPartialTransferNotAllowed);
+            }
+
+            // check if secondary transfers are not frozen, unless sent by the reserve or sent
+            // or received by a freeze-exempt account
+            if self.secondary_transfers_frozen
+                && sender != self.reserve_id
+                && !self.is_freeze_exempt(sender)
+                && !self.is_freeze_exempt(receiver)
+            {
+                return Err(Error::SecondaryMarketFrozen);
+            }
+
+            // check if the sender's subscription, if required, has not expired
+            if self.subscription_period_blocks > 0
+                && sender != self.reserve_id
+                && self
+                    .subscription_expiry
+                    .get(sender)
+                    .is_none_or(|expiry| expiry < self.env().block_number())
+            {
+                return Err(Error::SubscriptionExpired);
+            }
+
+            // check if the sender's cooldown, if any, has expired since its last transfer
+            let block = self.env().block_number();
+            if self.cooldown_blocks > 0
+                && sender != self.reserve_id
+                && self
+                    .last_sent_block
+                    .get(sender)
+                    .is_some_and(|last| block - last < self.cooldown_blocks)
+            {
+                return Err(Error::CooldownNotExpired);
+            }
+
+            // check if the amount is not above the threshold, which needs the manager's approval
+            if self.large_transfer_threshold > 0
+                && amount > self.large_transfer_threshold
+                && sender != self.reserve_id
+            {
+                return Err(Error::LargeTransferRequiresApproval);
+            }
+
+            // check if neither sender nor receiver is frozen
+            if self.blocked_by_freeze(sender) || self.blocked_by_freeze(receiver) {
+                return Err(Error::FrozenAccount);
+            }
+
+            // check if the amount is not under a time lock or partially frozen
+            if amount <= self.holder(sender).balance && amount > self.available_balance(sender) {
+                return Err(Error::BalanceLocked);
+            }
+
+            // check if the sender can send to the receiver
+            self.check_destination(sender, receiver)?;
+
+            // check if the amount fits in the sender's spend limit
+            let spend = self.check_spend_limit(sender, amount)?;
+
+            // check if the sender can cover the burnt share on top of the moves below
+            let burnt = self.transfer_burn(sender, amount);
+            if burnt > 0 && self.holder(sender).balance < amount {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            // move the amount net of the burnt share, fee, tax and royalties to the receiver,
+            // the fee and tax to the reserve, and the royalties to their recipients
+            // Note: a fee paid in the fee token is not deducted from the amount
+            let burnable = amount - burnt;
+            let fee = self.transfer_fee(sender, burnable);
+            let deducted = if self.fee_token.is_some() { 0 } else { fee };
+            let tax = self.transfer_tax(sender, receiver, burnable - deducted);
+            let taxed = tax.map_or(0, |(tax, _)| tax);
+            let royalties = self.royalties(sender, burnable - deducted - taxed);
+            let total_royalty = royalties
+                .iter()
+                .fold(0 as Balance, |sum, &(_, royalty)| sum + royalty);
+            let net = burnable - deducted - taxed - total_royalty;
+            if deducted == 0 && taxed == 0 && royalties.is_empty() {
+                self.move_balance(TransferOperation::Transfer, sender, receiver, burnable)?;
+            } else {
+                let mut moves = Vec::with_capacity(royalties.len() + 2);
+                moves.push((receiver, net));
+                if deducted + taxed > 0 {
+                    moves.push((self.reserve_id, deducted + taxed));
+                }
+                moves.extend_from_slice(&royalties);
+                self.move_balances(TransferOperation::Transfer, sender, &moves)?;
+            }
+
+            // destroy the burnt share out of the sender's balance
+            if burnt > 0 {
+                self.burn_from(sender, burnt)?;
+            }
+
+            // collect the fee in the fee token, once the transfer is written
+            if let Some(token) = self.fee_token.filter(|_| fee > 0) {
+                self.collect_token_fee(token, sender, fee)?;
+            }
+
+            // record the spend in the sender's current window
+            if let Some((spent_in_window, window_start)) = spend {
+                let mut holder = self.holder(sender);
+                holder.spent_in_window = spent_in_window;
+                holder.window_start = window_start;
+                self.holders.insert(sender, &holder);
+            }
+
+            // start the sender's cooldown
+            if self.cooldown_blocks > 0 {
+                self.last_sent_block.insert(sender, &block);
+            }
+
+            // emit transfer event, or NFT transfer event for an NFT
+            if self.is_nft() {
+                self.env().emit_event(NFTTransfer {
+                    from: sender,
+                    to: receiver,
+                    asset_id: self.asset_id(),
+                });
+            } else {
+                self.env().emit_event(Transfer {
+                    sender,
+                    receiver,
+                    asset_id: self.asset_id(),
+                    amount: Some(net),
+                    timestamp: self.env().block_timestamp(),
+                });
+            }
+
+            // emit burn event
+            if burnt > 0 {
+                self.env().emit_event(Burn {
+                    from: sender,
+                    amount: burnt,
+                    current_supply: self.current_supply(),
+                });
+            }
+
+            // emit transfer fee collected event
+            if fee > 0 {
+                self.env().emit_event(TransferFeeCollected { sender, fee });
+            }
+
+            // emit tax collected event
+            if let Some((amount, jurisdiction)) = tax.filter(|&(tax, _)| tax > 0) {
+                self.env().emit_event(TaxCollected {
+                    from: sender,
+                    to: receiver,
+                    amount,
+                    jurisdiction,
+                });
+            }
+
+            // emit royalty split paid event
+            if !royalties.is_empty() {
+                self.env().emit_event(RoyaltySplitPaid {
+                    from: sender,
+                    to: receiver,
+                    splits_count: royalties.len() as u32,
+                    total_royalty,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Escrow `amount` of tokens from `sender` for `receiver`.
+        pub fn do_send_pending(
+            &mut self,
+            sender: AccountId,
+            receiver: AccountId,
+            amount: Balance,
+            expiry: Timestamp,
+        ) -> Result<u64, Error> {
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the pending transfer is not empty and expires in the future
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if expiry <= self.env().block_timestamp() {
+                return Err(Error::InvalidPendingExpiry);
+            }
+
+            // check if receiver is not the zero address, which can never claim the tokens
+            if receiver == zero_account() {
+                return Err(Error::TransferToZeroAddress);
+            }
+
+            // check if sender is not frozen
+            if self.blocked_by_freeze(sender) {
+                return Err(Error::FrozenAccount);
+            }
+
+            // check if the amount is not under a time lock or partially frozen
+            if amount <= self.holder(sender).balance && amount > self.available_balance(sender) {
+                return Err(Error::BalanceLocked);
+            }
+
+            // check if the sender can send to the receiver
+            self.check_destination(sender, receiver)?;
+
+            // check if the amount fits in the sender's spend limit
+            let spend = self.check_spend_limit(sender, amount)?;
+
+            // check if the receiver has room for another pending transfer
+            let mut ids = self.pending_ids.get(receiver).unwrap_or_default();
+            if ids.len() >= MAX_PENDING_PER_RECEIVER {
+                return Err(Error::TooManyPendingTransfers);
+            }
+
+            self.debit(sender, amount)?;
+            self.pending_total += amount;
+
+            // record the spend in the sender's current window
+            if let Some((spent_in_window, window_start)) = spend {
+                let mut holder = self.holder(sender);
+                holder.spent_in_window = spent_in_window;
+                holder.window_start = window_start;
+                self.holders.insert(sender, &holder);
+            }
+
+            let id = self.next_pending_id;
+            self.next_pending_id += 1;
+            self.pending_transfers.insert(
+                id,
+                &PendingTransfer {
+                    id,
+                    sender,
+                    receiver,
+                    amount,
+                    expiry,
+                },
+            );
+            ids.push(id);
+            self.pending_ids.insert(receiver, &ids);
+
+            // emit pending created event
+            self.env().emit_event(PendingCreated {
+                id,
+                sender,
+                receiver,
+                amount,
+                expiry,
+            });
+
+            Ok(id)
+        }
+
+        /// Claim the pending transfer `id` on behalf of `caller`
+        pub fn do_claim_pending(&mut self, caller: AccountId, id: u64) -> Result<(), Error> {
+            let pending = self
+                .pending_transfers
+                .get(id)
+                .ok_or(Error::PendingTransferNotFound)?;
+
+            // check if caller is the receiver
+            if caller != pending.receiver {
+                return Err(Error::NotPendingReceiver);
+            }
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the pending transfer has not expired
+            if self.env().block_timestamp() >= pending.expiry {
+                return Err(Error::PendingTransferExpired);
+            }
+
+            // check if caller has opted in
+            if !self.holder(caller).opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            self.credit(caller, pending.amount)?;
+            self.remove_pending(&pending);
+
+            // emit pending claimed event
+            self.env().emit_event(PendingClaimed {
+                id,
+                receiver: caller,
+                amount: pending.amount,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel the pending transfer `id` on behalf of `caller`
+        pub fn do_cancel_pending(&mut self, caller: AccountId, id: u64) -> Result<(), Error> {
+            let pending = self
+                .pending_transfers
+                .get(id)
+                .ok_or(Error::PendingTransferNotFound)?;
+
+            // check if caller is the sender, or the pending transfer has expired
+            if caller != pending.sender && self.env().block_timestamp() < pending.expiry {
+                return Err(Error::CannotCancelPending);
+            }
+
+            self.credit(pending.sender, pending.amount)?;
+            self.remove_pending(&pending);
+
+            // emit pending cancelled event
+            self.env().emit_event(PendingCancelled {
+                id,
+                sender: pending.sender,
+                amount: pending.amount,
+                by: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Escrow `amount` from `sender` for a transfer to `receiver` from `execute_after`
+        pub fn do_schedule_transfer(
+            &mut self,
+            sender: AccountId,
+            receiver: AccountId,
+            amount: Balance,
+            execute_after: Timestamp,
+        ) -> Result<u64, Error> {
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the scheduled transfer is not empty and executes in the future
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if execute_after <= self.env().block_timestamp() {
+                return Err(Error::InvalidScheduleTime);
+            }
+
+            // check if receiver is not the zero address, which can never opt in
+            if receiver == zero_account() {
+                return Err(Error::TransferToZeroAddress);
+            }
+
+            // check if sender is not frozen
+            if self.blocked_by_freeze(sender) {
+                return Err(Error::FrozenAccount);
+            }
+
+            // check if the amount is not under a time lock or partially frozen
+            if amount <= self.holder(sender).balance && amount > self.available_balance(sender) {
+                return Err(Error::BalanceLocked);
+            }
+
+            // check if the sender can send to the receiver
+            self.check_destination(sender, receiver)?;
+
+            // check if the amount fits in the sender's spend limit
+            let spend = self.check_spend_limit(sender, amount)?;
+
+            self.debit(sender, amount)?;
+            self.scheduled_total += amount;
+
+            // record the spend in the sender's current window
+            if let Some((spent_in_window, window_start)) = spend {
+                let mut holder = self.holder(sender);
+                holder.spent_in_window = spent_in_window;
+                holder.window_start = window_start;
+                self.holders.insert(sender, &holder);
+            }
+
+            let id = self.next_scheduled_id;
+            self.next_scheduled_id += 1;
+            self.scheduled_transfers.insert(
+                id,
+                &ScheduledTransfer {
+                    id,
+                    sender,
+                    receiver,
+                    amount,
+                    execute_after,
+                },
+            );
+
+            // emit transfer scheduled event
+            self.env().emit_event(TransferScheduled {
+                id,
+                sender,
+                receiver,
+                amount,
+                execute_after,
+            });
+
+            Ok(id)
+        }
+
+        /// Escrow `deposit` from the reserve into a stream to `recipient` on behalf of `caller`
+        pub fn do_create_stream(
+            &mut self,
+            caller: AccountId,
+            recipient: AccountId,
+            deposit: Balance,
+            start: Timestamp,
+            stop: Timestamp,
+        ) -> Result<u64, Error> {
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if caller is the reserve address
+            if caller != self.reserve_id {
+                return Err(Error::NotReserveId);
+            }
+
+            // check if the stream is not empty, and does not end before now or its start
+            if deposit == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if start >= stop || stop <= self.env().block_timestamp() {
+                return Err(Error::InvalidStreamTime);
+            }
+
+            // check if recipient has opted in
+            if !self.holder(recipient).opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            // check if the reserve's unlocked balance covers the deposit
+            if deposit > self.available_balance(caller) {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            self.debit(caller, deposit)?;
+            self.streamed_total += deposit;
+
+            let id = self.next_stream_id;
+            self.next_stream_id += 1;
+            self.streams.insert(
+                id,
+                &Stream {
+                    id,
+                    recipient,
+                    deposit,
+                    start,
+                    stop,
+                    withdrawn: 0,
+                },
+            );
+
+            // emit stream created event
+            self.env().emit_event(StreamCreated {
+                id,
+                recipient,
+                deposit,
+                start,
+                stop,
+            });
+
+            Ok(id)
+        }
+
+        /// Withdraw `amount` from the stream `id` on behalf of `caller`
+        pub fn do_withdraw_from_stream(
+            &mut self,
+            caller: AccountId,
+            id: u64,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let mut stream = self.streams.get(id).ok_or(Error::StreamNotFound)?;
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if caller is the recipient
+            if caller != stream.recipient {
+                return Err(Error::NotStreamRecipient);
+            }
+
+            // check if recipient has opted in and is not frozen
+            let recipient = self.lift_lapsed_freeze(caller);
+            if !recipient.opted_in {
+                return Err(Error::NotOptedIn);
+            }
+            if recipient.frozen && !self.is_freeze_exempt(caller) {
+                return Err(Error::FrozenAccount);
+            }
+
+            // check if the amount accrued and is not withdrawn yet
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let accrued = self.accrued_in_stream(&stream);
+            if amount > accrued - stream.withdrawn {
+                return Err(Error::StreamBalanceExceeded);
+            }
+
+            self.credit(caller, amount)?;
+            self.streamed_total -= amount;
+
+            // remove the stream once fully withdrawn
+            stream.withdrawn += amount;
+            if stream.withdrawn == stream.deposit {
+                self.streams.remove(id);
+            } else {
+                self.streams.insert(id, &stream);
+            }
+
+            // emit stream withdrawn event
+            self.env().emit_event(StreamWithdrawn {
+                id,
+                recipient: caller,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel the stream `id` on behalf of `caller`
+        pub fn do_cancel_stream(&mut self, caller: AccountId, id: u64) -> Result<(), Error> {
+            let stream = self.streams.get(id).ok_or(Error::StreamNotFound)?;
+
+            // check if caller is the reserve or the recipient
+            if caller != self.reserve_id && caller != stream.recipient {
+                return Err(Error::NotStreamParty);
+            }
+
+            // split what is left into its accrued and unaccrued parts
+            // Note: the accrued part is credited even to a frozen recipient, which keeps it
+            // frozen in its balance
+            let accrued = self.accrued_in_stream(&stream);
+            let recipient_amount = accrued - stream.withdrawn;
+            let reserve_amount = stream.deposit - accrued;
+            if recipient_amount > 0 {
+                self.credit(stream.recipient, recipient_amount)?;
+            }
+            if reserve_amount > 0 {
+                self.credit(self.reserve_id, reserve_amount)?;
+            }
+            self.streamed_total -= recipient_amount + reserve_amount;
+            self.streams.remove(id);
+
+            // emit stream cancelled event
+            self.env().emit_event(StreamCancelled {
+                id,
+                recipient: stream.recipient,
+                recipient_amount,
+                reserve_amount,
+                by: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Execute the scheduled transfer `id` on behalf of `caller`
+        pub fn do_execute_scheduled(&mut self, caller: AccountId, id: u64) -> Result<(), Error> {
+            let scheduled = self
+                .scheduled_transfers
+                .get(id)
+                .ok_or(Error::ScheduledTransferNotFound)?;
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the scheduled transfer is due
+            if self.env().block_timestamp() < scheduled.execute_after {
+                return Err(Error::ScheduledTransferNotDue);
+            }
+
+            // check if receiver has opted in and is not frozen
+            let receiver = self.lift_lapsed_freeze(scheduled.receiver);
+            if !receiver.opted_in {
+                return Err(Error::NotOptedIn);
+            }
+            if receiver.frozen && !self.is_freeze_exempt(scheduled.receiver) {
+                return Err(Error::FrozenAccount);
+            }
+
+            self.credit(scheduled.receiver, scheduled.amount)?;
+            self.remove_scheduled(&scheduled);
+
+            // emit scheduled executed event
+            self.env().emit_event(ScheduledExecuted {
+                id,
+                receiver: scheduled.receiver,
+                amount: scheduled.amount,
+                executor: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel the scheduled transfer `id` on behalf of `caller`
+        pub fn do_cancel_scheduled(&mut self, caller: AccountId, id: u64) -> Result<(), Error> {
+            let scheduled = self
+                .scheduled_transfers
+                .get(id)
+                .ok_or(Error::ScheduledTransferNotFound)?;
+
+            // check if caller is the sender
+            if caller != scheduled.sender {
+                return Err(Error::NotScheduleSender);
+            }
+
+            self.credit(scheduled.sender, scheduled.amount)?;
+            self.remove_scheduled(&scheduled);
+
+            // emit scheduled cancelled event
+            self.env().emit_event(ScheduledCancelled {
+                id,
+                sender: scheduled.sender,
+                amount: scheduled.amount,
+            });
+
+            Ok(())
+        }
+
+        /// Set the large transfer threshold on behalf of `caller`
+        pub fn do_set_large_transfer_threshold(
+            &mut self,
+            caller: AccountId,
+            threshold: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.large_transfer_threshold = threshold;
+
+            // emit large transfer threshold set event
+            self.env()
+                .emit_event(LargeTransferThresholdSet { threshold });
+
+            Ok(())
+        }
+
+        /// Escrow `amount` from `sender` for a large transfer to `receiver`
+        pub fn do_initiate_large_transfer(
+            &mut self,
+            sender: AccountId,
+            receiver: AccountId,
+            amount: Balance,
+        ) -> Result<u64, Error> {
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the amount is above the threshold, if any
+            if self.large_transfer_threshold == 0 || amount <= self.large_transfer_threshold {
+                return Err(Error::NotLargeTransfer);
+            }
+
+            // check if receiver is not the zero address, which can never opt in
+            if receiver == zero_account() {
+                return Err(Error::TransferToZeroAddress);
+            }
+
+            // check if sender is not frozen
+            if self.blocked_by_freeze(sender) {
+                return Err(Error::FrozenAccount);
+            }
+
+            // check if the amount is not under a time lock or partially frozen
+            if amount <= self.holder(sender).balance && amount > self.available_balance(sender) {
+                return Err(Error::BalanceLocked);
+            }
+
+            // check if the sender can send to the receiver
+            self.check_destination(sender, receiver)?;
+
+            // check if the amount fits in the sender's spend limit
+            let spend = self.check_spend_limit(sender, amount)?;
+
+            self.debit(sender, amount)?;
+            self.large_transfer_total += amount;
+
+            // record the spend in the sender's current window
+            if let Some((spent_in_window, window_start)) = spend {
+                let mut holder = self.holder(sender);
+                holder.spent_in_window = spent_in_window;
+                holder.window_start = window_start;
+                self.holders.insert(sender, &holder);
+            }
+
+            let id = self.next_large_transfer_id;
+            self.next_large_transfer_id += 1;
+            self.large_transfer_pending
+                .insert(id, &(sender, receiver, amount));
+
+            // emit large transfer initiated event
+            self.env().emit_event(LargeTransferInitiated {
+                id,
+                sender,
+                receiver,
+                amount,
+            });
+
+            Ok(id)
+        }
+
+        /// Approve the large transfer `id` on behalf of `caller`
+        pub fn do_approve_large_transfer(
+            &mut self,
+            caller: AccountId,
+            id: u64,
+        ) -> Result<(), Error> {
+            let (_, receiver, amount) = self
+                .large_transfer_pending
+                .get(id)
+                .ok_or(Error::LargeTransferNotFound)?;
+
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if receiver has opted in and is not frozen
+            let holder = self.lift_lapsed_freeze(receiver);
+            if !holder.opted_in {
+                return Err(Error::NotOptedIn);
+            }
+            if holder.frozen && !self.is_freeze_exempt(receiver) {
+                return Err(Error::FrozenAccount);
+            }
+
+            self.credit(receiver, amount)?;
+            self.large_transfer_pending.remove(id);
+            self.large_transfer_total -= amount;
+
+            // emit large transfer approved event
+            self.env().emit_event(LargeTransferApproved {
+                id,
+                receiver,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel the large transfer `id` on behalf of `caller`
+        pub fn do_cancel_large_transfer(
+            &mut self,
+            caller: AccountId,
+            id: u64,
+        ) -> Result<(), Error> {
+            let (sender, _, amount) = self
+                .large_transfer_pending
+                .get(id)
+                .ok_or(Error::LargeTransferNotFound)?;
+
+            // check if caller is the sender
+            if caller != sender {
+                return Err(Error::NotLargeTransferSender);
+            }
+
+            self.credit(sender, amount)?;
+            self.large_transfer_pending.remove(id);
+            self.large_transfer_total -= amount;
+
+            // emit large transfer cancelled event
+            self.env()
+                .emit_event(LargeTransferCancelled { id, sender, amount });
+
+            Ok(())
+        }
+
+        /// Escrow up to `max_amount` from `sender` for the committed transfer `commitment`
+        pub fn do_commit_transfer(
+            &mut self,
+            sender: AccountId,
+            commitment: [u8; 32],
+            receiver: AccountId,
+            expiry_block: BlockNumber,
+            max_amount: Balance,
+        ) -> Result<u64, Error> {
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the committed transfer is not empty and expires in the future
+            if max_amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if expiry_block <= self.env().block_number() {
+                return Err(Error::InvalidCommitmentExpiry);
+            }
+
+            // check if receiver is not the zero address, which can never opt in
+            if receiver == zero_account() {
+                return Err(Error::TransferToZeroAddress);
+            }
+
+            // check if sender is not frozen
+            if self.blocked_by_freeze(sender) {
+                return Err(Error::FrozenAccount);
+            }
+
+            // check if the amount is not under a time lock or partially frozen
+            if max_amount <= self.holder(sender).balance
+                && max_amount > self.available_balance(sender)
+            {
+                return Err(Error::BalanceLocked);
+            }
+
+            // check if the sender can send to the receiver
+            self.check_destination(sender, receiver)?;
+
+            // check if the amount fits in the sender's spend limit
+            let spend = self.check_spend_limit(sender, max_amount)?;
+
+            self.debit(sender, max_amount)?;
+            self.committed_total += max_amount;
+
+            // record the spend in the sender's current window
+            if let Some((spent_in_window, window_start)) = spend {
+                let mut holder = self.holder(sender);
+                holder.spent_in_window = spent_in_window;
+                holder.window_start = window_start;
+                self.holders.insert(sender, &holder);
+            }
+
+            let id = self.next_commitment_id;
+            self.next_commitment_id += 1;
+            self.committed_transfers.insert(
+                id,
+                &CommittedTransfer {
+                    id,
+                    sender,
+                    receiver,
+                    commitment,
+                    locked: max_amount,
+                    expiry_block,
+                },
+            );
+
+            // emit transfer committed event
+            self.env().emit_event(TransferCommitted {
+                id,
+                sender,
+                receiver,
+                commitment,
+            });
+
+            Ok(id)
+        }
+
+        /// Reveal the committed transfer `id` of `amount` on behalf of `caller`
+        pub fn do_reveal_transfer(
+            &mut self,
+            caller: AccountId,
+            id: u64,
+            amount: Balance,
+            salt: [u8; 32],
+        ) -> Result<(), Error> {
+            let committed = self
+                .committed_transfers
+                .get(id)
+                .ok_or(Error::CommittedTransferNotFound)?;
+
+            // check if caller is the sender
+            if caller != committed.sender {
+                return Err(Error::NotCommitmentSender);
+            }
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the committed transfer has not expired
+            if self.env().block_number() >= committed.expiry_block {
+                return Err(Error::CommittedTransferExpired);
+            }
+
+            // check if the amount and salt match the commitment, within the escrowed amount
+            if transfer_commitment(amount, salt) != committed.commitment {
+                return Err(Error::CommitmentMismatch);
+            }
+            if amount > committed.locked {
+                return Err(Error::CommitmentExceedsLock);
+            }
+
+            // check if receiver has opted in
+            if !self.holder(committed.receiver).opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            if amount > 0 {
+                self.credit(committed.receiver, amount)?;
+            }
+            if committed.locked > amount {
+                self.credit(committed.sender, committed.locked - amount)?;
+            }
+            self.remove_committed(&committed);
+
+            // emit transfer revealed event
+            self.env().emit_event(TransferRevealed { id, amount });
+
+            Ok(())
+        }
+
+        /// Cancel the committed transfer `id` on behalf of `caller`
+        pub fn do_cancel_committed_transfer(
+            &mut self,
+            caller: AccountId,
+            id: u64,
+        ) -> Result<(), Error> {
+            let committed = self
+                .committed_transfers
+                .get(id)
+                .ok_or(Error::CommittedTransferNotFound)?;
+
+            // check if caller is the sender, or the committed transfer has expired
+            if caller != committed.sender && self.env().block_number() < committed.expiry_block {
+                return Err(Error::CannotCancelCommitment);
+            }
+
+            self.credit(committed.sender, committed.locked)?;
+            self.remove_committed(&committed);
+
+            // emit commitment cancelled event
+            self.env().emit_event(CommitmentCancelled {
+                id,
+                sender: committed.sender,
+                amount: committed.locked,
+                by: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Execute `calls` on behalf of `caller`
+        pub fn do_multicall(
+            &mut self,
+            caller: AccountId,
+            calls: Vec<SubsaCall>,
+        ) -> Result<(), Error> {
+            // check if the number of calls is within the limit
+            if calls.len() > MAX_MULTICALL_CALLS {
+                return Err(Error::TooManyCalls);
+            }
+
+            for call in calls {
+                match call {
+                    SubsaCall::OptIn => self.do_opt_in(caller, 0),
+                    SubsaCall::OptOut => self.do_opt_out(caller),
+                    SubsaCall::Transfer { receiver, amount } => {
+                        self.do_transfer(caller, receiver, amount)
+                    }
+                    SubsaCall::ClaimPending { id } => self.do_claim_pending(caller, id),
+                }?;
+            }
+
+            Ok(())
+        }
+
+        /// Transfer a bundle of tokens from `sender`.
+        pub fn do_bundle_transfer(
+            &mut self,
+            sender: AccountId,
+            transfers: Vec<BundleTransfer>,
+        ) -> Result<(), Error> {
+            // check if every transfer is of this asset
+            let asset_contract = self.env().account_id();
+            if transfers
+                .iter()
+                .any(|transfer| transfer.asset_contract != asset_contract)
+            {
+                return Err(Error::UnsupportedAssetContract);
+            }
+
+            for transfer in &transfers {
+                self.do_transfer(sender, transfer.receiver, transfer.amount)?;
+            }
+
+            // emit bundle transfer executed event
+            self.env().emit_event(BundleTransferExecuted {
+                sender,
+                count: transfers.len() as u32,
+            });
+
+            Ok(())
+        }
+
+        /// OptIn `caller` to receive an asset
+        pub fn do_opt_in(&mut self, caller: AccountId, paid: Balance) -> Result<(), Error> {
+            // check if the paid value covers the opt-in fee
+            if paid < self.opt_in_fee {
+                return Err(Error::InsufficientOptInFee);
+            }
+
+            self.opt_in_account(caller, false)?;
+            self.collect_opt_in_fee(caller, caller, paid)
+        }
+
+        /// OptIn `account` on behalf of the sponsor `caller`
+        pub fn do_opt_in_for(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            signature: Vec<u8>,
+            paid: Balance,
+        ) -> Result<(), Error> {
+            // check if the signature is from the account, over its current nonce
+            let nonce = self.opt_in_nonce(account);
+            if self.recover_signer(&signature, nonce) != Some(account) {
+                return Err(Error::InvalidSignature);
+            }
+
+            // check if the paid value covers the opt-in fee
+            if paid < self.opt_in_fee {
+                return Err(Error::InsufficientOptInFee);
+            }
+
+            self.opt_in_account(account, false)?;
+            self.collect_opt_in_fee(account, caller, paid)?;
+            self.opt_in_nonces.insert(account, &(nonce + 1));
+            self.sponsors.insert(account, &caller);
+
+            // emit opt in sponsored event
+            self.env().emit_event(OptInSponsored {
+                account,
+                sponsor: caller,
+                nonce,
+            });
+
+            Ok(())
+        }
+
+        /// OptOut `caller` of receiving an asset
+        pub fn do_opt_out(&mut self, caller: AccountId) -> Result<(), Error> {
+            // check if caller has opted in
+            let mut holder = self.holder(caller);
+            if !holder.opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            // update caller's opt in status
+            holder.opted_in = false;
+            self.holders.insert(caller, &holder);
+            self.unindex_holder(caller);
+            self.sponsors.remove(caller);
+
+            // emit opt out event
+            self.env().emit_event(OptOut {
+                asset_id: self.asset_id(),
+                account: caller,
+                timestamp: self.env().block_timestamp(),
+                remaining_balance: holder.balance,
+                closed_to: None,
+                deposit_refunded: 0,
+            });
+
+            Ok(())
+        }
+
+        /// Purge the stale `account` on behalf of the manager `caller`
+        pub fn do_purge_stale_account(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if account has opted in
+            let holder = self.lift_lapsed_freeze(account);
+            if !holder.opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            // check if account holds nothing, is not frozen, and has been inactive for longer
+            // than the threshold
+            let inactive = self.inactivity_threshold > 0
+                && holder.last_activity.is_none_or(|last_activity| {
+                    self.env().block_timestamp().saturating_sub(last_activity)
+                        > self.inactivity_threshold
+                });
+            if holder.balance > 0 || holder.frozen || !inactive {
+                return Err(Error::AccountStillActive);
+            }
+
+            // clear the account's holder record, which opts it out
+            self.unindex_holder(account);
+            self.holders.remove(account);
+            self.sponsors.remove(account);
+            self.freeze_expiries.remove(account);
+            self.freeze_deadlines.remove(account);
+            self.partially_frozen.remove(account);
+
+            // emit account purged event
+            self.env().emit_event(AccountPurged {
+                account,
+                manager_id: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Set the inactivity threshold on behalf of `caller`
+        pub fn do_set_inactivity_threshold(
+            &mut self,
+            caller: AccountId,
+            threshold: Timestamp,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.inactivity_threshold = threshold;
+
+            // emit inactivity threshold set event
+            self.env().emit_event(InactivityThresholdSet { threshold });
+
+            Ok(())
+        }
+
+        /// Cancel the opt-in of `account` on behalf of the manager `caller`
+        pub fn do_cancel_opt_in(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if account has opted in, and holds nothing
+            let mut holder = self.holder(account);
+            if !holder.opted_in {
+                return Err(Error::NotOptedIn);
+            }
+            if holder.balance > 0 {
+                return Err(Error::NonZeroBalance);
+            }
+
+            // update account's opt in status
+            // Note: the frozen status is left to the freeze address
+            holder.opted_in = false;
+            self.holders.insert(account, &holder);
+            self.unindex_holder(account);
+            self.sponsors.remove(account);
+
+            // emit opt in cancelled event
+            self.env().emit_event(OptInCancelled {
+                account,
+                manager_id: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Freeze an account on behalf of `caller`
+        pub fn do_freeze(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            freeze: bool,
+            expiry: Option<BlockNumber>,
+        ) -> Result<(), Error> {
+            // check if token can be frozen
+            if !self.default_frozen {
+                return Err(Error::NotFreezable);
+            }
+
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            // check if account is already in the requested state
+            let holder = self.lift_lapsed_freeze(account);
+            if freeze && holder.frozen {
+                return Err(Error::AlreadyFrozen);
+            }
+            if !freeze && !holder.frozen {
+                return Err(Error::NotFrozen);
+            }
+
+            // check if the expiry, if any, is a future block of a freeze
+            if let Some(expiry) = expiry {
+                if !freeze || expiry <= self.env().block_number() {
+                    return Err(Error::InvalidFreezeExpiry);
+                }
+            }
+
+            // update account's frozen status, and its expiry
+            if let Some(expiry) = expiry {
+                self.freeze_expiries.insert(account, &expiry);
+            } else {
+                self.freeze_expiries.remove(account);
+            }
+            self.set_frozen(account, holder, freeze);
+            let kind = if freeze {
+                AdminActionKind::Freeze
+            } else {
+                AdminActionKind::Unfreeze
+            };
+            self.log_admin(caller, kind, Some(account), 0);
+
+            Ok(())
+        }
+
+        /// Freeze `account` until `expires_at` on behalf of `caller`
+        pub fn do_freeze_until(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            expires_at: Timestamp,
+        ) -> Result<(), Error> {
+            // check if token can be frozen
+            if !self.default_frozen {
+                return Err(Error::NotFreezable);
+            }
+
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            // check if account is not frozen already
+            let holder = self.lift_lapsed_freeze(account);
+            if holder.frozen {
+                return Err(Error::AlreadyFrozen);
+            }
+
+            // check if the freeze ends in the future
+            if expires_at <= self.env().block_timestamp() {
+                return Err(Error::InvalidFreezeExpiry);
+            }
+
+            // update account's frozen status, and when it ends
+            self.freeze_expiries.remove(account);
+            self.set_frozen(account, holder, true);
+            self.freeze_deadlines.insert(account, &expires_at);
+            self.log_admin(caller, AdminActionKind::Freeze, Some(account), 0);
+
+            Ok(())
+        }
+
+        /// Release the expired freeze of `account`
+        pub fn do_release_expired_freeze(&mut self, account: AccountId) -> Result<(), Error> {
+            // check if account is frozen, until a block that has been reached
+            let holder = self.lift_lapsed_freeze(account);
+            if !holder.frozen {
+                return Err(Error::NotFrozen);
+            }
+            let released_at = self.env().block_number();
+            match self.freeze_expiries.get(account) {
+                Some(expiry) if expiry <= released_at => {}
+                _ => return Err(Error::FreezeNotExpired),
+            }
+
+            self.freeze_expiries.remove(account);
+            self.set_frozen(account, holder, false);
+
+            // emit freeze expired event
+            self.env().emit_event(FreezeExpired {
+                account,
+                released_at,
+            });
+
+            Ok(())
+        }
+
+        /// Partially freeze `account` on behalf of `caller`
+        pub fn do_partial_freeze(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if token can be frozen
+            if !self.default_frozen {
+                return Err(Error::NotFreezable);
+            }
+
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            // check if the amount is not empty, and available
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if amount > self.available_balance(account) {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            // available balance is at most the balance, so the sum cannot overflow
+            let frozen = self.partially_frozen_balance_of(account) + amount;
+            self.partially_frozen.insert(account, &frozen);
+            self.log_admin(
+                caller,
+                AdminActionKind::PartialFreeze,
+                Some(account),
+                amount,
+            );
+
+            // emit partial freeze event
+            self.env().emit_event(PartialFreeze { account, amount });
+
+            Ok(())
+        }
+
+        /// Partially unfreeze `account` on behalf of `caller`
+        pub fn do_unpartial_freeze(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            // check if the amount is not empty, and partially frozen
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            let frozen = self.partially_frozen_balance_of(account);
+            if amount > frozen {
+                return Err(Error::NotPartiallyFrozen);
+            }
+
+            if frozen == amount {
+                self.partially_frozen.remove(account);
+            } else {
+                self.partially_frozen.insert(account, &(frozen - amount));
+            }
+            self.log_admin(
+                caller,
+                AdminActionKind::PartialUnfreeze,
+                Some(account),
+                amount,
+            );
+
+            // emit partial unfreeze event
+            self.env().emit_event(PartialUnfreeze { account, amount });
+
+            Ok(())
+        }
+
+        /// Create a lien of `borrower` for `lender` on behalf of `caller`
+        pub fn do_create_lien(
+            &mut self,
+            caller: AccountId,
+            borrower: AccountId,
+            lender: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if token can be frozen
+            if !self.default_frozen {
+                return Err(Error::NotFreezable);
+            }
+
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            // check if the amount is not empty, and available
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if amount > self.available_balance(borrower) {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            // available balance is at most the balance, so the sums cannot overflow
+            let lien = self.lien(borrower, lender) + amount;
+            self.liens.insert((borrower, lender), &lien);
+            let liened = self.liened_balance_of(borrower) + amount;
+            self.liened.insert(borrower, &liened);
+
+            // emit lien created event
+            self.env().emit_event(LienCreated {
+                borrower,
+                lender,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Release `amount` of the lien of `borrower` for `lender` on behalf of `caller`
+        pub fn do_release_lien(
+            &mut self,
+            caller: AccountId,
+            borrower: AccountId,
+            lender: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            // check if the amount is not empty, and under the lien
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if amount > self.lien(borrower, lender) {
+                return Err(Error::InsufficientLien);
+            }
+
+            self.reduce_lien(borrower, lender, amount);
+
+            // emit lien released event
+            self.env().emit_event(LienReleased {
+                borrower,
+                lender,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Enforce the lien of `borrower` for `lender` on behalf of `caller`
+        pub fn do_enforce_lien(
+            &mut self,
+            caller: AccountId,
+            borrower: AccountId,
+            lender: AccountId,
+        ) -> Result<(), Error> {
+            // check if caller is the clawback address
+            if caller != self.clawback_id {
+                return Err(Error::NotClawbackId);
+            }
+
+            // check if there is a lien to enforce
+            let lien = self.lien(borrower, lender);
+            if lien == 0 {
+                return Err(Error::InsufficientLien);
+            }
+
+            // clear the lien, and revoke what is left of it, as a revoke can take encumbered
+            // tokens
+            self.reduce_lien(borrower, lender, lien);
+            let amount = lien.min(self.holder(borrower).balance);
+            self.do_revoke_asset(caller, lender, borrower, amount)?;
+
+            // emit lien enforced event
+            self.env().emit_event(LienEnforced {
+                borrower,
+                lender,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Submit a freeze appeal on behalf of `caller`
+        pub fn do_submit_freeze_appeal(
+            &mut self,
+            caller: AccountId,
+            reason_hash: [u8; 32],
+        ) -> Result<(), Error> {
+            // check if caller is frozen
+            if !self.lift_lapsed_freeze(caller).frozen {
+                return Err(Error::NotFrozen);
+            }
+
+            // check if caller has no unresolved appeal
+            if self
+                .appeals
+                .get(caller)
+                .is_some_and(|appeal| !appeal.resolved)
+            {
+                return Err(Error::AppealPending);
+            }
+
+            self.appeals.insert(
+                caller,
+                &FreezeAppeal {
+                    appellant: caller,
+                    reason_hash,
+                    submitted_block: self.env().block_number(),
+                    resolved: false,
+                },
+            );
+
+            // emit appeal submitted event
+            self.env().emit_event(AppealSubmitted {
+                account: caller,
+                reason_hash,
+            });
+
+            Ok(())
+        }
+
+        /// Resolve the appeal of `account` on behalf of `caller`
+        pub fn do_resolve_appeal(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            grant: bool,
+        ) -> Result<(), Error> {
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            // check if account has an unresolved appeal
+            let mut appeal = match self.appeals.get(account) {
+                Some(appeal) if !appeal.resolved => appeal,
+                _ => return Err(Error::NoPendingAppeal),
+            };
+
+            // unfreeze the account, unless it was unfrozen since it appealed
+            if grant && self.lift_lapsed_freeze(account).frozen {
+                self.do_freeze(caller, account, false, None)?;
+            }
+
+            appeal.resolved = true;
+            self.appeals.insert(account, &appeal);
+
+            // emit appeal resolved event
+            self.env().emit_event(AppealResolved {
+                account,
+                granted: grant,
+            });
+
+            Ok(())
+        }
+
+        /// Modify/Reconfigure an asset on behalf of `caller`
+        pub fn do_modify_asset(
+            &mut self,
+            caller: AccountId,
+            manager: Option<AccountId>,
+            reserve: Option<AccountId>,
+            freeze: Option<AccountId>,
+            clawback: Option<AccountId>,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            let zero = zero_account();
+            let changes = [
+                (Role::Manager, manager.unwrap_or(zero)),
+                (Role::Reserve, reserve.unwrap_or(zero)),
+                (Role::Freeze, freeze.unwrap_or(zero)),
+                (Role::Clawback, clawback.unwrap_or(zero)),
+            ];
+
+            // validate every change before applying any
+            for (role, new) in changes {
+                self.check_role_change(role, new)?;
+            }
+            self.check_role_policy(&changes)?;
+
+            // update asset params
+            for (role, new) in changes {
+                self.apply_role_change(caller, role, new);
+            }
+            self.asset_version = self.asset_version.wrapping_add(1);
+
+            // emit modify asset event
+            self.env().emit_event(Modify {
+                manager_id: self.manager_id,
+                reserve_id: self.reserve_id,
+                freeze_id: self.freeze_id,
+                clawback_id: self.clawback_id,
+                asset_version: self.asset_version,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        /// Patch an asset on behalf of `caller`
+        pub fn do_patch_asset(
+            &mut self,
+            caller: AccountId,
+            patch: ModifyPatch,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            let zero = zero_account();
+            let changes: Vec<(Role, AccountId)> = [
+                (Role::Manager, patch.manager),
+                (Role::Reserve, patch.reserve),
+                (Role::Freeze, patch.freeze),
+                (Role::Clawback, patch.clawback),
+            ]
+            .into_iter()
+            .filter_map(|(role, new)| new.map(|new| (role, new.unwrap_or(zero))))
+            .collect();
+
+            // validate every change before applying any
+            for &(role, new) in &changes {
+                self.check_role_change(role, new)?;
+            }
+            self.check_role_policy(&changes)?;
+
+            // update the patched roles, noting those that actually changed
+            let mut changed_fields = 0;
+            for (role, new) in changes {
+                if self.role_id(role) != new {
+                    changed_fields |= 1 << role as u8;
+                }
+                self.apply_role_change(caller, role, new);
+            }
+            if changed_fields != 0 {
+                self.asset_version = self.asset_version.wrapping_add(1);
+            }
+
+            // emit asset patched event
+            self.env().emit_event(AssetPatched {
+                changed_fields,
+                asset_version: self.asset_version,
+            });
+
+            Ok(())
+        }
+
+        /// Propose a new manager on behalf of `caller`
+        pub fn do_propose_manager(
+            &mut self,
+            caller: AccountId,
+            new_manager: AccountId,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the proposed manager is not the contract itself
+            if new_manager == self.env().account_id() {
+                return Err(Error::SelfAsRole);
+            }
+
+            // check if the proposed manager holds no other role, when required
+            self.check_role_policy(&[(Role::Manager, new_manager)])?;
+
+            self.pending_manager = Some(new_manager);
+
+            // emit manager proposed event
+            self.env().emit_event(ManagerProposed {
+                proposed: new_manager,
+            });
+
+            Ok(())
+        }
+
+        /// Accept the manager role on behalf of `caller`
+        pub fn do_accept_manager(&mut self, caller: AccountId) -> Result<(), Error> {
+            // check if caller is the proposed manager
+            if self.pending_manager != Some(caller) {
+                return Err(Error::NotPendingManager);
+            }
+
+            self.check_role_policy(&[(Role::Manager, caller)])?;
+            self.apply_role_change(caller, Role::Manager, caller);
+            self.pending_manager = None;
+            self.asset_version = self.asset_version.wrapping_add(1);
+
+            // emit manager accepted event
+            self.env().emit_event(ManagerAccepted {
+                new_manager: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel the pending manager proposal on behalf of `caller`
+        pub fn do_cancel_manager_proposal(&mut self, caller: AccountId) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if there is a proposal to cancel
+            let proposed = self
+                .pending_manager
+                .take()
+                .ok_or(Error::NoManagerProposal)?;
+
+            // emit manager proposal cancelled event
+            self.env().emit_event(ManagerProposalCancelled { proposed });
+
+            Ok(())
+        }
+
+        /// Change a single role of an asset on behalf of `caller`
+        pub fn do_set_role(
+            &mut self,
+            caller: AccountId,
+            role: Role,
+            new: AccountId,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.check_role_change(role, new)?;
+            self.check_role_policy(&[(role, new)])?;
+            self.apply_role_change(caller, role, new);
+            self.asset_version = self.asset_version.wrapping_add(1);
+
+            Ok(())
+        }
+
+        /// Checks whether the roles after `changes` satisfy the role separation policy.
+        fn check_role_policy(&self, changes: &[(Role, AccountId)]) -> Result<(), Error> {
+            let role_after = |role: Role| {
+                changes
+                    .iter()
+                    .find(|&&(changed, _)| changed == role)
+                    .map_or_else(|| self.role_id(role), |&(_, new)| new)
+            };
+            check_role_separation(
+                [Role::Manager, Role::Freeze, Role::Clawback].map(role_after),
+                self.enforce_role_separation,
+            )
+        }
+
+        /// Returns the address currently holding `role`.
+        fn role_id(&self, role: Role) -> AccountId {
+            match role {
+                Role::Manager => self.manager_id,
+                Role::Reserve => self.reserve_id,
+                Role::Freeze => self.freeze_id,
+                Role::Clawback => self.clawback_id,
+            }
+        }
+
+        /// Checks whether `role` may be handed to `new`.
+        // Note: as in Algorand, a cleared (zero address) role can never be set again, nor can a
+        // clawback role past its sunset be handed to another address
+        // Note: a new reserve must have opted in, so it can hold the unminted supply
+        fn check_role_change(&self, role: Role, new: AccountId) -> Result<(), Error> {
+            // the contract can never act as an origin, so it cannot hold a role
+            if new == self.env().account_id() {
+                return Err(Error::SelfAsRole);
+            }
+
+            let zero = zero_account();
+            let old = self.role_id(role);
+            if self.role_cleared(role) && new != zero && new != old {
+                return Err(Error::RoleDisabled);
+            }
+
+            if role == Role::Reserve && new != old && new != zero && !self.holder(new).opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            Ok(())
+        }
+
+        /// Returns whether `role` is cleared, a clawback role past its sunset included.
+        fn role_cleared(&self, role: Role) -> bool {
+            self.role_id(role) == zero_account()
+                || role == Role::Clawback && self.clawback_expired()
+        }
+
+        /// Returns whether the clawback role has passed its sunset, if any.
+        fn clawback_expired(&self) -> bool {
+            self.clawback_sunset
+                .is_some_and(|sunset| self.env().block_timestamp() > sunset)
+        }
+
+        /// Hands `role` to `new`, emitting RoleChanged if it actually changed.
+        // Note: as in Algorand, the old reserve keeps its balance
+        fn apply_role_change(&mut self, by: AccountId, role: Role, new: AccountId) {
+            let old = self.role_id(role);
+            if old == new {
+                return;
+            }
+
+            // Note: a manager proposal does not outlive the manager that made it
+            match role {
+                Role::Manager => {
+                    self.manager_id = new;
+                    self.pending_manager = None;
+                }
+                Role::Reserve => self.reserve_id = new,
+                Role::Freeze => self.freeze_id = new,
+                Role::Clawback => self.clawback_id = new,
+            }
+
+            let zero = zero_account();
+            self.log_admin(
+                by,
+                AdminActionKind::RoleChange(role),
+                (new != zero).then_some(new),
+                0,
+            );
+
+            // emit role changed event
+            self.env().emit_event(RoleChanged {
+                role,
+                old: (old != zero).then_some(old),
+                new: (new != zero).then_some(new),
+                by,
+            });
+        }
+
+        /// Revoke `amount` from `recovation_target` and send it to `receiver` on behalf of `caller`
+        pub fn do_revoke_asset(
+            &mut self,
+            caller: AccountId,
+            receiver: AccountId,
+            recovation_target: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the clawback address
+            if caller != self.clawback_id {
+                return Err(Error::NotClawbackId);
+            }
+
+            // check if the clawback role has not passed its sunset
+            if self.clawback_expired() {
+                return Err(Error::ClawbackExpired);
+            }
+
+            // check if the revoke fits in the clawback cap
+            let used = self.check_clawback_cap(amount)?;
+
+            self.move_balance(
+                TransferOperation::Revoke,
+                recovation_target,
+                receiver,
+                amount,
+            )?;
+
+            if let Some((used, period_start)) = used {
+                self.clawback_used_this_period = used;
+                self.clawback_period_start = period_start;
+            }
+            self.log_admin(
+                caller,
+                AdminActionKind::Clawback,
+                Some(recovation_target),
+                amount,
+            );
+
+            // emit revoke asset event
+            self.env().emit_event(Revoke {
+                asset_id: self.asset_id(),
+                from: recovation_target,
+                to: receiver,
+                clawback_id: self.clawback_id,
+                amount,
+                from_balance: self.holder(recovation_target).balance,
+                to_balance: self.holder(receiver).balance,
+                timestamp: self.env().block_timestamp(),
+            });
+
+            Ok(())
+        }
+
+        /// Revoke from several targets on behalf of `caller`
+        pub fn do_revoke_many(
+            &mut self,
+            caller: AccountId,
+            ops: Vec<(AccountId, Option<Balance>, AccountId)>,
+        ) -> Result<(), Error> {
+            // check if caller is the clawback address
+            if caller != self.clawback_id {
+                return Err(Error::NotClawbackId);
+            }
+
+            // check if the clawback role has not passed its sunset
+            if self.clawback_expired() {
+                return Err(Error::ClawbackExpired);
+            }
+
+            // check if the number of revocations is within the limit
+            if ops.len() > MAX_REVOKE_BATCH {
+                return Err(Error::TooManyRevocations);
+            }
+
+            // resolve the amounts and check every revocation against the balances left by the
+            // previous ones before applying any, so that a failing entry changes nothing
+            // Note: the message still reverts should the hook or the mirror reject an entry
+            let mut balances: Vec<(AccountId, Balance)> = Vec::new();
+            let mut revocations = Vec::with_capacity(ops.len());
+            let mut total: Balance = 0;
+            for (target, amount, receiver) in ops {
+                let from = self.batch_balance(&mut balances, target);
+                let amount = amount.unwrap_or(balances[from].1);
+                if amount > balances[from].1 {
+                    return Err(Error::NotEnoughBalance);
+                }
+                if !self.holder(receiver).opted_in {
+                    return Err(Error::NotOptedIn);
+                }
+                balances[from].1 -= amount;
+                let to = self.batch_balance(&mut balances, receiver);
+                balances[to].1 += amount;
+                total = total
+                    .checked_add(amount)
+                    .ok_or(Error::ClawbackCapExceeded)?;
+                revocations.push((target, amount, receiver));
+            }
+            self.check_clawback_cap(total)?;
+
+            for (target, amount, receiver) in revocations {
+                self.do_revoke_asset(caller, receiver, target, amount)?;
+            }
+
+            Ok(())
+        }
+
+        /// Wipe `target` on behalf of `caller`
+        pub fn do_wipe(&mut self, caller: AccountId, target: AccountId) -> Result<(), Error> {
+            // check if caller is the clawback address
+            if caller != self.clawback_id {
+                return Err(Error::NotClawbackId);
+            }
+
+            // check if the clawback role has not passed its sunset
+            if self.clawback_expired() {
+                return Err(Error::ClawbackExpired);
+            }
+
+            // check if target is not the reserve, which would take the supply with it
+            if target == self.reserve_id {
+                return Err(Error::CannotWipeReserve);
+            }
+
+            // check if target is frozen, when required
+            let holder = self.lift_lapsed_freeze(target);
+            if self.wipe_requires_freeze && !holder.frozen {
+                return Err(Error::NotFrozen);
+            }
+
+            let amount = holder.balance;
+            self.do_revoke_asset(caller, self.reserve_id, target, amount)?;
+
+            // clear the target's holder record, which opts it out
+            if holder.opted_in {
+                self.unindex_holder(target);
+            }
+            self.holders.remove(target);
+            self.sponsors.remove(target);
+            self.freeze_expiries.remove(target);
+            self.freeze_deadlines.remove(target);
+            self.partially_frozen.remove(target);
+
+            // emit wiped event
+            self.env().emit_event(Wiped {
+                target,
+                amount,
+                clawback_id: self.clawback_id,
+            });
+
+            Ok(())
+        }
+
+        /// Transfer `amount` of tokens from `from` to `to` on behalf of the manager `caller`
+        pub fn do_admin_transfer(
+            &mut self,
+            caller: AccountId,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            self.move_balance(TransferOperation::AdminTransfer, from, to, amount)?;
+
+            // emit admin transfer event
+            self.env().emit_event(AdminTransfer {
+                from,
+                to,
+                amount,
+                manager_id: self.manager_id,
+            });
+
+            Ok(())
+        }
+
+        /// Set the KYC status of `account` on behalf of `caller`
+        pub fn do_set_kyc_status(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            status: u8,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if status is known
+            if status > KYC_REJECTED {
+                return Err(Error::InvalidKycStatus);
+            }
+
+            // update account's KYC status
+            self.kyc_status.insert(account, &status);
+
+            // emit KYC status updated event
+            self.env().emit_event(KycStatusUpdated { account, status });
+
+            Ok(())
+        }
+
+        /// Set the metadata field `key` of `caller`
+        pub fn do_set_account_metadata(
+            &mut self,
+            caller: AccountId,
+            key: [u8; 32],
+            value: Vec<u8>,
+        ) -> Result<(), Error> {
+            // check if caller has opted in
+            if !self.holder(caller).opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            // check if value fits
+            if value.len() > MAX_ACCOUNT_METADATA_SIZE {
+                return Err(Error::MetadataTooLarge);
+            }
+
+            self.account_metadata.insert((caller, key), &value);
+
+            // emit account metadata set event
+            self.env().emit_event(AccountMetadataSet {
+                account: caller,
+                key,
+            });
+
+            Ok(())
+        }
+
+        /// Clear the metadata field `key` of `caller`
+        pub fn do_clear_account_metadata(
+            &mut self,
+            caller: AccountId,
+            key: [u8; 32],
+        ) -> Result<(), Error> {
+            self.account_metadata.remove((caller, key));
+
+            // emit account metadata cleared event
+            self.env().emit_event(AccountMetadataCleared {
+                account: caller,
+                key,
+            });
+
+            Ok(())
+        }
+
+        /// Restrict the outbound transfers of `caller`
+        pub fn do_restrict_transfers(
+            &mut self,
+            caller: AccountId,
+            restricted: bool,
+        ) -> Result<(), Error> {
+            let mut holder = self.holder(caller);
+            holder.transfers_restricted = restricted;
+            self.holders.insert(caller, &holder);
+
+            // emit transfers restricted event
+            self.env().emit_event(TransfersRestricted {
+                account: caller,
+                restricted,
+            });
+
+            Ok(())
+        }
+
+        /// Elect dividend reinvestment on behalf of `caller`
+        pub fn do_elect_drip(&mut self, caller: AccountId, enabled: bool) -> Result<(), Error> {
+            // check if caller has opted in
+            if !self.holder(caller).opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            if enabled {
+                self.drip_election.insert(caller, &true);
+            } else {
+                self.drip_election.remove(caller);
+            }
+
+            // emit drip election changed event
+            self.env().emit_event(DripElectionChanged {
+                account: caller,
+                enabled,
+            });
+
+            Ok(())
+        }
+
+        /// Add or remove an allowed destination of `caller`
+        pub fn do_set_allowed_destination(
+            &mut self,
+            caller: AccountId,
+            destination: AccountId,
+            allowed: bool,
+        ) -> Result<(), Error> {
+            let mut destinations = self.allowed_destinations.get(caller).unwrap_or_default();
+            let position = destinations
+                .iter()
+                .position(|&listed| listed == destination);
+            match (allowed, position) {
+                (true, None) => {
+                    // check if caller has room for another destination
+                    if destinations.len() >= MAX_ALLOWED_DESTINATIONS {
+                        return Err(Error::TooManyDestinations);
+                    }
+                    destinations.push(destination);
+                }
+                (false, Some(position)) => {
+                    destinations.remove(position);
+                }
+                // already in the requested state
+                _ => {}
+            }
+
+            if destinations.is_empty() {
+                self.allowed_destinations.remove(caller);
+            } else {
+                self.allowed_destinations.insert(caller, &destinations);
+            }
+
+            // emit allowed destination set event
+            self.env().emit_event(AllowedDestinationSet {
+                account: caller,
+                destination,
+                allowed,
+            });
+
+            Ok(())
+        }
+
+        /// Set the spend limit on behalf of `caller`
+        pub fn do_set_spend_limit(
+            &mut self,
+            caller: AccountId,
+            limit: Balance,
+            period: Timestamp,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.spend_limit = limit;
+            self.spend_period = period;
+
+            // emit spend limit set event
+            self.env().emit_event(SpendLimitSet { limit, period });
+
+            Ok(())
+        }
+
+        /// Set the verified status on behalf of `caller`
+        pub fn do_set_verified(&mut self, caller: AccountId, verified: bool) -> Result<(), Error> {
+            let verifier = self.check_verifier(caller)?;
+            self.verified = verified;
+
+            // emit verification changed event
+            self.emit_verification_changed(verifier);
+
+            Ok(())
+        }
+
+        /// Set the verification URI on behalf of `caller`
+        pub fn do_set_verification_uri(
+            &mut self,
+            caller: AccountId,
+            uri: Vec<u8>,
+        ) -> Result<(), Error> {
+            let verifier = self.check_verifier(caller)?;
+
+            // check if the uri fits the size limit
+            if uri.len() > MAX_VERIFICATION_URI_SIZE {
+                return Err(Error::VerificationUriTooLarge);
+            }
+
+            self.verification_uri = uri;
+
+            // emit verification changed event
+            self.emit_verification_changed(verifier);
+
+            Ok(())
+        }
+
+        /// Freeze or unfreeze secondary transfers on behalf of `caller`
+        pub fn do_set_secondary_freeze(
+            &mut self,
+            caller: AccountId,
+            frozen: bool,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.secondary_transfers_frozen = frozen;
+            let kind = if frozen {
+                AdminActionKind::SecondaryFreeze
+            } else {
+                AdminActionKind::SecondaryUnfreeze
+            };
+            self.log_admin(caller, kind, None, 0);
+
+            // emit secondary freeze set event
+            self.env().emit_event(SecondaryFreezeSet { frozen });
+
+            Ok(())
+        }
+
+        /// Set the voting formula on behalf of `caller`
+        pub fn do_set_voting_formula(
+            &mut self,
+            caller: AccountId,
+            formula: VotingFormula,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.voting_formula = formula;
+
+            // emit voting formula set event
+            self.env().emit_event(VotingFormulaSet { formula });
+
+            Ok(())
+        }
+
+        /// Set the opt-in fee on behalf of `caller`
+        pub fn do_set_opt_in_fee(&mut self, caller: AccountId, fee: Balance) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.opt_in_fee = fee;
+
+            // emit opt in fee set event
+            self.env().emit_event(OptInFeeSet { fee });
+
+            Ok(())
+        }
+
+        /// Set the subscription fee and period on behalf of `caller`
+        pub fn do_set_subscription_params(
+            &mut self,
+            caller: AccountId,
+            fee: Balance,
+            period: BlockNumber,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.subscription_fee = fee;
+            self.subscription_period_blocks = period;
+
+            // emit subscription params set event
+            self.env().emit_event(SubscriptionParamsSet {
+                fee,
+                period_blocks: period,
+            });
+
+            Ok(())
+        }
+
+        /// Set the transfer cooldown on behalf of `caller`
+        pub fn do_set_cooldown(
+            &mut self,
+            caller: AccountId,
+            blocks: BlockNumber,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.cooldown_blocks = blocks;
+
+            // emit cooldown set event
+            self.env().emit_event(CooldownSet { blocks });
+
+            Ok(())
+        }
+
+        /// Renew the subscription of `caller`, paying `paid`
+        pub fn do_pay_subscription(
+            &mut self,
+            caller: AccountId,
+            paid: Balance,
+        ) -> Result<(), Error> {
+            // check if caller has opted in
+            if !self.holder(caller).opted_in {
+                return Err(Error::NotOptedIn);
+            }
+
+            // check if the paid value covers the subscription fee
+            let fee = self.subscription_fee;
+            if paid < fee {
+                return Err(Error::InsufficientSubscriptionFee);
+            }
+
+            // extend the subscription from its expiry, or from now if it lapsed
+            let start = self
+                .subscription_expiry(caller)
+                .max(self.env().block_number());
+            let new_expiry = start.saturating_add(self.subscription_period_blocks);
+
+            if paid > fee {
+                self.env()
+                    .transfer(caller, paid - fee)
+                    .map_err(|_| Error::SubscriptionFeeRefundFailed)?;
+            }
+            self.opt_in_fees_collected = self.opt_in_fees_collected.saturating_add(fee);
+            self.subscription_expiry.insert(caller, &new_expiry);
+
+            // emit subscription renewed event
+            self.env().emit_event(SubscriptionRenewed {
+                account: caller,
+                new_expiry,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw `amount` of the collected opt-in fees to `to` on behalf of `caller`
+        pub fn do_withdraw_opt_in_fees(
+            &mut self,
+            caller: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the collected fees cover the amount
+            if amount > self.opt_in_fees_collected {
+                return Err(Error::InsufficientCollectedFees);
+            }
+
+            self.env()
+                .transfer(to, amount)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            self.opt_in_fees_collected -= amount;
+
+            // emit opt in fees withdrawn event
+            self.env().emit_event(OptInFeesWithdrawn { to, amount });
+
+            Ok(())
+        }
+
+        /// Return `amount` of unattributed native value to `to` on behalf of `caller`
+        pub fn do_recover_native(
+            &mut self,
+            caller: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the unattributed balance covers the amount
+            if amount > self.native_accounting().unattributed {
+                return Err(Error::InsufficientUnattributedBalance);
+            }
+
+            self.env()
+                .transfer(to, amount)
+                .map_err(|_| Error::NativeTransferFailed)?;
+
+            // emit native recovered event
+            self.env().emit_event(NativeRecovered { to, amount });
+
+            Ok(())
+        }
+
+        /// Forward `amount` of the PSP22 `token` to `to` on behalf of `caller`
+        pub fn do_recover_token(
+            &mut self,
+            caller: AccountId,
+            token: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the token is another contract
+            if token == self.asset_id() {
+                return Err(Error::CannotRecoverOwnToken);
+            }
+
+            // Note: only the variant index of a `PSP22Error` is decoded, as any error fails
+            match build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                        .push_arg(to)
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), u8>>()
+                .try_invoke()
+            {
+                Ok(Ok(Ok(()))) => {}
+                _ => return Err(Error::TokenRecoveryFailed),
+            }
+
+            // emit token recovered event
+            self.env().emit_event(TokenRecovered { token, to, amount });
+
+            Ok(())
+        }
+
+        /// Set the transfer fee on behalf of `caller`
+        pub fn do_set_transfer_fee(
+            &mut self,
+            caller: AccountId,
+            fee_bps: u16,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the fee is under the maximum
+            if fee_bps > MAX_TRANSFER_FEE_BPS {
+                return Err(Error::InvalidTransferFee);
+            }
+
+            self.transfer_fee_bps = fee_bps;
+
+            // emit transfer fee set event
+            self.env().emit_event(TransferFeeSet { fee_bps });
+
+            Ok(())
+        }
+
+        /// Set the token the transfer fee is paid in on behalf of `caller`
+        pub fn do_set_fee_token(
+            &mut self,
+            caller: AccountId,
+            token: Option<AccountId>,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.fee_token = token;
+
+            // emit fee token set event
+            self.env().emit_event(FeeTokenSet { token });
+
+            Ok(())
+        }
+
+        /// Set the royalty recipients on behalf of `caller`
+        pub fn do_set_royalty_splits(
+            &mut self,
+            caller: AccountId,
+            splits: Vec<RoyaltySplit>,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the number of recipients is within the limit
+            if splits.len() > MAX_ROYALTY_SPLITS {
+                return Err(Error::TooManyRoyaltySplits);
+            }
+
+            // check if the shares add up to at most 100%
+            let total_bps = splits
+                .iter()
+                .try_fold(0u32, |sum, split| sum.checked_add(split.share_bps))
+                .unwrap_or(u32::MAX);
+            if total_bps > 10_000 {
+                return Err(Error::RoyaltySharesMismatch);
+            }
+
+            // check if every recipient has opted in
+            if splits
+                .iter()
+                .any(|split| !self.holder(split.recipient).opted_in)
+            {
+                return Err(Error::NotOptedIn);
+            }
+
+            self.royalty_splits = splits.clone();
+
+            // emit royalty splits set event
+            self.env().emit_event(RoyaltySplitsSet { splits });
+
+            Ok(())
+        }
+
+        /// Exempt `account` from transfer fees on behalf of `caller`
+        pub fn do_set_fee_exempt(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            exempt: bool,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            if exempt {
+                self.fee_exempt.insert(account, &true);
+            } else {
+                self.fee_exempt.remove(account);
+            }
+
+            // emit fee exemption set event
+            self.env().emit_event(FeeExemptionSet { account, exempt });
+
+            Ok(())
+        }
+
+        /// Apply `diff` to the compliance lists on behalf of `caller`
+        pub fn do_apply_compliance_diff(
+            &mut self,
+            caller: AccountId,
+            diff: ComplianceDiff,
+        ) -> Result<u32, Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the diff is within the limit
+            let size = diff.add_whitelist.len()
+                + diff.remove_whitelist.len()
+                + diff.add_blacklist.len()
+                + diff.remove_blacklist.len();
+            if size > MAX_STATUS_BATCH {
+                return Err(Error::TooManyAccounts);
+            }
+
+            // count only the entries that change a list
+            let mut removed_whitelist = 0;
+            for account in diff.remove_whitelist {
+                if self.whitelist.contains(account) {
+                    self.whitelist.remove(account);
+                    removed_whitelist += 1;
+                }
+            }
+            let mut added_whitelist = 0;
+            for account in diff.add_whitelist {
+                if self.whitelist.insert(account, &()).is_none() {
+                    added_whitelist += 1;
+                }
+            }
+            self.whitelist_count = self.whitelist_count + added_whitelist - removed_whitelist;
+            let mut removed_blacklist = 0;
+            for account in diff.remove_blacklist {
+                if self.blacklist.contains(account) {
+                    self.blacklist.remove(account);
+                    removed_blacklist += 1;
+                }
+            }
+            let mut added_blacklist = 0;
+            for account in diff.add_blacklist {
+                if self.blacklist.insert(account, &()).is_none() {
+                    added_blacklist += 1;
+                }
+            }
+
+            // emit compliance diff applied event
+            self.env().emit_event(ComplianceDiffApplied {
+                added_whitelist,
+                removed_whitelist,
+                added_blacklist,
+                removed_blacklist,
+            });
+
+            Ok(added_whitelist + removed_whitelist + added_blacklist + removed_blacklist)
+        }
+
+        /// Exempt `account` from freezes on behalf of `caller`
+        pub fn do_set_freeze_exempt(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            exempt: bool,
+        ) -> Result<(), Error> {
+            // check if caller is the freeze address
+            if caller != self.freeze_id {
+                return Err(Error::NotFreezeId);
+            }
+
+            if exempt {
+                self.freeze_exempt.insert(account, &true);
+            } else {
+                self.freeze_exempt.remove(account);
+            }
+
+            // emit freeze exemption changed event
+            self.env()
+                .emit_event(FreezeExemptionChanged { account, exempt });
+
+            Ok(())
+        }
+
+        /// Publish a reserve proof on behalf of `caller`
+        pub fn do_publish_reserve_proof(
+            &mut self,
+            caller: AccountId,
+            root: [u8; 32],
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            let block = self.env().block_number();
+            self.reserve_proof = Some(root);
+            self.reserve_proof_block = block;
+
+            // emit reserve proof published event
+            self.env().emit_event(ReserveProofPublished { root, block });
+
+            Ok(())
+        }
+
+        /// Set the asset URL on behalf of `caller`
+        pub fn do_set_url(&mut self, caller: AccountId, new_url: String) -> Result<(), Error> {
+            // check if the url can change at all
+            if !self.mutable_url {
+                return Err(Error::ImmutableField);
+            }
+
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the url has an allowed scheme, when required
+            check_url(&new_url, self.strict_url)?;
+
+            let old = core::mem::replace(&mut self.url, new_url.clone());
+            self.log_admin(caller, AdminActionKind::UrlChange, None, 0);
+
+            // emit url changed event
+            self.env().emit_event(UrlChanged {
+                old,
+                new: new_url,
+                by: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Set the jurisdiction of `account` on behalf of `caller`
+        pub fn do_set_jurisdiction(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            code: [u8; 2],
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            let code = if code == [0; 2] {
+                self.jurisdiction_codes.remove(account);
+                None
+            } else {
+                // check if the code is two uppercase letters
+                if !is_jurisdiction_code(code) {
+                    return Err(Error::InvalidJurisdiction);
+                }
+                self.jurisdiction_codes.insert(account, &code);
+                Some(code)
+            };
+
+            // emit jurisdiction set event
+            self.env().emit_event(JurisdictionSet { account, code });
+
+            Ok(())
+        }
+
+        /// Set the tax rate between two jurisdictions on behalf of `caller`
+        pub fn do_set_tax_rate(
+            &mut self,
+            caller: AccountId,
+            from_code: [u8; 2],
+            to_code: [u8; 2],
+            rate_bps: u32,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if both codes are two uppercase letters
+            if !is_jurisdiction_code(from_code) || !is_jurisdiction_code(to_code) {
+                return Err(Error::InvalidJurisdiction);
+            }
+
+            // check if the rate is at most 100%
+            if rate_bps > 10_000 {
+                return Err(Error::InvalidTaxRate);
+            }
+
+            if rate_bps == 0 {
+                self.tax_rates.remove((from_code, to_code));
+            } else {
+                self.tax_rates.insert((from_code, to_code), &rate_bps);
+            }
+
+            // emit tax rate set event
+            self.env().emit_event(TaxRateSet {
+                jurisdiction: (from_code, to_code),
+                rate_bps,
+            });
+
+            Ok(())
+        }
+
+        /// Set the clawback cap on behalf of `caller`
+        pub fn do_set_clawback_cap(
+            &mut self,
+            caller: AccountId,
+            cap: Balance,
+            period_blocks: BlockNumber,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.clawback_period_cap = cap;
+            self.clawback_period_blocks = period_blocks;
+
+            // emit clawback cap set event
+            self.env().emit_event(ClawbackCapSet { cap, period_blocks });
+
+            Ok(())
+        }
+
+        /// Set the transfer hook on behalf of `caller`
+        pub fn do_set_transfer_hook(
+            &mut self,
+            caller: AccountId,
+            hook: Option<AccountId>,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            self.transfer_hook = hook;
+
+            // emit transfer hook set event
+            self.env().emit_event(TransferHookSet { hook });
+
+            Ok(())
+        }
+
+        /// Deploy a clone of the asset on behalf of `caller`
+        pub fn do_clone_asset(
+            &mut self,
+            caller: AccountId,
+            override_total: Option<Balance>,
+            override_manager: Option<AccountId>,
+        ) -> Result<AccountId, Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            let config = self.clone_config(override_total, override_manager);
+            let code_hash = self.env().own_code_hash().map_err(|_| Error::CloneFailed)?;
+            let salt = self.clone_count.to_le_bytes();
+            let clone = match build_create::<SubsaRef>()
+                .code_hash(code_hash)
+                .gas_limit(0)
+                .endowment(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("new_with_config")))
+                        .push_arg(config),
+                )
+                .salt_bytes(salt)
+                .returns::<Result<SubsaRef, Error>>()
+                .try_instantiate()
+            {
+                Ok(Ok(Ok(clone))) => ink::ToAccountId::to_account_id(&clone),
+                Ok(Ok(Err(error))) => return Err(error),
+                _ => return Err(Error::CloneFailed),
+            };
+            self.clone_count += 1;
+
+            // emit asset cloned event
+            self.env().emit_event(AssetCloned {
+                original: self.asset_id(),
+                clone,
+            });
+
+            Ok(clone)
+        }
+
+        /// Register the asset symbol with `registry` on behalf of `caller`
+        pub fn do_register_symbol(
+            &mut self,
+            caller: AccountId,
+            registry: AccountId,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the asset has a symbol
+            if self.asset_symbol == [0; 12] {
+                return Err(Error::InvalidSymbol);
+            }
+
+            match build_call::<Environment>()
+                .call(registry)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "SymbolRegistry::register_asset"
+                    )))
+                    .push_arg(self.asset_symbol),
+                )
+                .returns::<Result<(), Error>>()
+                .try_invoke()
+            {
+                Ok(Ok(result)) => result,
+                _ => Err(Error::RegistryCallFailed),
+            }
+        }
+
+        /// Add a time lock on behalf of `caller`
+        pub fn do_add_time_lock(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            amount: Balance,
+            expires_at: BlockNumber,
+        ) -> Result<u64, Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if the lock is not empty and expires in the future
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if expires_at <= self.env().block_number() {
+                return Err(Error::InvalidLockExpiry);
+            }
+
+            // check if the account's unlocked balance covers the amount
+            if amount > self.available_balance(account) {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            // check if the account has room for another lock
+            let mut locks = self.locks_of(account);
+            if locks.len() >= MAX_LOCKS_PER_ACCOUNT {
+                return Err(Error::TooManyLocks);
+            }
+
+            let lock_id = self.next_lock_id;
+            self.next_lock_id += 1;
+            locks.push(LockEntry {
+                id: lock_id,
+                amount,
+                expires_at,
+            });
+            self.lock_entries.insert(account, &locks);
+
+            // emit lock added event
+            self.env().emit_event(LockAdded {
+                account,
+                lock_id,
+                amount,
+                expires_at,
+            });
+
+            Ok(lock_id)
+        }
+
+        /// Remove the expired time locks of `account`
+        pub fn do_remove_expired_locks(&mut self, account: AccountId) -> Result<Balance, Error> {
+            let now = self.env().block_number();
+            let (expired, active): (Vec<LockEntry>, Vec<LockEntry>) = self
+                .locks_of(account)
+                .into_iter()
+                .partition(|lock| lock.expires_at <= now);
+            if expired.is_empty() {
+                return Ok(0);
+            }
+
+            if active.is_empty() {
+                self.lock_entries.remove(account);
+            } else {
+                self.lock_entries.insert(account, &active);
+            }
+
+            // emit a lock expired event per removed lock
+            let mut released: Balance = 0;
+            for lock in expired {
+                released = released.saturating_add(lock.amount);
+                self.env().emit_event(LockExpired {
+                    account,
+                    lock_id: lock.id,
+                    amount: lock.amount,
+                });
+            }
+
+            Ok(released)
+        }
+
+        /// Opt in and fund `account` from the reserve on behalf of `caller`
+        pub fn do_opt_in_and_fund(
+            &mut self,
+            caller: AccountId,
+            account: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the reserve address
+            if caller != self.reserve_id {
+                return Err(Error::NotReserveId);
+            }
+
+            // check if the amount reaches the minimum opt in balance
+            if amount < self.min_opt_in_balance {
+                return Err(Error::BelowMinimumOptInBalance);
+            }
+
+            // check if the reserve's unlocked balance covers the amount, it can send to the
+            // account, and an NFT is sent whole
+            // Note: checked upfront, so that the transfer cannot fail once the account opted in
+            if self.is_nft() && amount != 1 {
+                return Err(Error::NFTPartialTransferNotAllowed);
+            }
+            if amount > self.available_balance(caller) {
+                return Err(Error::NotEnoughBalance);
+            }
+            self.check_destination(caller, account)?;
+
+            self.opt_in_account(account, true)?;
+            self.do_transfer(caller, account, amount)
+        }
+
+        /// Authorize or revoke a distributor on behalf of `caller`
+        pub fn do_set_distributor(
+            &mut self,
+            caller: AccountId,
+            distributor: AccountId,
+            allowed: bool,
+        ) -> Result<(), Error> {
+            // check if caller is the reserve address
+            if caller != self.reserve_id {
+                return Err(Error::NotReserveId);
+            }
+
+            if allowed {
+                self.distributors.insert((caller, distributor), &());
+            } else {
+                self.distributors.remove((caller, distributor));
+            }
+
+            // emit distributor set event
+            self.env().emit_event(DistributorSet {
+                reserve_id: caller,
+                distributor,
+                allowed,
+            });
+
+            Ok(())
+        }
+
+        /// Fund the address holding `role` from the reserve on behalf of `caller`
+        pub fn do_fund_role(
+            &mut self,
+            caller: AccountId,
+            role: Role,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            // check if caller is the reserve address
+            if caller != self.reserve_id {
+                return Err(Error::NotReserveId);
+            }
+
+            // check if the role is not cleared
+            if self.role_cleared(role) {
+                return Err(Error::RoleDisabled);
+            }
+            let account = self.role_id(role);
+
+            if self.holder(account).opted_in {
+                self.do_transfer(caller, account, amount)?;
+            } else {
+                self.do_opt_in_and_fund(caller, account, amount)?;
+            }
+
+            // emit role funded event
+            self.env().emit_event(RoleFunded {
+                role,
+                account,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Distribute tokens from the reserve on behalf of `caller`
+        pub fn do_distribute_from_reserve(
+            &mut self,
+            caller: AccountId,
+            recipients: Vec<(AccountId, Balance)>,
+        ) -> Result<(), Error> {
+            // check if caller is the reserve address or one of its distributors
+            let reserve = self.reserve_id;
+            if caller != reserve && !self.is_distributor(caller) {
+                return Err(Error::NotReserveId);
+            }
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if the reserve can send to every recipient
+            for &(recipient, _) in &recipients {
+                self.check_destination(reserve, recipient)?;
+            }
+
+            self.move_balances(TransferOperation::Distribute, reserve, &recipients)?;
+
+            // emit reserve distribution event
+            // Note: move_balances checked that the amounts add up without overflowing
+            self.env().emit_event(ReserveDistribution {
+                reserve_id: reserve,
+                by: caller,
+                recipient_count: recipients.len() as u32,
+                total_distributed: recipients.iter().map(|&(_, amount)| amount).sum(),
+            });
+
+            Ok(())
+        }
+
+        /// Activate the asset on behalf of `caller`
+        pub fn do_activate(&mut self, caller: AccountId) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if asset is pending or suspended
+            if !matches!(
+                self.asset_status,
+                AssetStatus::Pending | AssetStatus::Suspended
+            ) {
+                return Err(Error::InvalidStatusTransition);
+            }
+
+            self.set_status(AssetStatus::Active);
+            self.log_admin(
+                caller,
+                AdminActionKind::StatusChange(AssetStatus::Active),
+                None,
+                0,
+            );
+            Ok(())
+        }
+
+        /// Import `entries` into the pending asset on behalf of `caller`
+        pub fn do_import_holdings(
+            &mut self,
+            caller: AccountId,
+            entries: Vec<(AccountId, Balance, bool)>,
+        ) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if asset was never activated
+            if self.asset_status != AssetStatus::Pending {
+                return Err(Error::ImportClosed);
+            }
+
+            // check if the batch is within the limit, frozen flags are allowed, and the
+            // reserve covers the amounts
+            if entries.len() > MAX_STATUS_BATCH {
+                return Err(Error::TooManyAccounts);
+            }
+            if !self.default_frozen && entries.iter().any(|&(_, _, frozen)| frozen) {
+                return Err(Error::NotFreezable);
+            }
+            let moves: Vec<(AccountId, Balance)> = entries
+                .iter()
+                .map(|&(account, amount, _)| (account, amount))
+                .collect();
+            let amount = moves
+                .iter()
+                .try_fold(0 as Balance, |sum, &(_, amount)| sum.checked_add(amount))
+                .ok_or(Error::NotEnoughBalance)?;
+            if amount > self.available_balance(self.reserve_id) {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            self.seed_holdings(&moves)?;
+
+            // set the frozen flags that differ
+            for (account, _, frozen) in entries {
+                let holder = self.holder(account);
+                if holder.frozen != frozen {
+                    self.set_frozen(account, holder, frozen);
+                }
+            }
+
+            // emit holdings imported event
+            self.env().emit_event(HoldingsImported {
+                count: moves.len() as u32,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Suspend the asset on behalf of `caller`
+        pub fn do_suspend(&mut self, caller: AccountId) -> Result<(), Error> {
+            // check if caller is the manager
+            if caller != self.manager_id {
+                return Err(Error::NotManagerId);
+            }
+
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::InvalidStatusTransition);
+            }
+
+            self.set_status(AssetStatus::Suspended);
+            self.log_admin(
+                caller,
+                AdminActionKind::StatusChange(AssetStatus::Suspended),
+                None,
+                0,
+            );
+            Ok(())
+        }
+
+        /// Opts in `account`, by itself or `forced` by the reserve.
+        fn opt_in_account(&mut self, account: AccountId, forced: bool) -> Result<(), Error> {
+            // check if asset is active
+            if self.asset_status != AssetStatus::Active {
+                return Err(Error::AssetNotActive);
+            }
+
+            // check if account has already opted in
+            let holder = self.holder(account);
+            if holder.opted_in {
+                return Err(Error::AlreadyOptedIn);
+            }
+
+            // check if account passed KYC, when required
+            if self.kyc_required && self.kyc_status_of(account) != KYC_APPROVED {
+                return Err(Error::KycNotApproved);
+            }
+
+            self.record_opt_in(account, holder, forced);
+
+            Ok(())
+        }
+
+        /// Opts in `account`, whose current state is `holder`, emitting OptIn.
+        /// Note: does not check anything, see `opt_in_account`.
+        fn record_opt_in(&mut self, account: AccountId, mut holder: HolderState, forced: bool) {
+            // update account's opt in status, keeping the original opt in time
+            let now = Some(self.env().block_timestamp());
+            holder.opted_in = true;
+            holder.opted_in_at = holder.opted_in_at.or(now);
+            holder.last_activity = now;
+            self.holders.insert(account, &holder);
+            self.index_holder(account);
+
+            // emit opt in event
+            self.env().emit_event(OptIn {
+                asset_id: self.asset_id(),
+                account,
+                timestamp: self.env().block_timestamp(),
+                frozen: self.effectively_frozen(account, &holder),
+                deposit: 0,
+                forced,
+            });
+        }
+
+        /// Opts in each account of `distribution` and funds it from the reserve, at creation or
+        /// on import.
+        /// Note: KYC and the asset status do not apply, since the creator chose the accounts.
+        fn seed_holdings(&mut self, distribution: &[(AccountId, Balance)]) -> Result<(), Error> {
+            for &(account, _) in distribution {
+                // check if the account is neither the zero address nor the contract
+                if account == zero_account() {
+                    return Err(Error::TransferToZeroAddress);
+                }
+                if account == self.env().account_id() {
+                    return Err(Error::TransferToAssetContract);
+                }
+
+                let holder = self.holder(account);
+                if !holder.opted_in {
+                    self.record_opt_in(account, holder, true);
+                }
+            }
+
+            let reserve = self.reserve_id;
+            self.move_balances(TransferOperation::Transfer, reserve, distribution)?;
+
+            // emit transfer event for each entry
+            for &(receiver, amount) in distribution {
+                self.env().emit_event(Transfer {
+                    sender: reserve,
+                    receiver,
+                    asset_id: self.asset_id(),
+                    amount: Some(amount),
+                    timestamp: self.env().block_timestamp(),
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Returns the verifier if it is `caller`.
+        /// Note: fails with VerifierDisabled for an asset without verifier.
+        fn check_verifier(&self, caller: AccountId) -> Result<AccountId, Error> {
+            match self.verifier {
+                None => Err(Error::VerifierDisabled),
+                Some(verifier) if verifier != caller => Err(Error::NotVerifier),
+                Some(verifier) => Ok(verifier),
+            }
+        }
+
+        /// Emits the current verified status and verification URI.
+        fn emit_verification_changed(&self, verifier: AccountId) {
+            self.env().emit_event(VerificationChanged {
+                verifier,
+                verified: self.verified,
+                verification_uri: self.verification_uri.clone(),
+            });
+        }
+
+        /// Keeps the opt-in fee of `account` out of `paid`, refunding the excess to `payer`
+        fn collect_opt_in_fee(
+            &mut self,
+            account: AccountId,
+            payer: AccountId,
+            paid: Balance,
+        ) -> Result<(), Error> {
+            let fee = self.opt_in_fee;
+            if paid > fee {
+                self.env()
+                    .transfer(payer, paid - fee)
+                    .map_err(|_| Error::OptInFeeRefundFailed)?;
+            }
+
+            self.opt_in_fees_collected = self.opt_in_fees_collected.saturating_add(fee);
+
+            // emit opt in fee collected event
+            if fee > 0 {
+                self.env().emit_event(OptInFeeCollected { account, fee });
+            }
+
+            Ok(())
+        }
+
+        /// Moves `fee` of the PSP22 `token` from `sender` to the reserve, out of the allowance
+        /// `sender` gave this contract.
+        fn collect_token_fee(
+            &mut self,
+            token: AccountId,
+            sender: AccountId,
+            fee: Balance,
+        ) -> Result<(), Error> {
+            // Note: only the variant index of a `PSP22Error` is decoded, as any error fails
+            match build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "PSP22::transfer_from"
+                    )))
+                    .push_arg(sender)
+                    .push_arg(self.reserve_id)
+                    .push_arg(fee)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), u8>>()
+                .try_invoke()
+            {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::FeeTokenTransferFailed),
+            }
+        }
+
+        /// Returns the fee `sender` pays on a transfer of `amount`, rounded down.
+        /// Note: the reserve, which collects the fees, and fee-exempt senders pay none.
+        fn transfer_fee(&self, sender: AccountId, amount: Balance) -> Balance {
+            if self.transfer_fee_bps == 0
+                || sender == self.reserve_id
+                || self.reserve_id == zero_account()
+                || self.is_fee_exempt(sender)
+            {
+                return 0;
+            }
+
+            // split the amount, so that the product cannot overflow
+            let bps = Balance::from(self.transfer_fee_bps);
+            amount / 10_000 * bps + amount % 10_000 * bps / 10_000
+        }
+
+        /// Returns the share of a transfer of `amount` by `sender` that is burnt, rounded down.
+        /// Note: distributions from the reserve burn nothing.
+        fn transfer_burn(&self, sender: AccountId, amount: Balance) -> Balance {
+            if self.burn_on_transfer_bps == 0 || sender == self.reserve_id {
+                return 0;
+            }
+
+            // split the amount, so that the product cannot overflow
+            let bps = Balance::from(self.burn_on_transfer_bps);
+            amount / 10_000 * bps + amount % 10_000 * bps / 10_000
+        }
+
+        /// Returns the tax on a transfer of `amount` from `sender` to `receiver`, rounded down,
+        /// and the jurisdictions it is due between, if both have one.
+        /// Note: the reserve pays none.
+        fn transfer_tax(
+            &self,
+            sender: AccountId,
+            receiver: AccountId,
+            amount: Balance,
+        ) -> Option<(Balance, JurisdictionPair)> {
+            if sender == self.reserve_id || self.reserve_id == zero_account() {
+                return None;
+            }
+            let jurisdiction = (
+                self.jurisdiction_codes.get(sender)?,
+                self.jurisdiction_codes.get(receiver)?,
+            );
+            let rate_bps = self.tax_rates.get(jurisdiction)?;
+
+            // split the amount, so that the product cannot overflow
+            let bps = Balance::from(rate_bps);
+            let tax = amount / 10_000 * bps + amount % 10_000 * bps / 10_000;
+            Some((tax, jurisdiction))
+        }
+
+        /// Returns the configuration `clone_asset` deploys a clone with.
+        fn clone_config(
+            &self,
+            override_total: Option<Balance>,
+            override_manager: Option<AccountId>,
+        ) -> AssetConfig {
+            AssetConfig {
+                asset_name: self.asset_name.clone(),
+                unit_name: self.unit_name.clone(),
+                total: override_total.unwrap_or(self.total),
+                decimals: self.decimals,
+                default_frozen: self.default_frozen,
+                url: self.url.clone(),
+                metadata_hash: self.metadata_hash,
+                manager: Some(override_manager.unwrap_or(self.manager_id)),
+                reserve: Some(self.reserve_id),
+                freeze: Some(self.freeze_id),
+                clawback: Some(self.clawback_id),
+                clawback_sunset: self.clawback_sunset,
+                source_commitment: self.source_commitment,
+                enforce_role_separation: self.enforce_role_separation,
+                initial_distribution: Vec::new(),
+                burn_on_transfer_bps: self.burn_on_transfer_bps,
+                ..Default::default()
+            }
+        }
+
+        /// Returns the royalty each recipient receives on a transfer of `amount` by `sender`,
+        /// rounded down, leaving out zero royalties.
+        /// Note: the reserve pays none.
+        fn royalties(&self, sender: AccountId, amount: Balance) -> Vec<(AccountId, Balance)> {
+            if sender == self.reserve_id {
+                return Vec::new();
+            }
+
+            // split the amount, so that the product cannot overflow
+            self.royalty_splits
+                .iter()
+                .map(|split| {
+                    let bps = Balance::from(split.share_bps);
+                    let royalty = amount / 10_000 * bps + amount % 10_000 * bps / 10_000;
+                    (split.recipient, royalty)
+                })
+                .filter(|&(_, royalty)| royalty > 0)
+                .collect()
+        }
+
+        /// Returns whether `account`, with state `holder`, is frozen.
+        /// Note: a freeze whose `freeze_until` time has passed no longer counts, even before
+        /// `lift_lapsed_freeze` clears it.
+        fn effectively_frozen(&self, account: AccountId, holder: &HolderState) -> bool {
+            holder.frozen
+                && self
+                    .freeze_deadlines
+                    .get(account)
+                    .is_none_or(|deadline| deadline > self.env().block_timestamp())
+        }
+
+        /// Unfreezes `account` if its `freeze_until` time has passed, returning its state.
+        fn lift_lapsed_freeze(&mut self, account: AccountId) -> HolderState {
+            let holder = self.holder(account);
+            if holder.frozen && !self.effectively_frozen(account, &holder) {
+                self.set_frozen(account, holder, false);
+                return self.holder(account);
+            }
+
+            holder
+        }
+
+        /// Removes `amount` from the lien of `borrower` for `lender`, and from its total.
+        fn reduce_lien(&mut self, borrower: AccountId, lender: AccountId, amount: Balance) {
+            let lien = self.lien(borrower, lender) - amount;
+            if lien == 0 {
+                self.liens.remove((borrower, lender));
+            } else {
+                self.liens.insert((borrower, lender), &lien);
+            }
+
+            // the total covers every lien of the borrower, so it cannot underflow
+            let liened = self.liened_balance_of(borrower) - amount;
+            if liened == 0 {
+                self.liened.remove(borrower);
+            } else {
+                self.liened.insert(borrower, &liened);
+            }
+        }
+
+        /// Returns whether `account` is frozen and not freeze-exempt, lifting a lapsed freeze.
+        fn blocked_by_freeze(&mut self, account: AccountId) -> bool {
+            self.lift_lapsed_freeze(account).frozen && !self.is_freeze_exempt(account)
+        }
+
+        /// Sets the frozen status of `account`, recording it in its history and emitting Freeze.
+        fn set_frozen(&mut self, account: AccountId, mut holder: HolderState, freeze: bool) {
+            holder.frozen = freeze;
+            holder.last_activity = Some(self.env().block_timestamp());
+            self.holders.insert(account, &holder);
+            self.freeze_deadlines.remove(account);
+
+            // record the change in the account's freeze history, evicting the oldest entry
+            let mut history = self.freeze_history.get(account).unwrap_or_default();
+            if history.len() >= FREEZE_HISTORY_CAPACITY {
+                history.remove(0);
+            }
+            history.push(FreezeEvent {
+                block: self.env().block_number(),
+                frozen: freeze,
+                freeze_id: self.freeze_id,
+            });
+            self.freeze_history.insert(account, &history);
+
+            // emit freeze event
+            self.env().emit_event(Freeze {
+                asset_id: self.asset_id(),
+                account,
+                freeze,
+                freeze_id: self.freeze_id,
+                timestamp: self.env().block_timestamp(),
+            });
+        }
+
+        /// Appends `account` to the index of the opted-in accounts.
+        fn index_holder(&mut self, account: AccountId) {
+            self.holder_accounts.insert(self.opted_in_count, &account);
+            self.holder_positions.insert(account, &self.opted_in_count);
+            self.opted_in_count += 1;
+        }
+
+        /// Removes `account` from the index of the opted-in accounts, moving the last indexed
+        /// account into its position.
+        fn unindex_holder(&mut self, account: AccountId) {
+            self.opted_in_count -= 1;
+            let last = self.opted_in_count;
+            let Some(position) = self.holder_positions.take(account) else {
+                return;
+            };
+            if let Some(moved) = self.holder_accounts.take(last) {
+                if position != last {
+                    self.holder_accounts.insert(position, &moved);
+                    self.holder_positions.insert(moved, &position);
+                }
+            }
+        }
+
+        /// Moves the asset to the `new` lifecycle state, emitting StatusChanged.
+        fn set_status(&mut self, new: AssetStatus) {
+            let old = self.asset_status;
+            self.asset_status = new;
+
+            // emit status changed event
+            self.env().emit_event(StatusChanged { old, new });
+        }
+
+        /// Moves `amount` of tokens from `from` to `to`, as part of `operation`.
+        /// Note: fails if `from` does not hold `amount` or `to` has not opted in.
+        fn move_balance(
+            &mut self,
+            operation: TransferOperation,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            self.move_balances(operation, from, &[(to, amount)])
+        }
+
+        /// Moves tokens from `from` to each of the `(receiver, amount)` pairs of `moves`, as
+        /// part of `operation`.
+        /// Note: fails if `from` does not hold the sum of the amounts, a receiver has not
+        /// opted in, or the transfer hook rejects a move; everything is checked before
+        /// anything is written, and each account is written once.
+        fn move_balances(
+            &mut self,
+            operation: TransferOperation,
+            from: AccountId,
+            moves: &[(AccountId, Balance)],
+        ) -> Result<(), Error> {
+            // check if sender has enough balance
+            let total = moves
+                .iter()
+                .try_fold(0 as Balance, |sum, &(_, amount)| sum.checked_add(amount))
+                .ok_or(Error::NotEnoughBalance)?;
+            let mut sender = self.holder(from);
+            if sender.balance < total {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            // debit the sender, accruing its time-weighted balance first
+            let now = Some(self.env().block_timestamp());
+            let mut sender_totals = self.lifetime_totals.get(from).unwrap_or_default();
+            sender_totals.sent = sender_totals
+                .sent
+                .checked_add(total)
+                .ok_or(Error::StatsOverflow)?;
+            self.accrue_time_weighted_balance(&mut sender);
+            sender.balance -= total;
+            sender.last_activity = now;
+
+            // credit the receivers, once per distinct receiver
+            // Note: a receiver starts from the debited sender when it is the sender itself,
+            // so that moving to oneself is a no-op
+            let mut receivers: Vec<(AccountId, HolderState, LifetimeTotals)> = Vec::new();
+            for &(to, amount) in moves {
+                let index = match receivers.iter().position(|(account, ..)| *account == to) {
+                    Some(index) => index,
+                    None => {
+                        let (mut receiver, totals) = if to == from {
+                            (sender, sender_totals)
+                        } else {
+                            (
+                                self.holder(to),
+                                self.lifetime_totals.get(to).unwrap_or_default(),
+                            )
+                        };
+
+                        // check if receiver has opted in
+                        if !receiver.opted_in {
+                            return Err(Error::NotOptedIn);
+                        }
+
+                        self.accrue_time_weighted_balance(&mut receiver);
+                        receivers.push((to, receiver, totals));
+                        receivers.len() - 1
+                    }
+                };
+                let (_, receiver, totals) = &mut receivers[index];
+                receiver.balance += amount;
+                receiver.last_activity = now;
+                totals.received = totals
+                    .received
+                    .checked_add(amount)
+                    .ok_or(Error::StatsOverflow)?;
+            }
+
+            // check the moves with the hook and mirror them before writing anything, so that
+            // a failure changes nothing
+            for &(to, amount) in moves {
+                if to != from && amount > 0 {
+                    self.before_transfer_hook(operation, from, to, amount)?;
+                    self.mirror_transfer(from, to, amount)?;
+                }
+            }
+
+            // write the sender first, so that its entry as a receiver takes precedence
+            self.holders.insert(from, &sender);
+            self.lifetime_totals.insert(from, &sender_totals);
+            for (to, receiver, totals) in &receivers {
+                self.holders.insert(*to, receiver);
+                self.lifetime_totals.insert(*to, totals);
+            }
+
+            // track the holder of the single unit
+            if self.nft_owner.is_some() {
+                if let Some(&(to, _)) = moves.iter().find(|&&(_, amount)| amount > 0) {
+                    self.nft_owner = Some(to);
+                }
+            }
+
+            // notify the hook of the written moves
+            for &(to, amount) in moves {
+                if to != from && amount > 0 {
+                    self.after_movement(operation, from, to, amount)?;
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Subsa {
+        /// Takes `amount` out of the balance of `from`, e.g. into an escrow.
+        fn debit(&mut self, from: AccountId, amount: Balance) -> Result<(), Error> {
+            let mut holder = self.holder(from);
+            if holder.balance < amount {
+                return Err(Error::NotEnoughBalance);
+            }
+            let mut totals = self.lifetime_totals.get(from).unwrap_or_default();
+            totals.sent = totals
+                .sent
+                .checked_add(amount)
+                .ok_or(Error::StatsOverflow)?;
+            let escrow = self.env().account_id();
+            self.before_transfer_hook(TransferOperation::Escrow, from, escrow, amount)?;
+            self.mirror_transfer(from, escrow, amount)?;
+
+            self.accrue_time_weighted_balance(&mut holder);
+            holder.balance -= amount;
+            holder.last_activity = Some(self.env().block_timestamp());
+            self.holders.insert(from, &holder);
+            self.lifetime_totals.insert(from, &totals);
+            self.after_movement(TransferOperation::Escrow, from, escrow, amount)
+        }
+
+        /// Destroys `amount` of the balance of `from`, taking it out of the current supply.
+        fn burn_from(&mut self, from: AccountId, amount: Balance) -> Result<(), Error> {
+            let mut holder = self.holder(from);
+            if holder.balance < amount {
+                return Err(Error::NotEnoughBalance);
+            }
+            let mut totals = self.lifetime_totals.get(from).unwrap_or_default();
+            totals.sent = totals
+                .sent
+                .checked_add(amount)
+                .ok_or(Error::StatsOverflow)?;
+            self.mirror_burn(from, amount)?;
+
+            self.accrue_time_weighted_balance(&mut holder);
+            holder.balance -= amount;
+            self.holders.insert(from, &holder);
+            self.lifetime_totals.insert(from, &totals);
+            self.burned += amount;
+            Ok(())
+        }
+
+        /// Adds `amount` taken out by `debit` to the balance of `to`.
+        /// Note: does not check whether `to` has opted in.
+        fn credit(&mut self, to: AccountId, amount: Balance) -> Result<(), Error> {
+            let mut holder = self.holder(to);
+            let mut totals = self.lifetime_totals.get(to).unwrap_or_default();
+            totals.received = totals
+                .received
+                .checked_add(amount)
+                .ok_or(Error::StatsOverflow)?;
+            let escrow = self.env().account_id();
+            self.before_transfer_hook(TransferOperation::Release, escrow, to, amount)?;
+            self.mirror_transfer(escrow, to, amount)?;
+
+            self.accrue_time_weighted_balance(&mut holder);
+            holder.balance += amount;
+            holder.last_activity = Some(self.env().block_timestamp());
+            self.holders.insert(to, &holder);
+            self.lifetime_totals.insert(to, &totals);
+
+            // track the holder of the single unit
+            if self.nft_owner.is_some() {
+                self.nft_owner = Some(to);
+            }
+            self.after_movement(TransferOperation::Release, escrow, to, amount)
+        }
+
+        /// Runs the state-changing message `f`, failing if another one is running.
+        /// Note: the flag written through to storage is only needed, and only kept, while a
+        /// transfer hook is set, since the hook is the only contract the asset calls.
+        fn non_reentrant<T>(
+            &mut self,
+            f: impl FnOnce(&mut Self) -> Result<T, Error>,
+        ) -> Result<T, Error> {
+            if self.transfer_hook.is_none() {
+                return f(self);
+            }
+            self.check_not_entered()?;
+            self.entered.set(&true);
+            let result = f(self);
+            self.entered.set(&false);
+            result
+        }
+
+        /// Fails if a state-changing message is running, i.e. if the transfer hook re-enters.
+        fn check_not_entered(&self) -> Result<(), Error> {
+            if self.entered.get().unwrap_or(false) {
+                return Err(Error::ReentrancyDetected);
+            }
+            Ok(())
+        }
+
+        /// Asks the transfer hook, if any, whether `from` can move `amount` to `to`.
+        // Note: the hook can re-enter the asset for reads; state-changing messages are blocked
+        // by `non_reentrant`
+        fn before_transfer_hook(
+            &self,
+            operation: TransferOperation,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let Some(hook) = self.transfer_hook else {
+                return Ok(());
+            };
+            match build_call::<Environment>()
+                .call(hook)
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "TransferHook::before_transfer"
+                    )))
+                    .push_arg(operation)
+                    .push_arg(from)
+                    .push_arg(to)
+                    .push_arg(amount),
+                )
+                .returns::<Result<(), u32>>()
+                .try_invoke()
+            {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(code))) => Err(Error::HookRejected(code)),
+                _ => Err(Error::HookCallFailed),
+            }
+        }
+
+        /// Counts the written movement of `amount` from `from` to `to`, and notifies the
+        /// transfer hook, if any.
+        fn after_movement(
+            &mut self,
+            operation: TransferOperation,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            self.lifetime_transfers = self.lifetime_transfers.saturating_add(1);
+            self.log_supply_event(operation, from, to, amount);
+
+            let Some(hook) = self.transfer_hook else {
+                return Ok(());
+            };
+            match build_call::<Environment>()
+                .call(hook)
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "TransferHook::after_transfer"
+                    )))
+                    .push_arg(operation)
+                    .push_arg(from)
+                    .push_arg(to)
+                    .push_arg(amount),
+                )
+                .returns::<()>()
+                .try_invoke()
+            {
+                Ok(Ok(())) => Ok(()),
+                _ => Err(Error::HookCallFailed),
+            }
+        }
+
+        /// Appends the written movement of `amount` from `from` to `to` to the supply audit
+        /// trail, if it is a revoke or changed the circulating supply.
+        fn log_supply_event(
+            &mut self,
+            operation: TransferOperation,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) {
+            let operation = if operation == TransferOperation::Revoke {
+                SupplyOperation::Revoke
+            } else if from == self.reserve_id {
+                SupplyOperation::Mint
+            } else if to == self.reserve_id {
+                SupplyOperation::Burn
+            } else {
+                return;
+            };
+
+            self.supply_events.insert(
+                self.supply_event_count,
+                &SupplyEvent {
+                    block: self.env().block_number(),
+                    operation,
+                    amount,
+                    total_after: self.circulating_supply(),
+                },
+            );
+            self.supply_event_count = self.supply_event_count.saturating_add(1);
+        }
+
+        /// Appends an action of `actor` on `account`, of `value`, to the admin log.
+        fn log_admin(
+            &mut self,
+            actor: AccountId,
+            kind: AdminActionKind,
+            account: Option<AccountId>,
+            value: Balance,
+        ) {
+            self.admin_actions.insert(
+                self.admin_action_count,
+                &AdminAction {
+                    actor,
+                    kind,
+                    account,
+                    value,
+                    timestamp: self.env().block_timestamp(),
+                },
+            );
+            self.admin_action_count = self.admin_action_count.saturating_add(1);
+        }
+
+        /// Returns the index of `account` in the running `balances` of a batch, adding it with
+        /// its stored balance on first use.
+        fn batch_balance(
+            &self,
+            balances: &mut Vec<(AccountId, Balance)>,
+            account: AccountId,
+        ) -> usize {
+            match balances.iter().position(|(entry, _)| *entry == account) {
+                Some(index) => index,
+                None => {
+                    balances.push((account, self.holder(account).balance));
+                    balances.len() - 1
+                }
+            }
+        }
+
+        /// Returns the amount of `stream` accrued so far, withdrawn or not.
+        // Note: the conversion is only useless when `Balance` is `u128`
+        #[allow(clippy::useless_conversion)]
+        fn accrued_in_stream(&self, stream: &Stream) -> Balance {
+            let now = self.env().block_timestamp();
+            if now <= stream.start {
+                return 0;
+            }
+            if now >= stream.stop {
+                return stream.deposit;
+            }
+
+            // deposit * elapsed / duration, split so that the products cannot overflow
+            let deposit = u128::from(stream.deposit);
+            let elapsed = u128::from(now - stream.start);
+            let duration = u128::from(stream.stop - stream.start);
+            let accrued = deposit / duration * elapsed + deposit % duration * elapsed / duration;
+            Balance::try_from(accrued).unwrap_or(stream.deposit)
+        }
+
+        /// Removes an executed or cancelled scheduled transfer.
+        fn remove_scheduled(&mut self, scheduled: &ScheduledTransfer) {
+            self.scheduled_transfers.remove(scheduled.id);
+            self.scheduled_total -= scheduled.amount;
+        }
+
+        /// Removes a revealed or cancelled committed transfer.
+        fn remove_committed(&mut self, committed: &CommittedTransfer) {
+            self.committed_transfers.remove(committed.id);
+            self.committed_total -= committed.locked;
+        }
+
+        /// Removes a claimed or cancelled pending transfer.
+        fn remove_pending(&mut self, pending: &PendingTransfer) {
+            self.pending_transfers.remove(pending.id);
+            self.pending_total -= pending.amount;
+            let mut ids = self.pending_ids.get(pending.receiver).unwrap_or_default();
+            ids.retain(|&id| id != pending.id);
+            if ids.is_empty() {
+                self.pending_ids.remove(pending.receiver);
+            } else {
+                self.pending_ids.insert(pending.receiver, &ids);
+            }
+        }
+
+        /// Checks that `sender` can send to `receiver`, i.e. `receiver` is not the contract,
+        /// neither is blacklisted, `receiver` is whitelisted or the reserve while the whitelist
+        /// has entries, and `sender` is not restricted or `receiver` is one of its allowed
+        /// destinations.
+        fn check_destination(&self, sender: AccountId, receiver: AccountId) -> Result<(), Error> {
+            // escrows are internal, so the contract itself never holds a balance
+            if receiver == self.env().account_id() {
+                return Err(Error::TransferToAssetContract);
+            }
+            if self.is_blacklisted(sender) || self.is_blacklisted(receiver) {
+                return Err(Error::Blacklisted);
+            }
+            if self.whitelist_count > 0
+                && receiver != self.reserve_id
+                && !self.is_whitelisted(receiver)
+            {
+                return Err(Error::NotWhitelisted);
+            }
+
+            if self.holder(sender).transfers_restricted
+                && !self
+                    .allowed_destinations
+                    .get(sender)
+                    .unwrap_or_default()
+                    .contains(&receiver)
+            {
+                return Err(Error::DestinationNotAllowed);
+            }
+
+            Ok(())
+        }
+
+        /// Checks `amount` against the spend limit of `sender`, returning its spent amount and
+        /// window start after the transfer, or None if the sender is not limited.
+        /// Note: a window starts with the first transfer after the previous one ended.
+        fn check_spend_limit(
+            &self,
+            sender: AccountId,
+            amount: Balance,
+        ) -> Result<Option<(Balance, Timestamp)>, Error> {
+            if self.spend_limit == 0 || sender == self.reserve_id || sender == self.creator {
+                return Ok(None);
+            }
+
+            let now = self.env().block_timestamp();
+            let holder = self.holder(sender);
+            let (spent, window_start) =
+                if now >= holder.window_start.saturating_add(self.spend_period) {
+                    (0, now)
+                } else {
+                    (holder.spent_in_window, holder.window_start)
+                };
+
+            match spent.checked_add(amount) {
+                Some(spent) if spent <= self.spend_limit => Ok(Some((spent, window_start))),
+                _ => Err(Error::SpendLimitExceeded {
+                    retry_after: window_start.saturating_add(self.spend_period),
+                }),
+            }
+        }
+
+        /// Checks `amount` against the clawback cap, returning the amount revoked in the period and
+        /// its start block after the revoke, or None if there is no cap.
+        /// Note: a period starts with the first revoke after the previous one ended.
+        fn check_clawback_cap(
+            &self,
+            amount: Balance,
+        ) -> Result<Option<(Balance, BlockNumber)>, Error> {
+            if self.clawback_period_cap == 0 {
+                return Ok(None);
+            }
+
+            let now = self.env().block_number();
+            let (used, period_start) =
+                if now.saturating_sub(self.clawback_period_start) > self.clawback_period_blocks {
+                    (0, now)
+                } else {
+                    (self.clawback_used_this_period, self.clawback_period_start)
+                };
+
+            match used.checked_add(amount) {
+                Some(used) if used <= self.clawback_period_cap => Ok(Some((used, period_start))),
+                _ => Err(Error::ClawbackCapExceeded),
+            }
+        }
+
+        /// Returns the account whose key signed the opt-in of `nonce`, if `signature` is valid.
+        fn recover_signer(&self, signature: &[u8], nonce: u64) -> Option<AccountId> {
+            let signature: [u8; 65] = signature.try_into().ok()?;
+            // check if the recovery id is one of 0, 1, 27 or 28
+            if !matches!(signature[64], 0 | 1 | 27 | 28) {
+                return None;
+            }
+
+            let message = (self.env().account_id(), *b"opt_in", nonce);
+            let message_hash = self.env().hash_encoded::<Blake2x256, _>(&message);
+            let public_key = self.env().ecdsa_recover(&signature, &message_hash).ok()?;
+            let account = self.env().hash_bytes::<Blake2x256>(&public_key);
+            AccountId::decode(&mut &account[..]).ok()
+        }
+
+        /// Returns the state of `account`, defaulting to an empty, not opted-in holder.
+        fn holder(&self, account: AccountId) -> HolderState {
+            self.holders.get(account).unwrap_or_default()
+        }
+
+        /// Returns the time-weighted balance of `holder` up to the current block.
+        // Note: the conversion is only useless when `Balance` is `u128`
+        #[allow(clippy::useless_conversion)]
+        fn time_weighted_balance(&self, holder: &HolderState) -> u128 {
+            let elapsed = self.env().block_number() - holder.last_update_block;
+            holder
+                .time_weighted_balance
+                .saturating_add(u128::from(holder.balance).saturating_mul(u128::from(elapsed)))
+        }
+
+        /// Accrues the time-weighted balance of `holder` up to the current block.
+        /// Note: must run before every change of the balance of `holder`.
+        fn accrue_time_weighted_balance(&self, holder: &mut HolderState) {
+            holder.time_weighted_balance = self.time_weighted_balance(holder);
+            holder.last_update_block = self.env().block_number();
+        }
+    }
+
+    /// Runtime mirror of the asset
+    // Note: without the `runtime-mirror` feature, these do nothing
+    #[cfg(feature = "runtime-mirror")]
+    impl Subsa {
+        /// Registers the mirror asset and mints `total` to `reserve`, returning its id.
+        fn register_mirror(
+            asset_name: &str,
+            unit_name: &str,
+            decimals: u32,
+            reserve: AccountId,
+            total: Balance,
+        ) -> Result<Option<u32>, Error> {
+            let asset = <Self as ink::codegen::StaticEnv>::env()
+                .extension()
+                .register_asset(asset_name.into(), unit_name.into(), decimals)
+                .map_err(|_| Error::RuntimeMirrorFailed)?;
+            <Self as ink::codegen::StaticEnv>::env()
+                .extension()
+                .mint(asset, reserve, total)
+                .map_err(|_| Error::RuntimeMirrorFailed)?;
+            Ok(Some(asset))
+        }
+
+        /// Replays a move of `amount` from `from` to `to` on the mirror asset.
+        fn mirror_transfer(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let asset = self.mirror_asset_id.ok_or(Error::RuntimeMirrorFailed)?;
+            self.env()
+                .extension()
+                .transfer(asset, from, to, amount)
+                .map_err(|_| Error::RuntimeMirrorFailed)
+        }
+
+        /// Burns `amount` of the mirror asset from `from`.
+        fn mirror_burn(&self, from: AccountId, amount: Balance) -> Result<(), Error> {
+            let asset = self.mirror_asset_id.ok_or(Error::RuntimeMirrorFailed)?;
+            self.env()
+                .extension()
+                .burn(asset, from, amount)
+                .map_err(|_| Error::RuntimeMirrorFailed)
+        }
+    }
+
+    #[cfg(not(feature = "runtime-mirror"))]
+    impl Subsa {
+        fn register_mirror(
+            _asset_name: &str,
+            _unit_name: &str,
+            _decimals: u32,
+            _reserve: AccountId,
+            _total: Balance,
+        ) -> Result<Option<u32>, Error> {
+            Ok(None)
+        }
+
+        fn mirror_transfer(
+            &self,
+            _from: AccountId,
+            _to: AccountId,
+            _amount: Balance,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn mirror_burn(&self, _from: AccountId, _amount: Balance) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    /// Off-chain helpers for test and fuzzing harnesses
+    #[cfg(feature = "std")]
+    impl Subsa {
+        /// Returns the stored balance of `account`, regardless of its opt-in status.
+        pub fn stored_balance(&self, account: AccountId) -> Balance {
+            self.holder(account).balance
+        }
+    }
+
+    // Unit tests
+
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+
+        /// Imports `ink_lang` so we can use `#[ink::test]`.
+        use ink;
+
+        type Event = <Subsa as ::ink::reflect::ContractEventBase>::Type;
+
+        /// Decodes an event recorded by the off-chain environment.
+        fn decode_event(event: &ink::env::test::EmittedEvent) -> Event {
+            <Event as Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer")
+        }
+
+        /// Returns the block timestamp carried by `event`, if it has one.
+        fn event_timestamp(event: &Event) -> Option<Timestamp> {
+            match event {
+                Event::Transfer(event) => Some(event.timestamp),
+                Event::Freeze(event) => Some(event.timestamp),
+                Event::Modify(event) => Some(event.timestamp),
+                Event::OptIn(event) => Some(event.timestamp),
+                Event::OptOut(event) => Some(event.timestamp),
+                Event::Revoke(event) => Some(event.timestamp),
+                Event::Destruction(event) => Some(event.timestamp),
+                _ => None,
+            }
+        }
+
+        /// Returns the account whose id bytes are all `byte`.
+        fn account(byte: u8) -> AccountId {
+            let mut account = zero_account();
+            AsMut::<[u8]>::as_mut(&mut account).fill(byte);
+            account
+        }
+
+        /// Sets the caller of the next call.
+        // Note: the off-chain engine only takes 32-byte callers, and the contract decodes its
+        // caller from their leading bytes
+        fn set_caller(caller: AccountId) {
+            let caller = AsRef::<[u8]>::as_ref(&caller);
+            let mut bytes = [0x0; 32];
+            bytes[..caller.len()].copy_from_slice(caller);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(bytes.into());
+            // the contract defaults to account(0x1), which cannot hold a role or receive, so
+            // it moves to an account of its own, funded like the default one
+            let contract = [0xff; 32].into();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(contract);
+            if ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(contract)
+                .is_err()
+            {
+                ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                    contract, 1_000_000,
+                );
+            }
+        }
+
+        /// Creates the test asset with the given roles, with `account(0x1)` as the creator.
+        fn new_asset(
             manager: Option<AccountId>,
             reserve: Option<AccountId>,
             freeze: Option<AccountId>,
             clawback: Option<AccountId>,
-        ) -> Self {
-            // Emits creation event
-            Self::env().emit_event(Creation {
-                asset_id: Self::env().account_id(),
-                asset_name: asset_name.clone(),
-                creator: Self::env().caller(),
-                total,
-            });
+        ) -> Subsa {
+            set_caller(account(0x1));
+            Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                manager,
+                reserve,
+                freeze,
+                clawback,
+            )
+            .unwrap()
+        }
+
+        /// Test constructor and initial state
+        #[ink::test]
+        fn constructor_works() {
+            let asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                Some(account(0x0)),
+                Some(account(0x0)),
+                Some(account(0x0)),
+                Some(account(0x0)),
+            )
+            .unwrap();
+
+            assert_eq!(asset.asset_name(), "Test subsa");
+            assert_eq!(asset.unit_name(), "TSSA");
+            assert_eq!(asset.total(), 1000);
+            assert_eq!(asset.decimals(), 10);
+            assert!(asset.default_frozen());
+            assert_eq!(asset.url(), "www.test.com");
+            assert_eq!(asset.manager_id(), account(0x0));
+            assert_eq!(asset.reserve_id(), account(0x0));
+            assert_eq!(asset.freeze_id(), account(0x0));
+            assert_eq!(asset.clawback_id(), account(0x0));
+        }
+
+        // Test if constructor works with default values
+        #[ink::test]
+        fn constructor_works_with_default_values() {
+            let asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(asset.asset_name(), "Test subsa");
+            assert_eq!(asset.unit_name(), "TSSA");
+            assert_eq!(asset.total(), 1000);
+            assert_eq!(asset.decimals(), 10);
+            assert!(asset.default_frozen());
+            assert_eq!(asset.url(), "www.test.com");
+            assert_eq!(asset.manager_id(), account(0x0));
+            assert_eq!(asset.reserve_id(), account(0x1));
+            assert_eq!(asset.freeze_id(), account(0x0));
+            assert_eq!(asset.clawback_id(), account(0x0));
+            assert_eq!(asset.holder(asset.reserve_id()).balance, 1000);
+        }
+
+        // Test if asset_id field is set correctly in constructor to the contract address
+        #[ink::test]
+        fn constructor_sets_asset_id() {
+            use ink::codegen::Env;
+            let asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(asset.asset_id(), asset.env().account_id());
+        }
+
+        // check if Create event is emitted in constructor
+        #[ink::test]
+        fn constructor_emits_create_event() {
+            let _asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 1);
+            let event = &events[0];
+            assert_eq!(event.topics.len(), 5);
+            // TODO TEST EVENT WITH Event as scale:Decode
+        }
+
+        // Test if optIn works
+        #[ink::test]
+        fn opt_in_works() {
+            // set caller
+            set_caller(account(0x0));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(account(0x1));
+            assert_eq!(asset.opt_in(), Ok(()));
+            // check if caller account is opted in in accounts_opted_in map
+            assert!(asset.holder(account(0x1)).opted_in);
+        }
+
+        // Test if optIn emits OptIn event
+        #[ink::test]
+        fn opt_in_emits_opt_in_event() {
+            // set caller
+            set_caller(account(0x0));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(account(0x1));
+            assert_eq!(asset.opt_in(), Ok(()));
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            let event = &events[1];
+            assert_eq!(event.topics.len(), 3);
+            let Event::OptIn(event) = decode_event(event) else {
+                panic!("expected an OptIn event");
+            };
+            assert_eq!(event.account, account(0x1));
+            assert!(!event.frozen);
+            assert_eq!(event.deposit, 0);
+            assert!(!event.forced);
+        }
+
+        // Test if OptIn tells whether the holding starts frozen, and whether the opt-in is forced
+        #[ink::test]
+        fn opt_in_event_reports_frozen_and_forced() {
+            let reserve = account(0x1);
+            let freeze = account(0x3);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, Some(freeze), None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(freeze);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            set_caller(holder);
+            assert_eq!(asset.opt_out(), Ok(()));
+            assert_eq!(asset.opt_in(), Ok(()));
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::OptIn(OptIn {
+                    frozen: true,
+                    forced: false,
+                    ..
+                })
+            ));
+
+            set_caller(reserve);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.opt_in_and_fund(account(0x4), 10), Ok(()));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events[0],
+                Event::OptIn(OptIn {
+                    frozen: false,
+                    deposit: 0,
+                    forced: true,
+                    ..
+                })
+            ));
+        }
+
+        #[ink::test]
+        fn opt_in_throws_already_opt_in_if_already_opt_in() {
+            // set caller
+            set_caller(account(0x0));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(account(0x1));
+            assert_eq!(asset.opt_in(), Ok(()));
+            // check if caller account is opted in in accounts_opted_in map
+            assert!(asset.holder(account(0x1)).opted_in);
+            // check if optIn throws AlreadyOptedIn error
+            assert_eq!(asset.opt_in(), Err(Error::AlreadyOptedIn));
+        }
+
+        // Test if optOut works
+        #[ink::test]
+        fn opt_out_works() {
+            // set caller
+            set_caller(account(0x0));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(account(0x1));
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.opt_out(), Ok(()));
+            // check if caller account is opted in in accounts_opted_in map
+            assert!(!asset.holder(account(0x1)).opted_in);
+        }
+
+        // Test if optOut emits OptOut event
+        #[ink::test]
+        fn opt_out_emits_opt_out_event() {
+            // set caller
+            set_caller(account(0x0));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(account(0x1));
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.opt_out(), Ok(()));
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 3);
+            let event = &events[2];
+            assert_eq!(event.topics.len(), 3);
+            let Event::OptOut(event) = decode_event(event) else {
+                panic!("expected an OptOut event");
+            };
+            assert_eq!(event.account, account(0x1));
+            assert_eq!(event.remaining_balance, 0);
+            assert_eq!(event.closed_to, None);
+            assert_eq!(event.deposit_refunded, 0);
+
+            // the balance left in the account is reported
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(account(0x0));
+            assert_eq!(asset.transfer(account(0x1), 25), Ok(()));
+            set_caller(account(0x1));
+            assert_eq!(asset.opt_out(), Ok(()));
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::OptOut(OptOut {
+                    remaining_balance: 25,
+                    closed_to: None,
+                    deposit_refunded: 0,
+                    ..
+                })
+            ));
+        }
+
+        #[ink::test]
+        fn opt_out_throws_not_opt_in_when_opt_out_without_opt_in() {
+            // set caller
+            set_caller(account(0x0));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(account(0x1));
+            assert_eq!(asset.opt_out(), Err(Error::NotOptedIn));
+        }
+
+        // Test if is_opted_in_batch reports the opt-in status of every account in order
+        #[ink::test]
+        fn is_opted_in_batch_works() {
+            set_caller(account(0x1));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let accounts: Vec<AccountId> = (0x2..0x7).map(account).collect();
+            for account in [accounts[0], accounts[2], accounts[4]] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            assert_eq!(
+                asset.is_opted_in_batch(accounts),
+                vec![true, false, true, false, true]
+            );
+        }
+
+        // Test if is_frozen_batch reports the frozen status of every account in order
+        #[ink::test]
+        fn is_frozen_batch_works() {
+            let freeze_id = account(0x9);
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                Some(freeze_id),
+                None,
+            )
+            .unwrap();
+            let accounts: Vec<AccountId> = (0x2..0x7).map(account).collect();
+            set_caller(freeze_id);
+            assert_eq!(asset.freeze(accounts[1], true, None), Ok(()));
+            assert_eq!(asset.freeze(accounts[3], true, None), Ok(()));
+            assert_eq!(
+                asset.is_frozen_batch(accounts),
+                vec![false, true, false, true, false]
+            );
+        }
+
+        // Test if batch queries return empty vecs for empty input
+        #[ink::test]
+        fn batch_queries_handle_empty_input() {
+            let asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert!(asset.is_opted_in_batch(Vec::new()).is_empty());
+            assert!(asset.is_frozen_batch(Vec::new()).is_empty());
+        }
+
+        // Test if revoke_asset debits the revocation target and credits the receiver
+        #[ink::test]
+        fn revoke_asset_moves_balance_from_target_to_receiver() {
+            let clawback_id = account(0x9);
+            let holder = account(0x2);
+            set_caller(account(0x1));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                10,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                Some(clawback_id),
+            )
+            .unwrap();
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(account(0x1));
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+
+            set_caller(clawback_id);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.revoke_asset(account(0x1), holder, 100), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(200));
+            assert_eq!(asset.balance_of(account(0x1)), Ok(800));
+            let event = ink::env::test::recorded_events()
+                .nth(events_before)
+                .map(|event| decode_event(&event))
+                .unwrap();
+            let Event::Revoke(revoke) = event else {
+                panic!("expected a Revoke event");
+            };
+            assert_eq!(revoke.from, holder);
+            assert_eq!(revoke.to, account(0x1));
+            assert_eq!(revoke.clawback_id, clawback_id);
+            assert_eq!(revoke.amount, 100);
+            assert_eq!(revoke.from_balance, 200);
+            assert_eq!(revoke.to_balance, 800);
+            assert_eq!(
+                asset.revoke_asset(account(0x1), holder, 201),
+                Err(Error::NotEnoughBalance)
+            );
+        }
+
+        // Test if admin_transfer lets the manager move tokens between two opted-in accounts
+        #[ink::test]
+        fn admin_transfer_works() {
+            let manager = account(0x9);
+            let holder = account(0x2);
+            let receiver = account(0x3);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for account in [holder, receiver] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            set_caller(account(0x1));
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+
+            set_caller(manager);
+            assert_eq!(asset.admin_transfer(holder, receiver, 120), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(180));
+            assert_eq!(asset.balance_of(receiver), Ok(120));
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let event = events.last().unwrap();
+            // signature + from + to + manager_id
+            assert_eq!(event.topics.len(), 4);
+        }
+
+        // Test if admin_transfer is rejected for anyone but the manager
+        #[ink::test]
+        fn admin_transfer_throws_not_manager_id() {
+            let manager = account(0x9);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            // not even the holder of the tokens may use it
+            set_caller(account(0x1));
+            assert_eq!(
+                asset.admin_transfer(account(0x1), holder, 10),
+                Err(Error::NotManagerId)
+            );
+            assert_eq!(asset.balance_of(holder), Ok(0));
+        }
+
+        // Test if admin_transfer respects balance and opt-in checks
+        #[ink::test]
+        fn admin_transfer_respects_balance_and_opt_in() {
+            let manager = account(0x9);
+            let creator = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+
+            set_caller(manager);
+            assert_eq!(
+                asset.admin_transfer(creator, holder, 10),
+                Err(Error::NotOptedIn)
+            );
+
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(
+                asset.admin_transfer(creator, holder, 1001),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(asset.balance_of(creator), Ok(1000));
+        }
+
+        /// Asserts that `a` and `b` have the same asset params.
+        fn assert_same_params(a: &Subsa, b: &Subsa) {
+            assert_eq!(a.asset_name(), b.asset_name());
+            assert_eq!(a.unit_name(), b.unit_name());
+            assert_eq!(a.total(), b.total());
+            assert_eq!(a.decimals(), b.decimals());
+            assert_eq!(a.default_frozen(), b.default_frozen());
+            assert_eq!(a.url(), b.url());
+            assert_eq!(a.metadata_hash(), b.metadata_hash());
+            assert_eq!(a.creator_id(), b.creator_id());
+            assert_eq!(a.manager_id(), b.manager_id());
+            assert_eq!(a.reserve_id(), b.reserve_id());
+            assert_eq!(a.freeze_id(), b.freeze_id());
+            assert_eq!(a.clawback_id(), b.clawback_id());
+            assert_eq!(a.balance_of(a.reserve_id()), b.balance_of(b.reserve_id()));
+        }
+
+        // Test if new_with_config creates the same asset as the positional constructor
+        #[ink::test]
+        fn new_with_config_matches_positional_constructor() {
+            let roles = [0x2, 0x3, 0x4, 0x5].map(|i| Some(account(i)));
+            let positional = new_asset(roles[0], roles[1], roles[2], roles[3]);
+            let config = Subsa::new_with_config(AssetConfig {
+                asset_name: "Test subsa".into(),
+                unit_name: "TSSA".into(),
+                total: 1000,
+                decimals: 10,
+                default_frozen: true,
+                url: "www.test.com".into(),
+                metadata_hash: [0x0; 4],
+                manager: roles[0],
+                reserve: roles[1],
+                freeze: roles[2],
+                clawback: roles[3],
+                kyc_required: false,
+                start_suspended: false,
+                min_opt_in_balance: 0,
+                wipe_requires_freeze: false,
+                strict_url: false,
+                mutable_url: false,
+                verifier: None,
+                asset_symbol: [0; 12],
+                clawback_sunset: None,
+                source_commitment: None,
+                enforce_role_separation: false,
+                initial_distribution: Vec::new(),
+                burn_on_transfer_bps: 0,
+            })
+            .unwrap();
+            assert_same_params(&config, &positional);
+            assert_eq!(config.reserve_id(), account(0x3));
+        }
+
+        // Test if new_with_config works with default values
+        #[ink::test]
+        fn new_with_config_works_with_default_values() {
+            set_caller(account(0x1));
+            let asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(asset.asset_name(), "");
+            assert_eq!(asset.total(), 1000);
+            assert_eq!(asset.decimals(), 0);
+            assert!(!asset.default_frozen());
+            assert_eq!(asset.manager_id(), account(0x0));
+            assert_eq!(asset.reserve_id(), account(0x1));
+            assert_eq!(asset.balance_of(account(0x1)), Ok(1000));
+
+            let positional = Subsa::new(
+                String::new(),
+                String::new(),
+                1000,
+                0,
+                false,
+                String::new(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_same_params(&asset, &positional);
+        }
+
+        // Test if set_kyc_status updates the KYC status and emits KycStatusUpdated
+        #[ink::test]
+        fn set_kyc_status_works() {
+            let manager = account(0x9);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            assert_eq!(asset.kyc_status_of(holder), KYC_UNKNOWN);
+
+            set_caller(manager);
+            assert_eq!(asset.set_kyc_status(holder, KYC_PENDING), Ok(()));
+            assert_eq!(asset.kyc_status_of(holder), KYC_PENDING);
+            assert_eq!(asset.set_kyc_status(holder, KYC_APPROVED), Ok(()));
+            assert_eq!(asset.kyc_status_of(holder), KYC_APPROVED);
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 3);
+            assert_eq!(events[2].topics.len(), 2);
+        }
+
+        // Test if set_kyc_status rejects unknown statuses and non-manager callers
+        #[ink::test]
+        fn set_kyc_status_throws_on_invalid_input() {
+            let manager = account(0x9);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+
+            set_caller(holder);
+            assert_eq!(
+                asset.set_kyc_status(holder, KYC_APPROVED),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            assert_eq!(
+                asset.set_kyc_status(holder, 4),
+                Err(Error::InvalidKycStatus)
+            );
+            assert_eq!(asset.kyc_status_of(holder), KYC_UNKNOWN);
+        }
+
+        // Test if opt_in requires an approved KYC status when kyc_required is set
+        #[ink::test]
+        fn opt_in_throws_kyc_not_approved_when_kyc_required() {
+            let manager = account(0x9);
+            let holder = account(0x2);
+            set_caller(account(0x1));
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                manager: Some(manager),
+                kyc_required: true,
+                ..Default::default()
+            })
+            .unwrap();
+            assert!(asset.kyc_required());
+
+            for status in [KYC_UNKNOWN, KYC_PENDING, KYC_REJECTED] {
+                set_caller(manager);
+                assert_eq!(asset.set_kyc_status(holder, status), Ok(()));
+                set_caller(holder);
+                assert_eq!(asset.opt_in(), Err(Error::KycNotApproved));
+            }
+
+            set_caller(manager);
+            assert_eq!(asset.set_kyc_status(holder, KYC_APPROVED), Ok(()));
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+        }
+
+        // Test if opt_in ignores KYC statuses when kyc_required is not set
+        #[ink::test]
+        fn opt_in_ignores_kyc_when_not_required() {
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            assert!(!asset.kyc_required());
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+        }
+
+        // Test if new_nft creates an NFT held by the creator
+        #[ink::test]
+        fn new_nft_works() {
+            let creator = account(0x1);
+            set_caller(creator);
+            let asset = Subsa::new_nft(
+                "Test NFT".into(),
+                "TNFT".into(),
+                "ipfs://test".into(),
+                [0x1; 4],
+                Some(account(0x9)),
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(asset.total(), 1);
+            assert_eq!(asset.decimals(), 0);
+            assert!(asset.is_nft());
+            assert!(!asset.is_fractional_nft());
+            assert_eq!(asset.owner_of(), Some(creator));
+            assert_eq!(asset.balance_of(creator), Ok(1));
+        }
+
+        // Test if owner_of follows the NFT through a transfer
+        #[ink::test]
+        fn owner_of_follows_transfer() {
+            let creator = account(0x1);
+            let receiver = account(0x2);
+            set_caller(creator);
+            let mut asset = Subsa::new_nft(
+                "Test NFT".into(),
+                "TNFT".into(),
+                "ipfs://test".into(),
+                [0x1; 4],
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(receiver);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            // a zero-amount transfer does not move the NFT
+            set_caller(creator);
+            assert_eq!(
+                asset.transfer(receiver, 0),
+                Err(Error::NFTPartialTransferNotAllowed)
+            );
+            assert_eq!(asset.owner_of(), Some(creator));
+
+            assert_eq!(asset.transfer(receiver, 1), Ok(()));
+            assert_eq!(asset.owner_of(), Some(receiver));
+            assert_eq!(asset.balance_of(receiver), Ok(1));
+            assert_eq!(asset.balance_of(creator), Ok(0));
+        }
+
+        // Test if new_fractional_nft enforces total = 10^decimals
+        #[ink::test]
+        fn new_fractional_nft_enforces_total() {
+            set_caller(account(0x1));
+            let asset = Subsa::new_fractional_nft(
+                "Test fractional NFT".into(),
+                "TFNFT".into(),
+                "ipfs://test".into(),
+                [0x1; 4],
+                None,
+                None,
+                None,
+                3,
+            )
+            .unwrap();
+            assert_eq!(asset.total(), 1000);
+            assert_eq!(asset.decimals(), 3);
+            assert!(asset.is_fractional_nft());
+            assert!(!asset.is_nft());
+            assert_eq!(asset.owner_of(), None);
+
+            // 10^39 does not fit in a u128, nor in a u64
+            assert!(matches!(
+                Subsa::new_fractional_nft(
+                    "Test fractional NFT".into(),
+                    "TFNFT".into(),
+                    "ipfs://test".into(),
+                    [0x1; 4],
+                    None,
+                    None,
+                    None,
+                    39,
+                ),
+                Err(Error::InvalidDecimals)
+            ));
+        }
+
+        // Test if is_nft and is_fractional_nft are false for regular assets
+        #[ink::test]
+        fn regular_asset_is_not_nft() {
+            // total 1000 with 10 decimals is neither
+            let asset = new_asset(None, None, None, None);
+            assert!(!asset.is_nft());
+            assert!(!asset.is_fractional_nft());
+            assert_eq!(asset.owner_of(), None);
+        }
+
+        // Test if patching an asset changes only the given roles
+        #[ink::test]
+        fn patch_asset_works() {
+            let manager = account(0x9);
+            let freeze = account(0x8);
+            let clawback = account(0x7);
+            let other = account(0x2);
+            let mut asset = new_asset(Some(manager), None, Some(freeze), Some(clawback));
+            let patch = ModifyPatch {
+                freeze: Some(Some(other)),
+                ..Default::default()
+            };
+            set_caller(other);
+            assert_eq!(asset.patch_asset(patch), Err(Error::NotManagerId));
+
+            set_caller(manager);
+            assert_eq!(asset.patch_asset(patch), Ok(()));
+            assert_eq!(asset.freeze_id(), other);
+            assert_eq!(asset.manager_id(), manager);
+            assert_eq!(asset.reserve_id(), account(0x1));
+            assert_eq!(asset.clawback_id(), clawback);
+            assert_eq!(asset.asset_version(), 1);
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let Event::AssetPatched(event) = decode_event(emitted_events.last().unwrap()) else {
+                panic!("expected an AssetPatched event");
+            };
+            assert_eq!((event.changed_fields, event.asset_version), (0b0100, 1));
+
+            // clearing a role, and keeping another as is, only flags the cleared one
+            let patch = ModifyPatch {
+                freeze: Some(Some(other)),
+                clawback: Some(None),
+                ..Default::default()
+            };
+            assert_eq!(asset.patch_asset(patch), Ok(()));
+            assert_eq!(asset.clawback_id(), zero_account());
+            assert_eq!(asset.freeze_id(), other);
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let Event::AssetPatched(event) = decode_event(emitted_events.last().unwrap()) else {
+                panic!("expected an AssetPatched event");
+            };
+            assert_eq!(event.changed_fields, 0b1000);
+
+            // the cleared role cannot be set again, and nothing is applied
+            let patch = ModifyPatch {
+                manager: Some(Some(other)),
+                clawback: Some(Some(clawback)),
+                ..Default::default()
+            };
+            assert_eq!(asset.patch_asset(patch), Err(Error::RoleDisabled));
+            assert_eq!(asset.manager_id(), manager);
+            assert_eq!(asset.asset_version(), 2);
+        }
+
+        // Test if asset_version is incremented on every modification
+        #[ink::test]
+        fn asset_version_increments_on_modify_asset() {
+            let manager = account(0x9);
+            let freeze = account(0x8);
+            let mut asset = new_asset(Some(manager), None, Some(manager), None);
+            assert_eq!(asset.asset_version(), 0);
+
+            set_caller(manager);
+            assert_eq!(
+                asset.modify_asset(Some(manager), None, Some(freeze), None),
+                Ok(())
+            );
+            assert_eq!(asset.asset_version(), 1);
+
+            // a different field changing still bumps the version
+            assert_eq!(
+                asset.modify_asset(Some(freeze), None, Some(freeze), None),
+                Ok(())
+            );
+            assert_eq!(asset.asset_version(), 2);
+
+            // rejected modifications do not
+            set_caller(manager);
+            assert_eq!(
+                asset.modify_asset(None, None, None, None),
+                Err(Error::NotManagerId)
+            );
+            assert_eq!(asset.asset_version(), 2);
+        }
+
+        // Test if the initial distribution opts in and funds each account at creation
+        #[ink::test]
+        fn initial_distribution_works() {
+            let creator = account(0x1);
+            let pool = account(0x2);
+            let team = account(0x3);
+            let config = |initial_distribution: Vec<(AccountId, Balance)>| AssetConfig {
+                asset_name: "Test subsa".into(),
+                total: 1000,
+                initial_distribution,
+                ..Default::default()
+            };
+            set_caller(creator);
+            let asset =
+                Subsa::new_with_config(config(vec![(pool, 600), (team, 150), (team, 50)])).unwrap();
+
+            // the supply is conserved, the reserve keeping the remainder
+            assert_eq!(asset.balance_of(pool), Ok(600));
+            assert_eq!(asset.balance_of(team), Ok(200));
+            assert_eq!(asset.balance_of(creator), Ok(200));
+            assert_eq!(asset.opted_in_count(), 3);
+
+            let events = ink::env::test::recorded_events()
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            let opt_ins = events
+                .iter()
+                .filter(|event| matches!(event, Event::OptIn(OptIn { forced: true, .. })))
+                .count();
+            let transfers = events
+                .iter()
+                .filter(|event| matches!(event, Event::Transfer(Transfer { sender, .. }) if *sender == creator))
+                .count();
+            assert_eq!((opt_ins, transfers), (2, 3));
+
+            // the distribution cannot exceed the total supply, nor the size cap
+            assert!(matches!(
+                Subsa::new_with_config(config(vec![(pool, 600), (team, 401)])),
+                Err(Error::InitialDistributionExceedsTotal)
+            ));
+            assert!(matches!(
+                Subsa::new_with_config(config(vec![(pool, Balance::MAX), (team, 1)])),
+                Err(Error::InitialDistributionExceedsTotal)
+            ));
+            assert!(matches!(
+                Subsa::new_with_config(config(vec![(pool, 1); MAX_STATUS_BATCH + 1])),
+                Err(Error::TooManyAccounts)
+            ));
+            assert!(matches!(
+                Subsa::new_with_config(config(vec![(zero_account(), 1)])),
+                Err(Error::TransferToZeroAddress)
+            ));
+        }
+
+        // Test if transfers burn their configured share, conserving the current supply
+        #[ink::test]
+        fn burn_on_transfer_works() {
+            let creator = account(0x1);
+            let alice = account(0x2);
+            let bob = account(0x3);
+            let config = |burn_on_transfer_bps: u16| AssetConfig {
+                asset_name: "Test subsa".into(),
+                total: 1_000_000,
+                initial_distribution: vec![(alice, 400_000), (bob, 100_000)],
+                burn_on_transfer_bps,
+                ..Default::default()
+            };
+            set_caller(creator);
+            assert!(matches!(
+                Subsa::new_with_config(config(MAX_BURN_ON_TRANSFER_BPS + 1)),
+                Err(Error::InvalidBurnRate)
+            ));
+            let mut asset = Subsa::new_with_config(config(250)).unwrap();
+            assert_eq!(asset.burn_on_transfer_bps(), 250);
+
+            // the distributions from the reserve burn nothing
+            assert_eq!(asset.transfer(bob, 1000), Ok(()));
+            assert_eq!(asset.balance_of(bob), Ok(101_000));
+            assert_eq!(asset.burned(), 0);
+
+            // the burnt share is rounded down, and taken out of the amount received
+            let mut burned = 0;
+            for amount in (1..=200).map(|step| step * 37) {
+                let (sender, receiver) = if amount % 2 == 0 {
+                    (alice, bob)
+                } else {
+                    (bob, alice)
+                };
+                let receiver_before = asset.balance_of(receiver).unwrap();
+                let sender_before = asset.balance_of(sender).unwrap();
+                set_caller(sender);
+                assert_eq!(asset.transfer(receiver, amount), Ok(()));
+
+                let burnt = amount * 250 / 10_000;
+                burned += burnt;
+                assert_eq!(asset.balance_of(sender), Ok(sender_before - amount));
+                assert_eq!(
+                    asset.balance_of(receiver),
+                    Ok(receiver_before + amount - burnt)
+                );
+                assert_eq!(asset.burned(), burned);
+                assert_eq!(asset.current_supply(), 1_000_000 - burned);
+                let held = [creator, alice, bob]
+                    .iter()
+                    .map(|&account| asset.balance_of(account).unwrap())
+                    .sum::<Balance>();
+                assert_eq!(held, asset.current_supply());
+                assert_eq!(
+                    asset.circulating_supply(),
+                    held - asset.balance_of(creator).unwrap()
+                );
+            }
+            assert!(burned > 0);
+
+            // the burn follows the transfer of the net amount
+            set_caller(alice);
+            assert_eq!(asset.transfer(bob, 10_000), Ok(()));
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let Event::Transfer(transfer) = decode_event(&events[events.len() - 2]) else {
+                panic!("expected a Transfer event")
+            };
+            assert_eq!(transfer.amount, Some(9750));
+            let Event::Burn(event) = decode_event(&events[events.len() - 1]) else {
+                panic!("expected a Burn event")
+            };
+            assert_eq!(event.from, alice);
+            assert_eq!(event.amount, 250);
+            assert_eq!(event.current_supply, 1_000_000 - burned - 250);
+
+            // the sender must cover the whole amount, burnt share included
+            set_caller(bob);
+            let balance = asset.balance_of(bob).unwrap();
+            assert_eq!(
+                asset.transfer(alice, balance + 1),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(asset.transfer(alice, balance), Ok(()));
+            assert_eq!(asset.balance_of(bob), Ok(0));
+        }
+
+        // Test if the creator is opted in when a separate reserve is specified
+        #[ink::test]
+        fn constructor_opts_in_creator_with_separate_reserve() {
+            let creator = account(0x1);
+            let reserve = account(0x2);
+            let mut asset = new_asset(None, Some(reserve), None, None);
+            assert_eq!(asset.is_opted_in(creator), Ok(true));
+            assert_eq!(asset.balance_of(creator), Ok(0));
+            assert_eq!(asset.balance_of(reserve), Ok(1000));
+            assert_eq!(asset.opted_in_count(), 2);
+            assert!(!asset.is_destroyable());
+
+            // the creator can immediately receive tokens
+            set_caller(reserve);
+            assert_eq!(asset.transfer(creator, 400), Ok(()));
+            assert_eq!(asset.balance_of(creator), Ok(400));
+
+            // and the asset becomes destroyable once the whole supply is back with the creator
+            assert_eq!(asset.transfer(creator, 600), Ok(()));
+            assert!(asset.is_destroyable());
+        }
+
+        // Test if opted_in_count tracks opt-ins and opt-outs
+        #[ink::test]
+        fn opted_in_count_works() {
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            // the creator is also the reserve
+            assert_eq!(asset.opted_in_count(), 1);
+
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.opted_in_count(), 2);
+            assert_eq!(asset.opt_in(), Err(Error::AlreadyOptedIn));
+            assert_eq!(asset.opted_in_count(), 2);
+            assert_eq!(asset.opt_out(), Ok(()));
+            assert_eq!(asset.opted_in_count(), 1);
+        }
+
+        // Test if freeze can unfreeze a frozen account, and rejects no-op changes
+        #[ink::test]
+        fn freeze_and_unfreeze_works() {
+            let freeze_id = account(0x9);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, Some(freeze_id), None);
+            set_caller(freeze_id);
+            assert_eq!(asset.freeze(holder, false, None), Err(Error::NotFrozen));
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            assert_eq!(asset.is_frozen(holder), Ok(true));
+            assert_eq!(asset.freeze(holder, true, None), Err(Error::AlreadyFrozen));
+            assert_eq!(asset.freeze(holder, false, None), Ok(()));
+            assert_eq!(asset.is_frozen(holder), Ok(false));
+        }
+
+        // Test if freeze_history_of records freezes and unfreezes in order
+        #[ink::test]
+        fn freeze_history_records_changes() {
+            let freeze_id = account(0x9);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, Some(freeze_id), None);
+            assert!(asset.freeze_history_of(holder).is_empty());
+
+            set_caller(freeze_id);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            ink::env::test::advance_block::<Environment>();
+            assert_eq!(asset.freeze(holder, false, None), Ok(()));
+            // rejected changes are not recorded
+            assert_eq!(asset.freeze(holder, false, None), Err(Error::NotFrozen));
+
+            assert_eq!(
+                asset.freeze_history_of(holder),
+                vec![
+                    FreezeEvent {
+                        block: 0,
+                        frozen: true,
+                        freeze_id,
+                    },
+                    FreezeEvent {
+                        block: 1,
+                        frozen: false,
+                        freeze_id,
+                    },
+                ]
+            );
+        }
+
+        // Test if freeze_history_of evicts the oldest entry once full
+        #[ink::test]
+        fn freeze_history_evicts_oldest_entry() {
+            let freeze_id = account(0x9);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, Some(freeze_id), None);
+            set_caller(freeze_id);
+            // 22 changes, one per block: blocks 0 and 1 fall out of the history
+            for block in 0..22 {
+                assert_eq!(asset.freeze(holder, block % 2 == 0, None), Ok(()));
+                ink::env::test::advance_block::<Environment>();
+            }
+
+            let history = asset.freeze_history_of(holder);
+            assert_eq!(history.len(), FREEZE_HISTORY_CAPACITY);
+            assert_eq!(history[0].block, 2);
+            assert!(history[0].frozen);
+            assert_eq!(history[FREEZE_HISTORY_CAPACITY - 1].block, 21);
+            assert!(!history[FREEZE_HISTORY_CAPACITY - 1].frozen);
+        }
+
+        // Test if modify_asset rejects a new reserve that has not opted in
+        #[ink::test]
+        fn modify_asset_throws_not_opted_in_for_new_reserve() {
+            let manager = account(0x9);
+            let treasury = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(manager);
+            assert_eq!(
+                asset.modify_asset(Some(manager), Some(treasury), None, None),
+                Err(Error::NotOptedIn)
+            );
+            assert_eq!(asset.reserve_id(), account(0x1));
+            assert_eq!(asset.asset_version(), 0);
+        }
+
+        // Test if rotating the reserve to an opted-in account leaves the old reserve's balance
+        #[ink::test]
+        fn modify_asset_rotates_reserve_without_moving_balance() {
+            let manager = account(0x9);
+            let old_reserve = account(0x1);
+            let treasury = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            assert_eq!(asset.unminted_supply(), 1000);
+
+            set_caller(treasury);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(old_reserve);
+            assert_eq!(asset.transfer(treasury, 100), Ok(()));
+
+            set_caller(manager);
+            assert_eq!(
+                asset.modify_asset(Some(manager), Some(treasury), None, None),
+                Ok(())
+            );
+            assert_eq!(asset.reserve_id(), treasury);
+            assert_eq!(asset.balance_of(old_reserve), Ok(900));
+            assert_eq!(asset.unminted_supply(), 100);
+
+            // clearing the reserve needs no opt-in
+            assert_eq!(asset.modify_asset(Some(manager), None, None, None), Ok(()));
+            assert_eq!(asset.unminted_supply(), 0);
+        }
+
+        // Test if each role setter changes only its role and emits RoleChanged
+        #[ink::test]
+        fn role_setters_work() {
+            let manager = account(0x9);
+            let holder = account(0x2);
+            let roles = [0x6, 0x7, 0x8].map(|i| Some(account(i)));
+            let mut asset = new_asset(Some(manager), None, roles[1], roles[2]);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            let events_before = ink::env::test::recorded_events().count();
+
+            set_caller(manager);
+            assert_eq!(asset.set_freeze(holder), Ok(()));
+            assert_eq!(asset.freeze_id(), holder);
+            assert_eq!(asset.clawback_id(), roles[2].unwrap());
+            assert_eq!(asset.set_clawback(holder), Ok(()));
+            assert_eq!(asset.clawback_id(), holder);
+            assert_eq!(asset.set_reserve(holder), Ok(()));
+            assert_eq!(asset.reserve_id(), holder);
+            assert_eq!(asset.set_manager(holder), Ok(()));
+            assert_eq!(asset.manager_id(), holder);
+            assert_eq!(asset.asset_version(), 4);
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), events_before + 4);
+            // signature + role + by
+            assert_eq!(events[events_before].topics.len(), 3);
+
+            // the old manager has lost its powers
+            assert_eq!(asset.set_freeze(manager), Err(Error::NotManagerId));
+        }
+
+        // Test if role setters are rejected for anyone but the manager
+        #[ink::test]
+        fn role_setters_throw_not_manager_id() {
+            let manager = account(0x9);
+            let other = account(0x2);
+            let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+            set_caller(other);
+            assert_eq!(asset.set_manager(other), Err(Error::NotManagerId));
+            assert_eq!(asset.set_reserve(other), Err(Error::NotManagerId));
+            assert_eq!(asset.set_freeze(other), Err(Error::NotManagerId));
+            assert_eq!(asset.set_clawback(other), Err(Error::NotManagerId));
+            assert_eq!(asset.manager_id(), manager);
+            assert_eq!(asset.asset_version(), 0);
+        }
+
+        // Test if the contract itself is rejected as a role holder and as a receiver
+        #[ink::test]
+        fn self_as_role_rejected() {
+            let manager = account(0x9);
+            let holder = account(0x2);
+
+            // in the constructor, for each role
+            set_caller(manager);
+            let contract = ink::env::test::callee::<Environment>();
+            for roles in [
+                [Some(contract), None, None, None],
+                [None, Some(contract), None, None],
+                [None, None, Some(contract), None],
+                [None, None, None, Some(contract)],
+            ] {
+                let config = AssetConfig {
+                    asset_name: "Test subsa".into(),
+                    total: 1000,
+                    manager: roles[0],
+                    reserve: roles[1],
+                    freeze: roles[2],
+                    clawback: roles[3],
+                    ..Default::default()
+                };
+                assert!(matches!(
+                    Subsa::new_with_config(config),
+                    Err(Error::SelfAsRole)
+                ));
+            }
+
+            // in every role change path
+            let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.set_manager(contract), Err(Error::SelfAsRole));
+            assert_eq!(asset.set_reserve(contract), Err(Error::SelfAsRole));
+            assert_eq!(asset.set_freeze(contract), Err(Error::SelfAsRole));
+            assert_eq!(asset.set_clawback(contract), Err(Error::SelfAsRole));
+            assert_eq!(
+                asset.modify_asset(Some(manager), Some(contract), Some(manager), Some(manager)),
+                Err(Error::SelfAsRole)
+            );
+            assert_eq!(asset.propose_manager(contract), Err(Error::SelfAsRole));
+            assert_eq!(asset.manager_id(), manager);
+            assert_eq!(asset.asset_version(), 0);
+
+            // as a transfer receiver
+            set_caller(account(0x1));
+            assert_eq!(
+                asset.transfer(contract, 10),
+                Err(Error::TransferToAssetContract)
+            );
+            assert_eq!(asset.transfer(holder, 10), Ok(()));
+        }
+
+        // Test if the role separation policy rejects shared roles at creation and on change
+        #[ink::test]
+        fn role_separation_works() {
+            let manager = account(0x9);
+            let freeze = account(0x8);
+            let clawback = account(0x7);
+            let config = |roles: [Option<AccountId>; 3]| AssetConfig {
+                asset_name: "Test subsa".into(),
+                total: 1000,
+                manager: roles[0],
+                freeze: roles[1],
+                clawback: roles[2],
+                enforce_role_separation: true,
+                ..Default::default()
+            };
+
+            // at creation, any two roles shared by a non-zero account are rejected
+            set_caller(manager);
+            for roles in [
+                [Some(manager), Some(manager), None],
+                [Some(manager), None, Some(manager)],
+                [None, Some(freeze), Some(freeze)],
+            ] {
+                assert!(matches!(
+                    Subsa::new_with_config(config(roles)),
+                    Err(Error::RoleSeparationViolated)
+                ));
+            }
+            let permissive = new_asset(Some(manager), None, Some(manager), Some(manager));
+            assert!(!permissive.role_policy());
+
+            set_caller(manager);
+            let mut asset =
+                Subsa::new_with_config(config([Some(manager), Some(freeze), None])).unwrap();
+            assert!(asset.role_policy());
+
+            // on change, through each role change path
+            assert_eq!(
+                asset.set_freeze(manager),
+                Err(Error::RoleSeparationViolated)
+            );
+            assert_eq!(
+                asset.modify_asset(Some(freeze), None, Some(freeze), None),
+                Err(Error::RoleSeparationViolated)
+            );
+            assert_eq!(
+                asset.propose_manager(freeze),
+                Err(Error::RoleSeparationViolated)
+            );
+            assert_eq!(asset.freeze_id(), freeze);
+
+            // swapping roles at once, or clearing one, keeps them separate
+            assert_eq!(
+                asset.modify_asset(Some(freeze), None, Some(manager), None),
+                Ok(())
+            );
+            set_caller(freeze);
+            assert_eq!(asset.set_freeze(zero_account()), Ok(()));
+            assert_eq!(asset.propose_manager(clawback), Ok(()));
+            set_caller(clawback);
+            assert_eq!(asset.accept_manager(), Ok(()));
+            assert_eq!(asset.manager_id(), clawback);
+        }
+
+        // Test if a cleared role can never be set again
+        #[ink::test]
+        fn role_setters_throw_role_disabled_for_cleared_roles() {
+            let manager = account(0x9);
+            let freeze = account(0x8);
+            let zero = account(0x0);
+            // clawback is cleared from the start
+            let mut asset = new_asset(Some(manager), None, Some(freeze), None);
+
+            set_caller(manager);
+            assert_eq!(asset.set_clawback(manager), Err(Error::RoleDisabled));
+
+            // clearing the freeze role through the setter disables it too
+            assert_eq!(asset.set_freeze(zero), Ok(()));
+            assert_eq!(asset.freeze_id(), zero);
+            assert_eq!(asset.set_freeze(freeze), Err(Error::RoleDisabled));
+            assert_eq!(
+                asset.modify_asset(Some(manager), None, Some(freeze), None),
+                Err(Error::RoleDisabled)
+            );
+        }
+
+        // Test if set_reserve requires the new reserve to be opted in
+        #[ink::test]
+        fn set_reserve_throws_not_opted_in() {
+            let manager = account(0x9);
+            let treasury = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(manager);
+            assert_eq!(asset.set_reserve(treasury), Err(Error::NotOptedIn));
+            set_caller(treasury);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.set_reserve(treasury), Ok(()));
+            assert_eq!(asset.reserve_id(), treasury);
+        }
+
+        // Test if modify_asset validates every role before applying any
+        #[ink::test]
+        fn modify_asset_is_all_or_nothing() {
+            let manager = account(0x9);
+            let freeze = account(0x8);
+            let mut asset = new_asset(Some(manager), None, Some(freeze), None);
+            set_caller(manager);
+            // the freeze change is valid, but the clawback role is cleared
+            assert_eq!(
+                asset.modify_asset(Some(manager), None, Some(manager), Some(manager)),
+                Err(Error::RoleDisabled)
+            );
+            assert_eq!(asset.freeze_id(), freeze);
+            assert_eq!(asset.reserve_id(), account(0x1));
+        }
+
+        // Test if set_account_metadata stores a value readable by anyone
+        #[ink::test]
+        fn set_account_metadata_works() {
+            let holder = account(0x2);
+            let key = [0xAB; 32];
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.get_account_metadata(holder, key), None);
+
+            assert_eq!(asset.set_account_metadata(key, vec![1, 2, 3]), Ok(()));
+            assert_eq!(asset.get_account_metadata(holder, key), Some(vec![1, 2, 3]));
+            // other accounts and keys are untouched
+            assert_eq!(asset.get_account_metadata(account(0x1), key), None);
+            assert_eq!(asset.get_account_metadata(holder, [0xCD; 32]), None);
+
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            // signature + account + key
+            assert_eq!(events.last().unwrap().topics.len(), 3);
+        }
+
+        // Test if set_account_metadata requires opt-in and enforces the size limit
+        #[ink::test]
+        fn set_account_metadata_throws_on_invalid_input() {
+            let holder = account(0x2);
+            let key = [0xAB; 32];
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(
+                asset.set_account_metadata(key, vec![1]),
+                Err(Error::NotOptedIn)
+            );
+
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(
+                asset.set_account_metadata(key, vec![0; MAX_ACCOUNT_METADATA_SIZE + 1]),
+                Err(Error::MetadataTooLarge)
+            );
+            assert_eq!(
+                asset.set_account_metadata(key, vec![0; MAX_ACCOUNT_METADATA_SIZE]),
+                Ok(())
+            );
+        }
+
+        // Test if clear_account_metadata removes the value, even after opting out
+        #[ink::test]
+        fn clear_account_metadata_works() {
+            let holder = account(0x2);
+            let key = [0xAB; 32];
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.set_account_metadata(key, vec![1, 2, 3]), Ok(()));
+            assert_eq!(asset.opt_out(), Ok(()));
+
+            assert_eq!(asset.clear_account_metadata(key), Ok(()));
+            assert_eq!(asset.get_account_metadata(holder, key), None);
+        }
+
+        // Test if time_weighted_avg_balance averages balances over the elapsed blocks
+        #[ink::test]
+        fn time_weighted_avg_balance_works() {
+            let creator = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            // the creator holds 1000 for 10 blocks, then 600 for 10 blocks
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            set_caller(creator);
+            assert_eq!(asset.transfer(holder, 400), Ok(()));
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Environment>();
+            }
+
+            assert_eq!(asset.time_weighted_avg_balance(creator, 0), 800);
+            assert_eq!(asset.time_weighted_avg_balance(holder, 10), 400);
+            // half of the holding time of the holder falls in the window
+            assert_eq!(asset.time_weighted_avg_balance(holder, 0), 200);
+            // an empty window reports the current balance
+            assert_eq!(asset.time_weighted_avg_balance(creator, 20), 600);
+            assert_eq!(asset.time_weighted_avg_balance(account(0x3), 0), 0);
+        }
+
+        // Test if state-changing events carry the block timestamp
+        #[ink::test]
+        fn events_carry_block_timestamp() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+            let events_before = ink::env::test::recorded_events().count();
+
+            let timestamps = [1_000, 2_000, 3_000, 4_000, 5_000, 6_000];
+            ink::env::test::set_block_timestamp::<Environment>(timestamps[0]);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(timestamps[1]);
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(timestamps[2]);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(timestamps[3]);
+            assert_eq!(asset.revoke_asset(manager, holder, 100), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(timestamps[4]);
+            assert_eq!(
+                asset.modify_asset(Some(manager), Some(manager), Some(manager), Some(manager)),
+                Ok(())
+            );
+            ink::env::test::set_block_timestamp::<Environment>(timestamps[5]);
+            set_caller(holder);
+            assert_eq!(asset.opt_out(), Ok(()));
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(events[0], Event::OptIn(_)));
+            assert!(matches!(events[1], Event::Transfer(_)));
+            assert!(matches!(events[2], Event::Freeze(_)));
+            assert!(matches!(events[3], Event::Revoke(_)));
+            assert!(matches!(events[4], Event::Modify(_)));
+            assert!(matches!(events[5], Event::OptOut(_)));
+            let carried = events.iter().map(event_timestamp).collect::<Vec<_>>();
+            assert_eq!(carried, timestamps.map(Some));
+        }
+
+        // Test if last_activity_of and opted_in_at follow the holder's activity
+        #[ink::test]
+        fn activity_views_work() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            ink::env::test::set_block_timestamp::<Environment>(500);
+            let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+            assert_eq!(asset.opted_in_at(manager), Some(500));
+            assert_eq!(asset.last_activity_of(manager), Some(500));
+
+            ink::env::test::set_block_timestamp::<Environment>(1_000);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.opted_in_at(holder), Some(1_000));
+            assert_eq!(asset.last_activity_of(holder), Some(1_000));
+
+            // sending and receiving
+            ink::env::test::set_block_timestamp::<Environment>(2_000);
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            assert_eq!(asset.last_activity_of(manager), Some(2_000));
+            assert_eq!(asset.last_activity_of(holder), Some(2_000));
+
+            // freezing only touches the frozen account
+            ink::env::test::set_block_timestamp::<Environment>(3_000);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            assert_eq!(asset.last_activity_of(manager), Some(2_000));
+            assert_eq!(asset.last_activity_of(holder), Some(3_000));
+
+            // revoking touches the target and the receiver
+            ink::env::test::set_block_timestamp::<Environment>(4_000);
+            assert_eq!(asset.revoke_asset(manager, holder, 50), Ok(()));
+            assert_eq!(asset.last_activity_of(manager), Some(4_000));
+            assert_eq!(asset.last_activity_of(holder), Some(4_000));
+
+            // opting in again keeps the original opt in time
+            set_caller(holder);
+            assert_eq!(asset.opt_out(), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(5_000);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.opted_in_at(holder), Some(1_000));
+            assert_eq!(asset.last_activity_of(holder), Some(5_000));
+
+            assert_eq!(asset.opted_in_at(account(0x3)), None);
+            assert_eq!(asset.last_activity_of(account(0x3)), None);
+        }
+
+        // Test if holdings import in batches while pending, conserving the supply, and no more
+        // once the asset is activated
+        #[ink::test]
+        fn import_holdings_works() {
+            let manager = account(0x1);
+            let alice = account(0x2);
+            let bob = account(0x3);
+            let carol = account(0x4);
+            set_caller(manager);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                default_frozen: true,
+                manager: Some(manager),
+                freeze: Some(manager),
+                start_suspended: true,
+                ..Default::default()
+            })
+            .unwrap();
+            assert!(!asset.is_active());
+
+            set_caller(alice);
+            assert_eq!(
+                asset.import_holdings(vec![(alice, 100, false)]),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            assert_eq!(
+                asset.import_holdings(vec![(alice, 600, false), (bob, 401, false)]),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(
+                asset.import_holdings(vec![(alice, 1, false); MAX_STATUS_BATCH + 1]),
+                Err(Error::TooManyAccounts)
+            );
+
+            // two batches, the second adding to an imported account
+            assert_eq!(
+                asset.import_holdings(vec![(alice, 300, false), (bob, 200, true)]),
+                Ok(())
+            );
+            assert_eq!(
+                asset.import_holdings(vec![(carol, 150, false), (alice, 50, false)]),
+                Ok(())
+            );
+            let holders = [manager, alice, bob, carol];
+            let balances = holders.map(|holder| asset.balance_of(holder).unwrap());
+            assert_eq!(balances, [300, 350, 200, 150]);
+            assert_eq!(balances.iter().sum::<Balance>(), asset.total());
+            assert_eq!(asset.opted_in_count(), 4);
+            assert_eq!(asset.is_frozen(bob), Ok(true));
+            assert_eq!(asset.is_frozen(alice), Ok(false));
+
+            // transfers stay closed until activation, and imports close with it
+            set_caller(alice);
+            assert_eq!(asset.transfer(carol, 10), Err(Error::AssetNotActive));
+            set_caller(manager);
+            assert_eq!(asset.activate(), Ok(()));
+            assert!(asset.is_active());
+            assert_eq!(
+                asset.import_holdings(vec![(carol, 10, false)]),
+                Err(Error::ImportClosed)
+            );
+            assert_eq!(asset.suspend(), Ok(()));
+            assert_eq!(
+                asset.import_holdings(vec![(carol, 10, false)]),
+                Err(Error::ImportClosed)
+            );
+        }
+
+        // Test if assets start active, or pending with start_suspended
+        #[ink::test]
+        fn asset_status_starts_active_or_pending() {
+            let asset = new_asset(None, None, None, None);
+            assert_eq!(asset.asset_status(), AssetStatus::Active);
+
+            set_caller(account(0x1));
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                manager: Some(account(0x1)),
+                start_suspended: true,
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(asset.asset_status(), AssetStatus::Pending);
+            assert_eq!(asset.transfer(account(0x1), 0), Err(Error::AssetNotActive));
+            set_caller(account(0x2));
+            assert_eq!(asset.opt_in(), Err(Error::AssetNotActive));
+            assert_eq!(asset.activate(), Err(Error::NotManagerId));
+
+            set_caller(account(0x1));
+            assert_eq!(asset.activate(), Ok(()));
+            assert_eq!(asset.asset_status(), AssetStatus::Active);
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::StatusChanged(StatusChanged {
+                    old: AssetStatus::Pending,
+                    new: AssetStatus::Active,
+                })
+            ));
+        }
+
+        // Test if suspend blocks transfers and opt-ins until the asset is activated again
+        #[ink::test]
+        fn suspend_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.suspend(), Err(Error::NotManagerId));
+
+            set_caller(manager);
+            assert_eq!(asset.suspend(), Ok(()));
+            assert_eq!(asset.asset_status(), AssetStatus::Suspended);
+            assert_eq!(asset.suspend(), Err(Error::InvalidStatusTransition));
+            assert_eq!(asset.transfer(holder, 10), Err(Error::AssetNotActive));
+            assert_eq!(
+                asset.admin_transfer(manager, holder, 10),
+                Err(Error::AssetNotActive)
+            );
+            set_caller(account(0x3));
+            assert_eq!(asset.opt_in(), Err(Error::AssetNotActive));
+
+            set_caller(manager);
+            assert_eq!(asset.activate(), Ok(()));
+            assert_eq!(asset.activate(), Err(Error::InvalidStatusTransition));
+            assert_eq!(asset.transfer(holder, 10), Ok(()));
+        }
+
+        // Test if account_stats accumulates sent and received amounts across opt-outs
+        #[ink::test]
+        fn account_stats_works() {
+            let manager = account(0x1);
+            let alice = account(0x2);
+            let bob = account(0x3);
+            let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+            for holder in [alice, bob] {
+                set_caller(holder);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+
+            set_caller(manager);
+            assert_eq!(asset.transfer(alice, 300), Ok(()));
+            set_caller(alice);
+            assert_eq!(asset.transfer(bob, 100), Ok(()));
+            assert_eq!(asset.transfer(alice, 50), Ok(()));
+            // clawbacks count as sent by the target and received by the receiver
+            set_caller(manager);
+            assert_eq!(asset.freeze(bob, true, None), Ok(()));
+            assert_eq!(asset.revoke_asset(alice, bob, 40), Ok(()));
+
+            assert_eq!(
+                asset.account_stats(manager),
+                Some(AccountStats {
+                    total_received: 0,
+                    total_sent: 300,
+                    balance: 700,
+                })
+            );
+            assert_eq!(
+                asset.account_stats(alice),
+                Some(AccountStats {
+                    total_received: 390,
+                    total_sent: 150,
+                    balance: 240,
+                })
+            );
+            assert_eq!(
+                asset.account_stats(bob),
+                Some(AccountStats {
+                    total_received: 100,
+                    total_sent: 40,
+                    balance: 60,
+                })
+            );
+
+            // the totals survive opting out and in again
+            set_caller(alice);
+            assert_eq!(asset.opt_out(), Ok(()));
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.account_stats(alice).unwrap().total_received, 390);
+            assert_eq!(asset.account_stats(account(0x4)), None);
+        }
+
+        // Test if distribute_from_reserve credits every recipient from the reserve
+        #[ink::test]
+        fn distribute_from_reserve_works() {
+            let reserve = account(0x1);
+            let recipients = [account(0x2), account(0x3), account(0x4)];
+            let mut asset = new_asset(None, None, None, None);
+            for recipient in recipients {
+                set_caller(recipient);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            let events_before = ink::env::test::recorded_events().count();
+
+            set_caller(reserve);
+            assert_eq!(
+                asset.distribute_from_reserve(vec![
+                    (recipients[0], 100),
+                    (recipients[1], 200),
+                    (recipients[2], 300),
+                    (recipients[0], 50),
+                ]),
+                Ok(())
+            );
+            assert_eq!(asset.balance_of(reserve), Ok(350));
+            assert_eq!(asset.balance_of(recipients[0]), Ok(150));
+            assert_eq!(asset.balance_of(recipients[1]), Ok(200));
+            assert_eq!(asset.balance_of(recipients[2]), Ok(300));
+            assert_eq!(asset.unminted_supply(), 350);
+            assert_eq!(asset.circulating_supply(), 650);
+
+            // a single event for the whole distribution
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                events[0],
+                Event::ReserveDistribution(ReserveDistribution {
+                    recipient_count: 4,
+                    total_distributed: 650,
+                    ..
+                })
+            ));
+        }
+
+        // Test if distribute_from_reserve checks the caller, the total and the recipients
+        #[ink::test]
+        fn distribute_from_reserve_throws_on_invalid_input() {
+            let reserve = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(
+                asset.distribute_from_reserve(vec![(holder, 1)]),
+                Err(Error::NotReserveId)
+            );
+
+            set_caller(reserve);
+            assert_eq!(
+                asset.distribute_from_reserve(vec![(holder, 600), (reserve, 401)]),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(
+                asset.distribute_from_reserve(vec![(holder, 100), (account(0x3), 100)]),
+                Err(Error::NotOptedIn)
+            );
+            // nothing moved
+            assert_eq!(asset.balance_of(reserve), Ok(1000));
+            assert_eq!(asset.balance_of(holder), Ok(0));
+        }
+
+        // Test if holding_status distinguishes every holding state
+        #[ink::test]
+        fn holding_status_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, Some(manager), None);
+            assert_eq!(asset.holding_status(holder), HoldingStatus::NotOptedIn);
+
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(
+                asset.holding_status(holder),
+                HoldingStatus::OptedIn {
+                    balance: 0,
+                    frozen: false,
+                }
+            );
+
+            set_caller(manager);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            assert_eq!(
+                asset.holding_status(holder),
+                HoldingStatus::OptedIn {
+                    balance: 0,
+                    frozen: true,
+                }
+            );
+
+            assert_eq!(asset.freeze(holder, false, None), Ok(()));
+            assert_eq!(asset.transfer(holder, 10), Ok(()));
+            assert_eq!(
+                asset.holding_status(holder),
+                HoldingStatus::OptedIn {
+                    balance: 10,
+                    frozen: false,
+                }
+            );
+
+            set_caller(holder);
+            assert_eq!(asset.opt_out(), Ok(()));
+            assert_eq!(asset.holding_status(holder), HoldingStatus::NotOptedIn);
+        }
+
+        // Test if time locks block transfers of the locked amount until they expire
+        #[ink::test]
+        fn time_lock_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+
+            assert_eq!(asset.add_time_lock(holder, 60, 5), Ok(0));
+            assert_eq!(asset.add_time_lock(holder, 30, 10), Ok(1));
+            assert_eq!(asset.available_balance(holder), 10);
+
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 11), Err(Error::BalanceLocked));
+            assert_eq!(asset.transfer(manager, 101), Err(Error::NotEnoughBalance));
+            assert_eq!(asset.transfer(manager, 10), Ok(()));
+
+            // the first lock expires at block 5
+            for _ in 0..5 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(asset.available_balance(holder), 60);
+            assert_eq!(asset.transfer(manager, 61), Err(Error::BalanceLocked));
+
+            // anyone can remove the expired lock
+            set_caller(account(0x3));
+            assert_eq!(asset.remove_expired_locks(holder), Ok(60));
+            assert_eq!(asset.remove_expired_locks(holder), Ok(0));
+            let locks = asset.locks_of(holder);
+            assert_eq!(locks.len(), 1);
+            assert_eq!(locks[0].id, 1);
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::LockExpired(LockExpired {
+                    lock_id: 0,
+                    amount: 60,
+                    ..
+                })
+            ));
+        }
+
+        // Test if add_time_lock checks the caller, the amount and the expiry
+        #[ink::test]
+        fn add_time_lock_throws_on_invalid_input() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(
+                asset.add_time_lock(manager, 10, 5),
+                Err(Error::NotManagerId)
+            );
+
+            set_caller(manager);
+            assert_eq!(asset.add_time_lock(manager, 0, 5), Err(Error::ZeroAmount));
+            assert_eq!(
+                asset.add_time_lock(manager, 10, 0),
+                Err(Error::InvalidLockExpiry)
+            );
+            assert_eq!(
+                asset.add_time_lock(manager, 1001, 5),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(asset.add_time_lock(manager, 1000, 5), Ok(0));
+            assert_eq!(
+                asset.add_time_lock(manager, 1, 5),
+                Err(Error::NotEnoughBalance)
+            );
+        }
+
+        // Test if the spend limit caps transfers per window, and resets with the next window
+        #[ink::test]
+        fn spend_limit_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let receiver = account(0x3);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for account in [holder, receiver] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            set_caller(holder);
+            assert_eq!(asset.set_spend_limit(100, 1_000), Err(Error::NotManagerId));
+
+            set_caller(manager);
+            assert_eq!(asset.set_spend_limit(100, 1_000), Ok(()));
+            assert_eq!(asset.spend_limit(), (100, 1_000));
+            // the creator is exempt
+            assert_eq!(asset.transfer(holder, 500), Ok(()));
+
+            ink::env::test::set_block_timestamp::<Environment>(10_000);
+            set_caller(holder);
+            assert_eq!(asset.transfer(receiver, 60), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(10_500);
+            assert_eq!(asset.transfer(receiver, 40), Ok(()));
+            assert_eq!(
+                asset.transfer(receiver, 1),
+                Err(Error::SpendLimitExceeded {
+                    retry_after: 11_000
+                })
+            );
+
+            // the next window starts from zero
+            ink::env::test::set_block_timestamp::<Environment>(11_000);
+            assert_eq!(asset.transfer(receiver, 100), Ok(()));
+            assert_eq!(
+                asset.transfer(receiver, 1),
+                Err(Error::SpendLimitExceeded {
+                    retry_after: 12_000
+                })
+            );
+
+            // a limit of 0 disables it
+            set_caller(manager);
+            assert_eq!(asset.set_spend_limit(0, 1_000), Ok(()));
+            set_caller(holder);
+            assert_eq!(asset.transfer(receiver, 200), Ok(()));
+        }
+
+        // Test if opt_in_and_fund opts in and funds the account in a single call
+        #[ink::test]
+        fn opt_in_and_fund_works() {
+            let reserve = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            let events_before = ink::env::test::recorded_events().count();
+
+            set_caller(reserve);
+            assert_eq!(asset.opt_in_and_fund(holder, 250), Ok(()));
+            assert_eq!(asset.is_opted_in(holder), Ok(true));
+            assert_eq!(asset.balance_of(holder), Ok(250));
+            assert_eq!(asset.balance_of(reserve), Ok(750));
+            assert_eq!(asset.opted_in_count(), 2);
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(events[0], Event::OptIn(_)));
+            assert!(matches!(events[1], Event::Transfer(_)));
+        }
+
+        // Test if opt_in_and_fund checks the caller, the opt-in status and the reserve balance
+        #[ink::test]
+        fn opt_in_and_fund_throws_on_invalid_input() {
+            let reserve = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(
+                asset.opt_in_and_fund(account(0x3), 10),
+                Err(Error::NotReserveId)
+            );
+
+            set_caller(reserve);
+            assert_eq!(
+                asset.opt_in_and_fund(holder, 1001),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(asset.is_opted_in(holder), Ok(false));
+
+            assert_eq!(asset.opt_in_and_fund(holder, 10), Ok(()));
+            assert_eq!(
+                asset.opt_in_and_fund(holder, 10),
+                Err(Error::AlreadyOptedIn)
+            );
+            assert_eq!(asset.balance_of(holder), Ok(10));
+        }
+
+        // Test if a restricted account can only send to its allowed destinations
+        #[ink::test]
+        fn restrict_transfers_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let withdrawal = account(0x3);
+            let other = account(0x4);
+            let mut asset = new_asset(Some(manager), None, None, Some(manager));
+            for account in [holder, withdrawal, other] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+
+            set_caller(holder);
+            assert_eq!(asset.set_allowed_destination(withdrawal, true), Ok(()));
+            assert_eq!(asset.restrict_transfers(true), Ok(()));
+            assert!(asset.transfers_restricted(holder));
+            assert_eq!(asset.allowed_destinations(holder, 0, 10), vec![withdrawal]);
+
+            assert_eq!(asset.transfer(other, 10), Err(Error::DestinationNotAllowed));
+            assert_eq!(asset.transfer(withdrawal, 10), Ok(()));
+
+            // clawback bypasses the restriction
+            set_caller(manager);
+            assert_eq!(asset.revoke_asset(other, holder, 10), Ok(()));
+
+            // lifting the restriction
+            set_caller(holder);
+            assert_eq!(asset.restrict_transfers(false), Ok(()));
+            assert_eq!(asset.transfer(other, 10), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(70));
+        }
+
+        // Test if allowed destinations can be removed and paged through
+        #[ink::test]
+        fn allowed_destinations_work() {
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            for byte in 0x3..0x8 {
+                assert_eq!(asset.set_allowed_destination(account(byte), true), Ok(()));
+            }
+            // adding twice is a no-op
+            assert_eq!(asset.set_allowed_destination(account(0x3), true), Ok(()));
+            assert_eq!(asset.set_allowed_destination(account(0x4), false), Ok(()));
+
+            assert_eq!(
+                asset.allowed_destinations(holder, 0, 2),
+                vec![account(0x3), account(0x5)]
+            );
+            assert_eq!(
+                asset.allowed_destinations(holder, 2, 10),
+                vec![account(0x6), account(0x7)]
+            );
+            assert!(asset.allowed_destinations(holder, 4, 10).is_empty());
+        }
+
+        // Test if opt_in_and_fund enforces the minimum opt in balance
+        #[ink::test]
+        fn opt_in_and_fund_enforces_min_opt_in_balance() {
+            let reserve = account(0x1);
+            let holder = account(0x2);
+            set_caller(reserve);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                min_opt_in_balance: 50,
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(asset.min_opt_in_balance(), 50);
+
+            assert_eq!(
+                asset.opt_in_and_fund(holder, 49),
+                Err(Error::BelowMinimumOptInBalance)
+            );
+            assert_eq!(asset.is_opted_in(holder), Ok(false));
+            assert_eq!(asset.opt_in_and_fund(holder, 50), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(50));
+        }
+
+        // Test if wipe moves the whole balance to the reserve and clears the holder record
+        #[ink::test]
+        fn wipe_works() {
+            let reserve = account(0x1);
+            let clawback = account(0x2);
+            let holder = account(0x3);
+            let mut asset = new_asset(None, None, None, Some(clawback));
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(reserve);
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+            let events_before = ink::env::test::recorded_events().count();
+
+            set_caller(holder);
+            assert_eq!(asset.wipe(holder), Err(Error::NotClawbackId));
+            set_caller(clawback);
+            assert_eq!(asset.wipe(reserve), Err(Error::CannotWipeReserve));
+            assert_eq!(asset.wipe(holder), Ok(()));
+
+            assert_eq!(asset.balance_of(reserve), Ok(1000));
+            assert_eq!(asset.holding_status(holder), HoldingStatus::NotOptedIn);
+            assert_eq!(asset.stored_balance(holder), 0);
+            assert_eq!(asset.opted_in_count(), 1);
+            // the lifetime totals are kept
+            assert_eq!(asset.lifetime_totals.get(holder).unwrap().sent, 300);
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                events[0],
+                Event::Revoke(Revoke {
+                    amount: 300,
+                    from_balance: 0,
+                    to_balance: 1000,
+                    ..
+                })
+            ));
+            assert!(matches!(events[1], Event::Wiped(Wiped { amount: 300, .. })));
+
+            // a zero-balance target is wiped too
+            set_caller(account(0x4));
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(clawback);
+            assert_eq!(asset.wipe(account(0x4)), Ok(()));
+            assert_eq!(asset.is_opted_in(account(0x4)), Ok(false));
+            assert_eq!(asset.balance_of(reserve), Ok(1000));
+        }
+
+        // Test if the clawback role expires for good after its sunset
+        #[ink::test]
+        fn clawback_sunset_works() {
+            let manager = account(0x1);
+            let clawback = account(0x2);
+            let holder = account(0x3);
+            let sunset: Timestamp = 1_000;
+            set_caller(manager);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                manager: Some(manager),
+                clawback: Some(clawback),
+                clawback_sunset: Some(sunset),
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(asset.clawback_sunset(), Some(sunset));
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+
+            // up to the sunset itself, the clawback address still revokes
+            ink::env::test::set_block_timestamp::<Environment>(sunset);
+            assert!(asset.is_clawback_active());
+            set_caller(clawback);
+            assert_eq!(asset.revoke_asset(manager, holder, 100), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(200));
+
+            ink::env::test::set_block_timestamp::<Environment>(sunset + 1);
+            assert!(!asset.is_clawback_active());
+            assert_eq!(
+                asset.revoke_asset(manager, holder, 100),
+                Err(Error::ClawbackExpired)
+            );
+            assert_eq!(
+                asset.revoke_many(vec![(holder, None, manager)]),
+                Err(Error::ClawbackExpired)
+            );
+            assert_eq!(asset.wipe(holder), Err(Error::ClawbackExpired));
+            assert_eq!(asset.balance_of(holder), Ok(200));
+
+            // the role counts as cleared, so it cannot be handed over again
+            set_caller(manager);
+            assert_eq!(asset.set_clawback(manager), Err(Error::RoleDisabled));
+            assert_eq!(asset.set_clawback(account(0x0)), Ok(()));
+            assert!(!asset.is_clawback_active());
+        }
+
+        // Test if wipe only applies to frozen accounts with wipe_requires_freeze
+        #[ink::test]
+        fn wipe_requires_freeze_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            set_caller(manager);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                default_frozen: true,
+                manager: Some(manager),
+                freeze: Some(manager),
+                clawback: Some(manager),
+                wipe_requires_freeze: true,
+                ..Default::default()
+            })
+            .unwrap();
+            assert!(asset.wipe_requires_freeze());
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+
+            assert_eq!(asset.wipe(holder), Err(Error::NotFrozen));
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            assert_eq!(asset.wipe(holder), Ok(()));
+            assert_eq!(asset.balance_of(manager), Ok(1000));
+            // the freeze is cleared with the holder record
+            assert_eq!(asset.is_frozen(holder), Ok(false));
+        }
+
+        // Test if the clawback cap limits revokes per period, and resets with the next period
+        #[ink::test]
+        fn clawback_cap_works() {
+            let manager = account(0x1);
+            let clawback = account(0x2);
+            let holder = account(0x3);
+            let mut asset = new_asset(Some(manager), None, None, Some(clawback));
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 500), Ok(()));
+
+            set_caller(clawback);
+            assert_eq!(asset.set_clawback_cap(100, 10), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(asset.set_clawback_cap(100, 10), Ok(()));
+            assert_eq!(asset.clawback_cap(), (100, 10));
+
+            set_caller(clawback);
+            assert_eq!(asset.revoke_asset(manager, holder, 60), Ok(()));
+            assert_eq!(
+                asset.revoke_asset(manager, holder, 50),
+                Err(Error::ClawbackCapExceeded)
+            );
+            assert_eq!(asset.revoke_asset(manager, holder, 40), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(400));
+
+            // the period lasts `period_blocks` blocks after its start
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(
+                asset.revoke_asset(manager, holder, 1),
+                Err(Error::ClawbackCapExceeded)
+            );
+            ink::env::test::advance_block::<Environment>();
+            assert_eq!(asset.revoke_asset(manager, holder, 100), Ok(()));
+
+            // wipes count against the cap
+            assert_eq!(asset.wipe(holder), Err(Error::ClawbackCapExceeded));
+
+            // a cap of 0 disables it
+            set_caller(manager);
+            assert_eq!(asset.set_clawback_cap(0, 10), Ok(()));
+            set_caller(clawback);
+            assert_eq!(asset.wipe(holder), Ok(()));
+            assert_eq!(asset.balance_of(manager), Ok(1000));
+        }
+
+        /// Signs the opt-in of `nonce` with `key`, returning the signer's account and signature.
+        fn sign_opt_in(key: u8, nonce: u64) -> (AccountId, Vec<u8>) {
+            let key = secp256k1::SecretKey::from_slice(&[key; 32]).unwrap();
+            let public_key = key.public_key(secp256k1::SECP256K1).serialize();
+            let mut account = [0x0; 32];
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut account);
+
+            let message = (ink::env::account_id::<Environment>(), *b"opt_in", nonce);
+            let mut message_hash = [0x0; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&message, &mut message_hash);
+            let message = secp256k1::Message::from_slice(&message_hash).unwrap();
+            let (recovery_id, signature) = secp256k1::SECP256K1
+                .sign_ecdsa_recoverable(&message, &key)
+                .serialize_compact();
+            let mut signature = signature.to_vec();
+            signature.push(recovery_id.to_i32() as u8);
+            (AccountId::decode(&mut &account[..]).unwrap(), signature)
+        }
+
+        // Test if opt_in_for opts in the signer and records the sponsor
+        #[ink::test]
+        fn opt_in_for_works() {
+            let sponsor = account(0x5);
+            let mut asset = new_asset(None, None, None, None);
+            let (holder, signature) = sign_opt_in(0x11, 0);
+            let events_before = ink::env::test::recorded_events().count();
+
+            set_caller(sponsor);
+            assert_eq!(asset.opt_in_for(holder, signature.clone()), Ok(()));
+            assert_eq!(asset.is_opted_in(holder), Ok(true));
+            assert_eq!(asset.sponsor_of(holder), Some(sponsor));
+            assert_eq!(asset.opt_in_nonce(holder), 1);
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(events[0], Event::OptIn(OptIn { account, .. }) if account == holder));
+            assert!(matches!(
+                events[1],
+                Event::OptInSponsored(OptInSponsored { nonce: 0, .. })
+            ));
+
+            // the sponsor record ends with the opt-in
+            set_caller(holder);
+            assert_eq!(asset.opt_out(), Ok(()));
+            assert_eq!(asset.sponsor_of(holder), None);
+
+            // the signature cannot be replayed, but one over the next nonce works
+            set_caller(sponsor);
+            assert_eq!(
+                asset.opt_in_for(holder, signature),
+                Err(Error::InvalidSignature)
+            );
+            let (_, signature) = sign_opt_in(0x11, 1);
+            assert_eq!(asset.opt_in_for(holder, signature), Ok(()));
+        }
+
+        // Test if opt_in_for rejects signatures from anyone but the account
+        #[ink::test]
+        fn opt_in_for_rejects_invalid_signatures() {
+            let sponsor = account(0x5);
+            let mut asset = new_asset(None, None, None, None);
+            let (holder, signature) = sign_opt_in(0x11, 0);
+            let (other, other_signature) = sign_opt_in(0x22, 0);
+            set_caller(sponsor);
+
+            // signed by another key
+            assert_eq!(
+                asset.opt_in_for(holder, other_signature),
+                Err(Error::InvalidSignature)
+            );
+            // signed over another nonce
+            let (_, future_signature) = sign_opt_in(0x11, 1);
+            assert_eq!(
+                asset.opt_in_for(holder, future_signature),
+                Err(Error::InvalidSignature)
+            );
+            // malformed
+            assert_eq!(
+                asset.opt_in_for(holder, signature[..64].to_vec()),
+                Err(Error::InvalidSignature)
+            );
+            let mut bad_recovery_id = signature.clone();
+            bad_recovery_id[64] = 4;
+            assert_eq!(
+                asset.opt_in_for(holder, bad_recovery_id),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(asset.is_opted_in(holder), Ok(false));
+            assert_eq!(asset.is_opted_in(other), Ok(false));
+
+            // a second sponsor cannot opt in the account again
+            assert_eq!(asset.opt_in_for(holder, signature.clone()), Ok(()));
+            set_caller(account(0x6));
+            assert_eq!(
+                asset.opt_in_for(holder, signature),
+                Err(Error::InvalidSignature)
+            );
+            let (_, signature) = sign_opt_in(0x11, 1);
+            assert_eq!(
+                asset.opt_in_for(holder, signature),
+                Err(Error::AlreadyOptedIn)
+            );
+            assert_eq!(asset.sponsor_of(holder), Some(sponsor));
+            assert_eq!(asset.opt_in_nonce(holder), 1);
+        }
+
+        // Test if bundle_transfer makes every transfer of the bundle, and only of this asset
+        #[ink::test]
+        fn bundle_transfer_works() {
+            let creator = account(0x1);
+            let receivers = [account(0x2), account(0x3)];
+            let mut asset = new_asset(None, None, None, None);
+            for receiver in receivers {
+                set_caller(receiver);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            let this = ink::env::account_id::<Environment>();
+            let bundle = |amounts: [Balance; 2]| {
+                receivers
+                    .into_iter()
+                    .zip(amounts)
+                    .map(|(receiver, amount)| BundleTransfer {
+                        asset_contract: this,
+                        receiver,
+                        amount,
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            set_caller(creator);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.bundle_transfer(bundle([100, 200])), Ok(()));
+            assert_eq!(asset.balance_of(receivers[0]), Ok(100));
+            assert_eq!(asset.balance_of(receivers[1]), Ok(200));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 3);
+            assert!(matches!(
+                events[2],
+                Event::BundleTransferExecuted(BundleTransferExecuted { count: 2, .. })
+            ));
+
+            // transfers of another asset are not supported
+            let mut transfers = bundle([100, 200]);
+            transfers[1].asset_contract = account(0x9);
+            assert_eq!(
+                asset.bundle_transfer(transfers),
+                Err(Error::UnsupportedAssetContract)
+            );
+            assert_eq!(asset.balance_of(creator), Ok(700));
+
+            // the bundle fails with its first failing transfer
+            assert_eq!(
+                asset.bundle_transfer(bundle([100, 1000])),
+                Err(Error::NotEnoughBalance)
+            );
+        }
+
+        // Test if a pending transfer escrows the tokens until the receiver claims them
+        #[ink::test]
+        fn pending_transfer_claim_works() {
+            let creator = account(0x1);
+            let receiver = account(0x2);
+            let mut asset = new_asset(None, None, Some(creator), None);
+
+            set_caller(creator);
+            assert_eq!(
+                asset.send_pending(receiver, 100, 0),
+                Err(Error::InvalidPendingExpiry)
+            );
+            assert_eq!(
+                asset.send_pending(receiver, 0, 1_000),
+                Err(Error::ZeroAmount)
+            );
+            assert_eq!(asset.send_pending(receiver, 100, 1_000), Ok(0));
+            assert_eq!(asset.send_pending(receiver, 50, 2_000), Ok(1));
+            assert_eq!(asset.balance_of(creator), Ok(850));
+            assert_eq!(asset.pending_supply(), 150);
+            let pending = PendingTransfer {
+                id: 0,
+                sender: creator,
+                receiver,
+                amount: 100,
+                expiry: 1_000,
+            };
+            assert_eq!(asset.pending(0), Some(pending));
+            assert_eq!(asset.pending_for(receiver, 0, 1), vec![pending]);
+            assert_eq!(asset.pending_for(receiver, 1, 10).len(), 1);
+
+            // the receiver claims once opted in, even while frozen
+            set_caller(receiver);
+            assert_eq!(asset.claim_pending(0), Err(Error::NotOptedIn));
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(creator);
+            assert_eq!(asset.freeze(receiver, true, None), Ok(()));
+            assert_eq!(asset.claim_pending(0), Err(Error::NotPendingReceiver));
+            set_caller(receiver);
+            assert_eq!(asset.claim_pending(0), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(100));
+            assert_eq!(asset.pending_supply(), 50);
+            assert_eq!(asset.pending(0), None);
+            assert_eq!(asset.claim_pending(0), Err(Error::PendingTransferNotFound));
+
+            // an expired pending transfer can no longer be claimed
+            ink::env::test::set_block_timestamp::<Environment>(2_000);
+            assert_eq!(asset.claim_pending(1), Err(Error::PendingTransferExpired));
+        }
+
+        // Test if a pending transfer is refunded when its sender, or anyone after expiry, cancels it
+        #[ink::test]
+        fn pending_transfer_cancel_works() {
+            let creator = account(0x1);
+            let receiver = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(creator);
+            assert_eq!(asset.send_pending(receiver, 100, 1_000), Ok(0));
+            assert_eq!(asset.send_pending(receiver, 200, 1_000), Ok(1));
+
+            // the sender can cancel at any time
+            assert_eq!(asset.cancel_pending(0), Ok(()));
+            assert_eq!(asset.balance_of(creator), Ok(800));
+            assert_eq!(asset.pending_for(receiver, 0, 10).len(), 1);
+
+            // anyone else only once it expired
+            set_caller(account(0x3));
+            assert_eq!(asset.cancel_pending(1), Err(Error::CannotCancelPending));
+            ink::env::test::set_block_timestamp::<Environment>(1_000);
+            assert_eq!(asset.cancel_pending(1), Ok(()));
+            assert_eq!(asset.balance_of(creator), Ok(1000));
+            assert_eq!(asset.pending_supply(), 0);
+            assert!(asset.pending_for(receiver, 0, 10).is_empty());
+            assert_eq!(asset.cancel_pending(1), Err(Error::PendingTransferNotFound));
+        }
+
+        // Test if a frozen account can appeal, and the freeze address grant or reject it
+        #[ink::test]
+        fn freeze_appeal_works() {
+            let freeze = account(0x1);
+            let holder = account(0x2);
+            let reason_hash = [0x7; 32];
+            let mut asset = new_asset(None, None, Some(freeze), None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(
+                asset.submit_freeze_appeal(reason_hash),
+                Err(Error::NotFrozen)
+            );
+
+            set_caller(freeze);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            assert_eq!(
+                asset.resolve_appeal(holder, true),
+                Err(Error::NoPendingAppeal)
+            );
+            set_caller(holder);
+            assert_eq!(asset.submit_freeze_appeal(reason_hash), Ok(()));
+            assert_eq!(
+                asset.submit_freeze_appeal(reason_hash),
+                Err(Error::AppealPending)
+            );
+            assert_eq!(
+                asset.appeal_of(holder),
+                Some(FreezeAppeal {
+                    appellant: holder,
+                    reason_hash,
+                    submitted_block: 0,
+                    resolved: false,
+                })
+            );
+
+            // a rejected appeal keeps the account frozen, and it can appeal again
+            assert_eq!(asset.resolve_appeal(holder, false), Err(Error::NotFreezeId));
+            set_caller(freeze);
+            assert_eq!(asset.resolve_appeal(holder, false), Ok(()));
+            assert_eq!(asset.is_frozen(holder), Ok(true));
+            assert!(asset.appeal_of(holder).unwrap().resolved);
+            assert_eq!(
+                asset.resolve_appeal(holder, true),
+                Err(Error::NoPendingAppeal)
+            );
+            set_caller(holder);
+            assert_eq!(asset.submit_freeze_appeal(reason_hash), Ok(()));
+
+            // a granted appeal unfreezes the account
+            set_caller(freeze);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.resolve_appeal(holder, true), Ok(()));
+            assert_eq!(asset.is_frozen(holder), Ok(false));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                events[0],
+                Event::Freeze(Freeze { freeze: false, .. })
+            ));
+            assert!(matches!(
+                events[1],
+                Event::AppealResolved(AppealResolved { granted: true, .. })
+            ));
+        }
+
+        // Test if multicall executes its calls in order, stopping at the first failing one
+        #[ink::test]
+        fn multicall_works() {
+            let creator = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(creator);
+            assert_eq!(asset.send_pending(holder, 100, 1_000), Ok(0));
+
+            // opt in and claim in one call
+            set_caller(holder);
+            assert_eq!(
+                asset.multicall(vec![SubsaCall::OptIn, SubsaCall::ClaimPending { id: 0 }]),
+                Ok(())
+            );
+            assert_eq!(asset.balance_of(holder), Ok(100));
+
+            // a failing middle call fails the multicall, and later calls are not made
+            let receiver = account(0x3);
+            assert_eq!(
+                asset.multicall(vec![
+                    SubsaCall::Transfer {
+                        receiver: creator,
+                        amount: 10
+                    },
+                    SubsaCall::Transfer {
+                        receiver,
+                        amount: 10
+                    },
+                    SubsaCall::OptOut,
+                ]),
+                Err(Error::NotOptedIn)
+            );
+            assert_eq!(asset.is_opted_in(holder), Ok(true));
+            // Note: the off-chain environment does not revert a failed message, so the first
+            // transfer is kept here
+            assert_eq!(asset.balance_of(creator), Ok(910));
+
+            assert_eq!(
+                asset.multicall(vec![SubsaCall::OptOut; MAX_MULTICALL_CALLS + 1]),
+                Err(Error::TooManyCalls)
+            );
+        }
+
+        // Test if the reserve can fund the role addresses, opting them in first
+        #[ink::test]
+        fn fund_role_works() {
+            let reserve = account(0x1);
+            let manager = account(0x2);
+            let freeze = account(0x3);
+            let clawback = account(0x4);
+            let mut asset = new_asset(Some(manager), None, Some(freeze), Some(clawback));
+            set_caller(manager);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            assert_eq!(asset.fund_clawback(100), Err(Error::NotReserveId));
+            set_caller(reserve);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.fund_clawback(100), Ok(()));
+            assert_eq!(asset.balance_of(clawback), Ok(100));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 3);
+            assert!(matches!(events[0], Event::OptIn(_)));
+            assert!(matches!(events[1], Event::Transfer(_)));
+            assert!(matches!(
+                events[2],
+                Event::RoleFunded(RoleFunded {
+                    role: Role::Clawback,
+                    amount: 100,
+                    ..
+                })
+            ));
+
+            assert_eq!(asset.fund_freeze(200), Ok(()));
+            assert_eq!(asset.fund_manager(300), Ok(()));
+            assert_eq!(asset.balance_of(freeze), Ok(200));
+            assert_eq!(asset.balance_of(manager), Ok(300));
+            assert_eq!(asset.fund_manager(401), Err(Error::NotEnoughBalance));
+
+            // a cleared role cannot be funded
+            set_caller(manager);
+            assert_eq!(asset.set_freeze(zero_account()), Ok(()));
+            set_caller(reserve);
+            assert_eq!(asset.fund_freeze(100), Err(Error::RoleDisabled));
+        }
+
+        // Test if distributors authorized by the reserve can distribute from it until revoked
+        #[ink::test]
+        fn distributor_works() {
+            let reserve = account(0x1);
+            let distributor = account(0x2);
+            let recipient = account(0x3);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(recipient);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            // an unauthorized caller cannot distribute, nor authorize itself
+            set_caller(distributor);
+            assert_eq!(
+                asset.distribute_from_reserve(vec![(recipient, 100)]),
+                Err(Error::NotReserveId)
+            );
+            assert_eq!(
+                asset.set_distributor(distributor, true),
+                Err(Error::NotReserveId)
+            );
+
+            set_caller(reserve);
+            assert_eq!(asset.set_distributor(distributor, true), Ok(()));
+            assert!(asset.is_distributor(distributor));
+            set_caller(distributor);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(
+                asset.distribute_from_reserve(vec![(recipient, 100)]),
+                Ok(())
+            );
+            assert_eq!(asset.balance_of(reserve), Ok(900));
+            assert_eq!(asset.balance_of(recipient), Ok(100));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events[0],
+                Event::ReserveDistribution(ReserveDistribution { reserve_id, by, .. })
+                    if reserve_id == reserve && by == distributor
+            ));
+
+            // a revoked distributor can no longer distribute
+            set_caller(reserve);
+            assert_eq!(asset.set_distributor(distributor, false), Ok(()));
+            assert!(!asset.is_distributor(distributor));
+            set_caller(distributor);
+            assert_eq!(
+                asset.distribute_from_reserve(vec![(recipient, 100)]),
+                Err(Error::NotReserveId)
+            );
+            assert_eq!(asset.balance_of(recipient), Ok(100));
+        }
+
+        // Test if the manager role is handed over in two steps, and only to the proposed address
+        #[ink::test]
+        fn manager_proposal_works() {
+            let manager = account(0x1);
+            let proposed = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+
+            set_caller(proposed);
+            assert_eq!(asset.propose_manager(proposed), Err(Error::NotManagerId));
+            assert_eq!(asset.accept_manager(), Err(Error::NotPendingManager));
+            set_caller(manager);
+            assert_eq!(
+                asset.cancel_manager_proposal(),
+                Err(Error::NoManagerProposal)
+            );
+            assert_eq!(asset.propose_manager(proposed), Ok(()));
+            assert_eq!(asset.pending_manager(), Some(proposed));
+            // the proposal alone does not hand over the role
+            assert_eq!(asset.manager_id(), manager);
+
+            // only the proposed address can accept
+            set_caller(account(0x3));
+            assert_eq!(asset.accept_manager(), Err(Error::NotPendingManager));
+            set_caller(manager);
+            assert_eq!(asset.accept_manager(), Err(Error::NotPendingManager));
+
+            // a cancelled proposal can no longer be accepted
+            assert_eq!(asset.cancel_manager_proposal(), Ok(()));
+            assert_eq!(asset.pending_manager(), None);
+            set_caller(proposed);
+            assert_eq!(asset.accept_manager(), Err(Error::NotPendingManager));
+
+            set_caller(manager);
+            assert_eq!(asset.propose_manager(proposed), Ok(()));
+            set_caller(proposed);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.accept_manager(), Ok(()));
+            assert_eq!(asset.manager_id(), proposed);
+            assert_eq!(asset.pending_manager(), None);
+            assert_eq!(asset.asset_version(), 1);
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(events[0], Event::RoleChanged(_)));
+            assert!(matches!(events[1], Event::ManagerAccepted(_)));
+
+            // a proposal ends with the manager that made it
+            assert_eq!(asset.propose_manager(manager), Ok(()));
+            assert_eq!(asset.set_manager(account(0x3)), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.accept_manager(), Err(Error::NotPendingManager));
+        }
+
+        /// Mock of the pallet-assets chain extension, shared by its four functions.
+        #[cfg(feature = "runtime-mirror")]
+        #[derive(Clone, Default)]
+        struct MockAssets {
+            func_id: u32,
+            balances:
+                std::rc::Rc<std::cell::RefCell<std::collections::BTreeMap<AccountId, Balance>>>,
+            fail: std::rc::Rc<std::cell::Cell<bool>>,
+        }
+
+        #[cfg(feature = "runtime-mirror")]
+        impl ink::env::test::ChainExtension for MockAssets {
+            fn func_id(&self) -> u32 {
+                self.func_id
+            }
+
+            fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                if self.fail.get() {
+                    return 1;
+                }
+                // Note: the off-chain engine passes the encoded arguments as encoded bytes
+                let input = Vec::<u8>::decode(&mut &input[..]).unwrap();
+                let mut balances = self.balances.borrow_mut();
+                match self.func_id {
+                    0x5300_0001 => 7u32.encode_to(output),
+                    0x5300_0002 => {
+                        let (_, to, amount) =
+                            <(u32, AccountId, Balance)>::decode(&mut &input[..]).unwrap();
+                        *balances.entry(to).or_default() += amount;
+                    }
+                    0x5300_0003 => {
+                        let (_, from, amount) =
+                            <(u32, AccountId, Balance)>::decode(&mut &input[..]).unwrap();
+                        *balances.entry(from).or_default() -= amount;
+                    }
+                    _ => {
+                        let (_, from, to, amount) =
+                            <(u32, AccountId, AccountId, Balance)>::decode(&mut &input[..])
+                                .unwrap();
+                        *balances.entry(from).or_default() -= amount;
+                        *balances.entry(to).or_default() += amount;
+                    }
+                }
+                0
+            }
+        }
+
+        /// Registers the mock pallet-assets chain extension, returning it.
+        #[cfg(feature = "runtime-mirror")]
+        fn register_mock_assets() -> MockAssets {
+            let mock = MockAssets::default();
+            for func_id in 0x5300_0001..=0x5300_0004 {
+                ink::env::test::register_chain_extension(MockAssets {
+                    func_id,
+                    ..mock.clone()
+                });
+            }
+            mock
+        }
+
+        // Test if the runtime mirror registers the asset and follows every balance change
+        #[cfg(feature = "runtime-mirror")]
+        #[ink::test]
+        fn runtime_mirror_follows_balances() {
+            let mock = register_mock_assets();
+            // Note: the off-chain contract account is the creator's, so the reserve is another
+            let reserve = account(0x3);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, Some(reserve), None, None);
+            assert_eq!(asset.mirror_asset_id(), Some(7));
+            assert_eq!(mock.balances.borrow().get(&reserve), Some(&1000));
+
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(reserve);
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+            assert_eq!(asset.send_pending(holder, 100, 1_000), Ok(0));
+            // the escrow is held by the contract on the mirror asset
+            let this = ink::env::account_id::<Environment>();
+            assert_eq!(mock.balances.borrow().get(&this), Some(&100));
+            set_caller(holder);
+            assert_eq!(asset.claim_pending(0), Ok(()));
+
+            let balances = mock.balances.borrow();
+            assert_eq!(balances.get(&reserve), Some(&600));
+            assert_eq!(balances.get(&holder), Some(&400));
+            assert_eq!(balances.get(&this), Some(&0));
+        }
+
+        // Test if a failing runtime mirror fails the balance change, leaving it unchanged
+        #[cfg(feature = "runtime-mirror")]
+        #[ink::test]
+        fn runtime_mirror_failure_changes_nothing() {
+            let mock = register_mock_assets();
+            let reserve = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            mock.fail.set(true);
+            set_caller(reserve);
+            assert_eq!(asset.transfer(holder, 300), Err(Error::RuntimeMirrorFailed));
+            assert_eq!(asset.balance_of(reserve), Ok(1000));
+            assert_eq!(asset.balance_of(holder), Ok(0));
+            assert_eq!(
+                asset.send_pending(holder, 100, 1_000),
+                Err(Error::RuntimeMirrorFailed)
+            );
+            assert_eq!(asset.pending_supply(), 0);
+            assert_eq!(
+                Subsa::new_with_config(AssetConfig {
+                    total: 1000,
+                    ..Default::default()
+                })
+                .err(),
+                Some(Error::RuntimeMirrorFailed)
+            );
+        }
+
+        // Test if is_fully_distributed and reserve_fraction_bps follow the reserve's balance
+        #[ink::test]
+        fn distribution_progress_works() {
+            let reserve = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            assert!(!asset.is_fully_distributed());
+            assert_eq!(asset.reserve_fraction_bps(), 10_000);
+
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(reserve);
+            assert_eq!(asset.transfer(holder, 333), Ok(()));
+            assert!(!asset.is_fully_distributed());
+            assert_eq!(asset.reserve_fraction_bps(), 6_670);
+
+            assert_eq!(asset.transfer(holder, 667), Ok(()));
+            assert!(asset.is_fully_distributed());
+            assert_eq!(asset.reserve_fraction_bps(), 0);
+        }
+
+        // Test if cancel_opt_in only removes opted-in accounts without a balance
+        #[ink::test]
+        fn cancel_opt_in_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), Some(manager), Some(manager), Some(manager));
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+
+            set_caller(holder);
+            assert_eq!(asset.cancel_opt_in(holder), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(asset.cancel_opt_in(holder), Err(Error::NonZeroBalance));
+            assert_eq!(asset.cancel_opt_in(account(0x3)), Err(Error::NotOptedIn));
+
+            // once the frozen holder's balance is revoked, its opt-in can be cancelled
+            assert_eq!(asset.revoke_asset(manager, holder, 100), Ok(()));
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.cancel_opt_in(holder), Ok(()));
+            assert!(!asset.holder(holder).opted_in);
+            assert!(asset.holder(holder).frozen);
+            assert_eq!(asset.opted_in_count(), 1);
+            assert_eq!(asset.balance_of(holder), Err(Error::NotOptedIn));
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                events[0],
+                Event::OptInCancelled(OptInCancelled { account, manager_id })
+                    if account == holder && manager_id == manager
+            ));
+        }
+
+        // Test if only the manager can set the transfer hook, and movements work without one
+        #[ink::test]
+        fn set_transfer_hook_works() {
+            let manager = account(0x1);
+            let hook = account(0x5);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            assert_eq!(asset.transfer_hook(), None);
+
+            set_caller(account(0x2));
+            assert_eq!(
+                asset.set_transfer_hook(Some(hook)),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            assert_eq!(asset.set_transfer_hook(Some(hook)), Ok(()));
+            assert_eq!(asset.transfer_hook(), Some(hook));
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::TransferHookSet(TransferHookSet { hook: Some(set) }) if set == hook
+            ));
+
+            assert_eq!(asset.set_transfer_hook(None), Ok(()));
+            assert_eq!(asset.transfer_hook(), None);
+            set_caller(account(0x2));
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(account(0x2), 100), Ok(()));
+            assert_eq!(asset.balance_of(account(0x2)), Ok(100));
+        }
+
+        // Test if a time-limited freeze can be released by anyone once its expiry block is reached
+        #[ink::test]
+        fn release_expired_freeze_works() {
+            let freeze = account(0x3);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, Some(freeze), None);
+            set_caller(freeze);
+            let expiry = ink::env::block_number::<Environment>() + 2;
+            assert_eq!(
+                asset.freeze(holder, true, Some(expiry - 2)),
+                Err(Error::InvalidFreezeExpiry)
+            );
+            assert_eq!(asset.freeze(holder, true, Some(expiry)), Ok(()));
+            assert_eq!(
+                asset.freeze(holder, false, Some(expiry)),
+                Err(Error::InvalidFreezeExpiry)
+            );
+            assert_eq!(asset.freeze_expiry(holder), Some(expiry));
+
+            set_caller(account(0x4));
+            assert_eq!(
+                asset.release_expired_freeze(holder),
+                Err(Error::FreezeNotExpired)
+            );
+            ink::env::test::advance_block::<Environment>();
+            assert_eq!(
+                asset.release_expired_freeze(holder),
+                Err(Error::FreezeNotExpired)
+            );
+            ink::env::test::advance_block::<Environment>();
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.release_expired_freeze(holder), Ok(()));
+            assert!(!asset.holder(holder).frozen);
+            assert_eq!(asset.freeze_expiry(holder), None);
+            assert_eq!(asset.release_expired_freeze(holder), Err(Error::NotFrozen));
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                events[0],
+                Event::Freeze(Freeze { freeze: false, .. })
+            ));
+            assert!(matches!(
+                events[1],
+                Event::FreezeExpired(FreezeExpired { released_at, .. }) if released_at == expiry
+            ));
+
+            // a freeze without expiry, or refreezing without one, is never released
+            set_caller(freeze);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            assert_eq!(
+                asset.release_expired_freeze(holder),
+                Err(Error::FreezeNotExpired)
+            );
+            assert_eq!(asset.freeze(holder, false, None), Ok(()));
+            assert_eq!(asset.freeze(holder, true, Some(expiry + 5)), Ok(()));
+            assert_eq!(asset.freeze(holder, false, None), Ok(()));
+            assert_eq!(asset.freeze_expiry(holder), None);
+        }
+
+        // Test if a state-changing message is rejected while another one runs with a hook, but
+        // reads still work
+        #[ink::test]
+        fn reentrancy_guard_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            // without a hook, the flag is neither needed nor written
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            assert_eq!(asset.entered.get(), None);
+
+            // the flag is set while the outer message calls the hook
+            assert_eq!(asset.set_transfer_hook(Some(account(0x5))), Ok(()));
+            asset.entered.set(&true);
+            set_caller(account(0x5));
+            assert_eq!(asset.transfer(holder, 1), Err(Error::ReentrancyDetected));
+            assert_eq!(asset.opt_in(), Err(Error::ReentrancyDetected));
+            assert_eq!(
+                asset.release_expired_freeze(holder),
+                Err(Error::ReentrancyDetected)
+            );
+            set_caller(manager);
+            assert_eq!(
+                asset.set_transfer_hook(None),
+                Err(Error::ReentrancyDetected)
+            );
+            assert_eq!(asset.destroy_asset(), Err(Error::ReentrancyDetected));
+            assert_eq!(asset.balance_of(holder), Ok(100));
+            assert_eq!(asset.transfer_hook(), Some(account(0x5)));
+
+            asset.entered.set(&false);
+            assert_eq!(asset.set_transfer_hook(None), Ok(()));
+            assert_eq!(asset.transfer(holder, 1), Ok(()));
+        }
+
+        // Test if partially frozen tokens cannot be transferred until unfrozen
+        #[ink::test]
+        fn partial_freeze_works() {
+            let freeze = account(0x3);
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, Some(freeze), None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(account(0x1));
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+
+            set_caller(holder);
+            assert_eq!(asset.partial_freeze(holder, 60), Err(Error::NotFreezeId));
+            set_caller(freeze);
+            assert_eq!(asset.partial_freeze(holder, 0), Err(Error::ZeroAmount));
+            assert_eq!(
+                asset.partial_freeze(holder, 101),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(asset.partial_freeze(holder, 60), Ok(()));
+            assert_eq!(
+                asset.partial_freeze(holder, 41),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(asset.partially_frozen_balance_of(holder), 60);
+            assert_eq!(asset.available_balance(holder), 40);
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::PartialFreeze(PartialFreeze { amount: 60, .. })
+            ));
+
+            set_caller(holder);
+            assert_eq!(asset.transfer(account(0x1), 41), Err(Error::BalanceLocked));
+            assert_eq!(asset.transfer(account(0x1), 40), Ok(()));
+
+            set_caller(freeze);
+            assert_eq!(
+                asset.unpartial_freeze(holder, 61),
+                Err(Error::NotPartiallyFrozen)
+            );
+            assert_eq!(asset.unpartial_freeze(holder, 20), Ok(()));
+            assert_eq!(asset.partially_frozen_balance_of(holder), 40);
+            set_caller(holder);
+            assert_eq!(asset.transfer(account(0x1), 20), Ok(()));
+            assert_eq!(asset.transfer(account(0x1), 1), Err(Error::BalanceLocked));
+
+            set_caller(freeze);
+            assert_eq!(asset.unpartial_freeze(holder, 40), Ok(()));
+            assert_eq!(asset.partially_frozen_balance_of(holder), 0);
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::PartialUnfreeze(PartialUnfreeze { amount: 40, .. })
+            ));
+            set_caller(holder);
+            assert_eq!(asset.transfer(account(0x1), 40), Ok(()));
+        }
+
+        // Test if a lien encumbers collateral until released or enforced for the lender
+        #[ink::test]
+        fn lien_works() {
+            let manager = account(0x1);
+            let borrower = account(0x2);
+            let lender = account(0x3);
+            let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+            for account in [borrower, lender] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            assert_eq!(
+                asset.create_lien(borrower, lender, 60),
+                Err(Error::NotFreezeId)
+            );
+            set_caller(manager);
+            assert_eq!(asset.transfer(borrower, 100), Ok(()));
+            assert_eq!(
+                asset.create_lien(borrower, lender, 101),
+                Err(Error::NotEnoughBalance)
+            );
+            assert_eq!(asset.create_lien(borrower, lender, 60), Ok(()));
+            assert_eq!(asset.lien(borrower, lender), 60);
+            assert_eq!(asset.liened_balance_of(borrower), 60);
+            assert_eq!(asset.available_balance(borrower), 40);
+
+            // the encumbered tokens cannot be transferred
+            set_caller(borrower);
+            assert_eq!(asset.transfer(manager, 41), Err(Error::BalanceLocked));
+            assert_eq!(asset.transfer(manager, 20), Ok(()));
+
+            // a partial release frees part of the collateral
+            set_caller(manager);
+            assert_eq!(
+                asset.release_lien(borrower, lender, 61),
+                Err(Error::InsufficientLien)
+            );
+            assert_eq!(asset.release_lien(borrower, lender, 10), Ok(()));
+            assert_eq!(asset.lien(borrower, lender), 50);
+            assert_eq!(asset.available_balance(borrower), 30);
+
+            // enforcing sends the encumbered tokens to the lender, and clears the lien
+            set_caller(lender);
+            assert_eq!(
+                asset.enforce_lien(borrower, lender),
+                Err(Error::NotClawbackId)
+            );
+            set_caller(manager);
+            assert_eq!(asset.enforce_lien(borrower, lender), Ok(()));
+            assert_eq!(asset.balance_of(borrower), Ok(30));
+            assert_eq!(asset.balance_of(lender), Ok(50));
+            assert_eq!(asset.lien(borrower, lender), 0);
+            assert_eq!(asset.liened_balance_of(borrower), 0);
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(&events[events.len() - 2]),
+                Event::Revoke(Revoke { amount: 50, .. })
+            ));
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::LienEnforced(LienEnforced { amount: 50, .. })
+            ));
+            assert_eq!(
+                asset.enforce_lien(borrower, lender),
+                Err(Error::InsufficientLien)
+            );
+        }
+
+        // Test if opted-in accounts can elect and revoke dividend reinvestment
+        #[ink::test]
+        fn elect_drip_works() {
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.elect_drip(true), Err(Error::NotOptedIn));
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert!(!asset.drip_elected(holder));
+
+            assert_eq!(asset.elect_drip(true), Ok(()));
+            assert!(asset.drip_elected(holder));
+            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert!(matches!(
+                decode_event(events.last().unwrap()),
+                Event::DripElectionChanged(DripElectionChanged { enabled: true, .. })
+            ));
+
+            assert_eq!(asset.elect_drip(false), Ok(()));
+            assert!(!asset.drip_elected(holder));
+        }
+
+        // Test if transfers and pending transfers to the zero address are rejected
+        #[ink::test]
+        fn transfer_to_zero_address_fails() {
+            let reserve = account(0x1);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(reserve);
+            assert_eq!(
+                asset.transfer(zero_account(), 100),
+                Err(Error::TransferToZeroAddress)
+            );
+            assert_eq!(
+                asset.send_pending(zero_account(), 100, 1_000),
+                Err(Error::TransferToZeroAddress)
+            );
+            assert_eq!(asset.balance_of(reserve), Ok(1000));
+            assert_eq!(asset.total(), 1000);
+            assert_eq!(asset.stored_balance(zero_account()), 0);
+        }
+
+        // Test if destroy_asset terminates the contract, emitting the final statistics
+        // Note: the off-chain engine only decodes the termination of 32-byte account ids and
+        // 128-bit balances
+        #[cfg(not(feature = "custom-env"))]
+        #[ink::test]
+        fn destroy_asset_emits_final_statistics() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            assert_eq!(
+                asset.destroy_asset(),
+                Err(Error::NotAllAssetsOwnedByManager)
+            );
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 60), Ok(()));
+            assert_eq!(asset.send_pending(manager, 40, 1_000), Ok(0));
+            set_caller(manager);
+            assert_eq!(asset.claim_pending(0), Ok(()));
+            assert_eq!(asset.lifetime_transfers(), 4);
+
+            set_caller(holder);
+            assert_eq!(asset.destroy_asset(), Err(Error::NotManagerId));
+            set_caller(manager);
+            let events_before = ink::env::test::recorded_events().count();
+            let refund = ink::env::balance::<Environment>();
+            let destroy = move || {
+                let _ = asset.destroy_asset();
+            };
+            ink::env::test::assert_contract_termination::<Environment, _>(destroy, manager, refund);
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last(),
+                Some(Event::Destruction(Destruction {
+                    beneficiary,
+                    total_supply: 1000,
+                    lifetime_transfers: 4,
+                    holder_count_at_destroy: 2,
+                    ..
+                })) if *beneficiary == manager
+            ));
+        }
+
+        // Test if transfers pay the configured fee to the reserve, unless the sender is exempt
+        #[ink::test]
+        fn transfer_fee_works() {
+            let manager = account(0x1);
+            let treasury = account(0x2);
+            let holder = account(0x3);
+            let receiver = account(0x4);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for account in [treasury, holder, receiver] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            set_caller(manager);
+            assert_eq!(
+                asset.set_transfer_fee(1_001),
+                Err(Error::InvalidTransferFee)
+            );
+            assert_eq!(asset.set_transfer_fee(250), Ok(()));
+            assert_eq!(asset.transfer_fee_bps(), 250);
+            // the reserve pays no fee
+            assert_eq!(asset.transfer(holder, 400), Ok(()));
+            assert_eq!(asset.transfer(treasury, 400), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(400));
+
+            // a non-exempt sender pays 2.5%, rounded down
+            set_caller(holder);
+            assert_eq!(
+                asset.set_fee_exempt(treasury, true),
+                Err(Error::NotManagerId)
+            );
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.transfer(receiver, 199), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(195));
+            assert_eq!(asset.balance_of(holder), Ok(201));
+            assert_eq!(asset.balance_of(manager), Ok(204));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                events[0],
+                Event::Transfer(Transfer {
+                    amount: Some(195),
+                    ..
+                })
+            ));
+            assert!(matches!(
+                events[1],
+                Event::TransferFeeCollected(TransferFeeCollected { fee: 4, .. })
+            ));
+
+            // an exempt sender pays none
+            set_caller(manager);
+            assert_eq!(asset.set_fee_exempt(treasury, true), Ok(()));
+            assert!(asset.is_fee_exempt(treasury));
+            set_caller(treasury);
+            assert_eq!(asset.transfer(receiver, 200), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(395));
+            assert_eq!(asset.balance_of(manager), Ok(204));
+
+            set_caller(manager);
+            assert_eq!(asset.set_fee_exempt(treasury, false), Ok(()));
+            assert!(!asset.is_fee_exempt(treasury));
+            set_caller(treasury);
+            assert_eq!(asset.transfer(receiver, 200), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(590));
+        }
+
+        // Test if opt_in requires the opt-in fee and refunds the excess
+        // Note: the off-chain value transfer API needs u128 balances
+        #[cfg(not(feature = "custom-env"))]
+        #[ink::test]
+        fn opt_in_fee_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.set_opt_in_fee(10), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(asset.set_opt_in_fee(10), Ok(()));
+            assert_eq!(asset.opt_in_fee(), 10);
+
+            set_caller(holder);
+            ink::env::test::set_value_transferred::<Environment>(9);
+            assert_eq!(asset.opt_in(), Err(Error::InsufficientOptInFee));
+            assert_eq!(asset.holding_status(holder), HoldingStatus::NotOptedIn);
+
+            let balance_before =
+                ink::env::test::get_account_balance::<Environment>(holder).unwrap_or_default();
+            let events_before = ink::env::test::recorded_events().count();
+            ink::env::test::set_value_transferred::<Environment>(25);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.is_opted_in(holder), Ok(true));
+            // the excess over the fee goes back to the holder
+            assert_eq!(
+                ink::env::test::get_account_balance::<Environment>(holder).unwrap_or_default(),
+                balance_before + 15
+            );
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(events[0], Event::OptIn(_)));
+            assert!(matches!(
+                events[1],
+                Event::OptInFeeCollected(OptInFeeCollected { fee: 10, .. })
+            ));
+        }
+
+        // Test if a sender waits out the cooldown between transfers
+        #[ink::test]
+        fn transfer_cooldown_works() {
+            let manager = account(0x1);
+            let alice = account(0x2);
+            let bob = account(0x3);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for account in [alice, bob] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            assert_eq!(asset.set_cooldown(3), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(asset.set_cooldown(3), Ok(()));
+            assert_eq!(asset.cooldown_blocks(), 3);
+
+            // the reserve is not subject to the cooldown
+            assert_eq!(asset.transfer(alice, 100), Ok(()));
+            assert_eq!(asset.transfer(alice, 100), Ok(()));
+
+            set_caller(alice);
+            assert_eq!(asset.transfer(bob, 10), Ok(()));
+            assert_eq!(asset.transfer(bob, 10), Err(Error::CooldownNotExpired));
+            for _ in 0..2 {
+                ink::env::test::advance_block::<Environment>();
+            }
+            assert_eq!(asset.transfer(bob, 10), Err(Error::CooldownNotExpired));
+            ink::env::test::advance_block::<Environment>();
+            assert_eq!(asset.transfer(bob, 10), Ok(()));
+            assert_eq!(asset.balance_of(bob), Ok(20));
 
-            // handle balance of creator and reserve address
-            // if reserve address is not provided, creator is the reserve address
-            let reserve_id = reserve.unwrap_or_else(|| Self::env().caller());
+            // the cooldown is per sender, and disabled at 0
+            set_caller(bob);
+            assert_eq!(asset.transfer(alice, 5), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.set_cooldown(0), Ok(()));
+            set_caller(alice);
+            assert_eq!(asset.transfer(bob, 10), Ok(()));
+        }
 
-            let mut accounts_opted_in = Mapping::default();
-            accounts_opted_in.insert(reserve_id, &true);
+        // Test if transfers above the threshold only go through with the manager's approval
+        #[ink::test]
+        fn large_transfer_works() {
+            let manager = account(0x1);
+            let alice = account(0x2);
+            let bob = account(0x3);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for account in [alice, bob] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            assert_eq!(
+                asset.set_large_transfer_threshold(100),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            assert_eq!(asset.set_large_transfer_threshold(100), Ok(()));
+            assert_eq!(asset.large_transfer_threshold(), 100);
 
-            let mut balances = Mapping::default();
-            balances.insert(reserve_id, &total);
+            // the reserve is not subject to the threshold
+            assert_eq!(asset.transfer(alice, 500), Ok(()));
 
-            // initialize asset params
-            Self {
-                creator: Self::env().caller(),
-                asset_name,
-                unit_name,
-                total,
-                decimals,
-                default_frozen,
-                url,
-                metadata_hash,
-                manager_id: manager.unwrap_or_else(|| AccountId::from([0x0; 32])),
-                reserve_id,
-                freeze_id: freeze.unwrap_or_else(|| AccountId::from([0x0; 32])),
-                clawback_id: clawback.unwrap_or_else(|| AccountId::from([0x0; 32])),
-                balances,
-                accounts_opted_in,
-                frozen_holders: Mapping::default(),
+            set_caller(alice);
+            assert_eq!(asset.transfer(bob, 100), Ok(()));
+            assert_eq!(
+                asset.transfer(bob, 101),
+                Err(Error::LargeTransferRequiresApproval)
+            );
+            assert_eq!(
+                asset.initiate_large_transfer(bob, 100),
+                Err(Error::NotLargeTransfer)
+            );
+            assert_eq!(asset.initiate_large_transfer(bob, 150), Ok(0));
+            assert_eq!(asset.initiate_large_transfer(bob, 200), Ok(1));
+            assert_eq!(asset.large_transfer(0), Some((alice, bob, 150)));
+            assert_eq!(asset.large_transfer_supply(), 350);
+            assert_eq!(asset.balance_of(alice), Ok(50));
+
+            // only the manager approves, and only the sender cancels
+            assert_eq!(asset.approve_large_transfer(0), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(
+                asset.cancel_large_transfer(1),
+                Err(Error::NotLargeTransferSender)
+            );
+            assert_eq!(asset.approve_large_transfer(0), Ok(()));
+            assert_eq!(asset.balance_of(bob), Ok(250));
+            assert_eq!(asset.large_transfer(0), None);
+            assert_eq!(
+                asset.approve_large_transfer(0),
+                Err(Error::LargeTransferNotFound)
+            );
+            set_caller(alice);
+            assert_eq!(asset.cancel_large_transfer(1), Ok(()));
+            assert_eq!(asset.balance_of(alice), Ok(250));
+            assert_eq!(asset.large_transfer_supply(), 0);
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let Event::LargeTransferCancelled(event) = decode_event(emitted_events.last().unwrap())
+            else {
+                panic!("expected a LargeTransferCancelled event");
+            };
+            assert_eq!((event.id, event.sender, event.amount), (1, alice, 200));
+        }
+
+        // Test if transfers require a subscription, renewed by paying its fee
+        // Note: the off-chain value transfer API needs u128 balances
+        #[cfg(not(feature = "custom-env"))]
+        #[ink::test]
+        fn subscription_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(
+                asset.set_subscription_params(10, 5),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            assert_eq!(asset.set_subscription_params(10, 5), Ok(()));
+            assert_eq!(asset.subscription_params(), (10, 5));
+
+            // the reserve needs no subscription, the holder does
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 10), Err(Error::SubscriptionExpired));
+
+            ink::env::test::set_value_transferred::<Environment>(9);
+            assert_eq!(
+                asset.pay_subscription(),
+                Err(Error::InsufficientSubscriptionFee)
+            );
+            ink::env::test::set_value_transferred::<Environment>(10);
+            assert_eq!(asset.pay_subscription(), Ok(()));
+            assert_eq!(asset.subscription_expiry(holder), 5);
+            // renewing early extends the current subscription
+            assert_eq!(asset.pay_subscription(), Ok(()));
+            assert_eq!(asset.subscription_expiry(holder), 10);
+            assert_eq!(asset.native_accounting().opt_in_fees, 20);
+            let event = ink::env::test::recorded_events().last().unwrap();
+            let Event::SubscriptionRenewed(event) = decode_event(&event) else {
+                panic!("expected a SubscriptionRenewed event");
+            };
+            assert_eq!(event.account, holder);
+            assert_eq!(event.new_expiry, 10);
+
+            // the subscription covers transfers up to its expiry block
+            for _ in 0..10 {
+                ink::env::test::advance_block::<Environment>();
             }
+            assert_eq!(asset.transfer(manager, 10), Ok(()));
+            ink::env::test::advance_block::<Environment>();
+            assert_eq!(asset.transfer(manager, 10), Err(Error::SubscriptionExpired));
+
+            // a lapsed subscription restarts from the current block
+            assert_eq!(asset.pay_subscription(), Ok(()));
+            assert_eq!(asset.subscription_expiry(holder), 16);
+            assert_eq!(asset.transfer(manager, 10), Ok(()));
         }
 
-        /// Returns the asset name.
-        #[ink(message)]
-        pub fn asset_name(&self) -> String {
-            self.asset_name.clone()
+        // Test if the unit conversions use the decimals of the asset
+        #[allow(clippy::useless_conversion)]
+        #[ink::test]
+        fn unit_conversion_works() {
+            set_caller(account(0x1));
+            let asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                1000,
+                6,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(asset.decimals_factor(), Ok(1_000_000));
+            assert_eq!(asset.to_base_units(2, 500_000), Ok(2_500_000));
+            assert_eq!(asset.from_base_units(2_500_000), (2, 500_000));
+            assert_eq!(
+                asset.to_base_units(2, 1_000_000),
+                Err(Error::InvalidFractionalUnits)
+            );
+            assert_eq!(
+                asset.to_base_units(u128::MAX, 0),
+                Err(Error::UnitConversionOverflow)
+            );
+
+            // a factor that does not fit in a Balance fails, or makes everything a fraction
+            let too_many = Balance::MAX.ilog10() + 1;
+            assert_eq!(decimals_factor(too_many), Err(Error::InvalidDecimals));
+            assert_eq!(to_base_units(too_many, 0, 1), Err(Error::InvalidDecimals));
+            assert_eq!(
+                from_base_units(too_many, Balance::MAX),
+                (0, u128::from(Balance::MAX))
+            );
         }
 
-        /// Returns the asset unit name.
-        #[ink(message)]
-        pub fn unit_name(&self) -> String {
-            self.unit_name.clone()
+        proptest::proptest! {
+            // Test if base units round-trip through whole and fractional units
+            #[test]
+            fn base_units_round_trip(decimals in 0..=Balance::MAX.ilog10(), amount: Balance) {
+                let (whole, fractional) = from_base_units(decimals, amount);
+                proptest::prop_assert_eq!(to_base_units(decimals, whole, fractional), Ok(amount));
+            }
+
+            // Test if whole and fractional units round-trip through base units, when they fit
+            #[allow(clippy::useless_conversion)]
+            #[test]
+            fn whole_units_round_trip(
+                decimals in 0..=Balance::MAX.ilog10(),
+                whole: u128,
+                fractional: u128,
+            ) {
+                let factor = u128::from(decimals_factor(decimals).unwrap());
+                let fractional = fractional % factor;
+                match to_base_units(decimals, whole, fractional) {
+                    Ok(amount) => proptest::prop_assert_eq!(
+                        from_base_units(decimals, amount),
+                        (whole, fractional)
+                    ),
+                    Err(error) => {
+                        proptest::prop_assert_eq!(error, Error::UnitConversionOverflow);
+                        proptest::prop_assert!(
+                            whole > (u128::from(Balance::MAX) - fractional) / factor
+                        );
+                    }
+                }
+            }
         }
 
-        /// Returns the total supply of the asset.
-        #[ink(message)]
-        pub fn total(&self) -> Balance {
-            self.total
+        // Test if the top holders come by balance descending, ties broken by account
+        #[ink::test]
+        fn top_holders_works() {
+            let manager = account(0x1);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            let balances = [30, 50, 10, 50, 0, 70, 20, 40, 60];
+            for (i, &amount) in balances.iter().enumerate() {
+                let holder = account(0x10 + i as u8);
+                set_caller(holder);
+                assert_eq!(asset.opt_in(), Ok(()));
+                set_caller(manager);
+                if amount > 0 {
+                    assert_eq!(asset.transfer(holder, amount), Ok(()));
+                }
+            }
+            assert_eq!(asset.opted_in_count(), 10);
+
+            let top = asset.top_holders(4).unwrap();
+            assert_eq!(
+                top,
+                vec![
+                    (manager, 670),
+                    (account(0x15), 70),
+                    (account(0x18), 60),
+                    (account(0x11), 50),
+                ]
+            );
+            let all = asset.top_holders(100).unwrap();
+            assert_eq!(all.len(), 10);
+            assert!(all.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+            assert_eq!(all[3..5], [(account(0x11), 50), (account(0x13), 50)]);
+            assert_eq!(all[..4], top[..]);
+            assert_eq!(all.last(), Some(&(account(0x14), 0)));
+            assert_eq!(asset.top_holders(0), Ok(vec![]));
+            assert_eq!(asset.top_holders(101), Err(Error::RequestedRankingTooLarge));
         }
 
-        /// Returns the number of decimals used to display the asset.
-        #[ink(message)]
-        pub fn decimals(&self) -> u32 {
-            self.decimals
+        // Test if the build info round-trips, with or without a source commitment
+        #[ink::test]
+        fn build_info_works() {
+            let asset = new_asset(None, None, None, None);
+            assert_eq!(
+                asset.build_info(),
+                BuildInfo {
+                    crate_version: env!("CARGO_PKG_VERSION").into(),
+                    storage_version: STORAGE_VERSION,
+                    ink_major_version: 4,
+                    source_commitment: None,
+                }
+            );
+
+            let commitment = [0xab; 32];
+            let asset = Subsa::new_with_config(AssetConfig {
+                asset_name: "Test subsa".into(),
+                total: 1000,
+                source_commitment: Some(commitment),
+                ..Default::default()
+            })
+            .unwrap();
+            let info = asset.build_info();
+            assert_eq!(info.source_commitment, Some(commitment));
+            assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+
+            let events = ink::env::test::recorded_events()
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            let Some(Event::Creation(creation)) = events.last() else {
+                panic!("expected a Creation event");
+            };
+            assert_eq!(creation.build_info, info);
         }
 
-        /// Returns whether the asset is frozen by default.
-        #[ink(message)]
-        pub fn default_frozen(&self) -> bool {
-            self.default_frozen
+        // Test if the on-chain fingerprint matches the one computed off-chain and tells
+        // deployments by other creators apart
+        #[ink::test]
+        fn fingerprint_works() {
+            let asset = new_asset(None, None, None, None);
+            assert_eq!(
+                asset.fingerprint(),
+                compute_fingerprint(&asset.export_config())
+            );
+            assert_eq!(
+                new_asset(None, None, None, None).fingerprint(),
+                asset.fingerprint()
+            );
+
+            // the same params deployed by another account yield another fingerprint
+            set_caller(account(0x2));
+            let copy = Subsa::new_with_config(asset.clone_config(None, None)).unwrap();
+            assert_eq!(
+                copy.fingerprint(),
+                compute_fingerprint(&copy.export_config())
+            );
+            assert_ne!(copy.fingerprint(), asset.fingerprint());
         }
 
-        /// Returns the URL of the asset.
-        #[ink(message)]
-        pub fn url(&self) -> String {
-            self.url.clone()
+        proptest::proptest! {
+            // Test if the paged export matches storage after a random series of operations
+            #[test]
+            fn export_matches_storage(
+                ops in proptest::collection::vec((0..5u8, 0..6u8, 0..6u8, 0..200u16), 0..40),
+            ) {
+                ink::env::test::run_test::<ink::env::DefaultEnvironment, _>(|_| {
+                    let manager = account(0x1);
+                    let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+                    for (op, from, to, amount) in ops {
+                        let (from, to) = (account(from + 1), account(to + 1));
+                        set_caller(from);
+                        let _ = match op {
+                            0 => asset.opt_in(),
+                            1 => asset.transfer(to, Balance::from(amount)),
+                            2 => asset.opt_out(),
+                            3 => {
+                                set_caller(manager);
+                                asset.freeze(to, !asset.is_frozen(to).unwrap(), None)
+                            }
+                            _ => {
+                                set_caller(manager);
+                                asset.wipe(to)
+                            }
+                        };
+                        ink::env::test::advance_block::<Environment>();
+                    }
+
+                    let state = export_full_state(asset.export_config(), 2, |offset, limit| {
+                        asset.export_holders(offset, limit)
+                    });
+                    assert_eq!(state.config.opted_in_count, asset.opted_in_count());
+                    assert_eq!(state.config.lifetime_transfers, asset.lifetime_transfers());
+                    let mut exported = state.holders;
+                    exported.sort();
+                    let mut expected = (0x1..=0x6)
+                        .map(account)
+                        .filter(|&holder| asset.is_opted_in(holder).unwrap())
+                        .map(|holder| {
+                            let balance = asset.balance_of(holder).unwrap();
+                            (holder, balance, asset.is_frozen(holder).unwrap())
+                        })
+                        .collect::<Vec<_>>();
+                    expected.sort();
+                    assert_eq!(exported, expected);
+                    Ok(())
+                })
+                .unwrap();
+            }
         }
 
-        /// Returns the metadata hash of the asset.
-        #[ink(message)]
-        pub fn metadata_hash(&self) -> [u8; 4] {
-            self.metadata_hash
+        // Test if the supply audit trail records mints, burns and revokes in order
+        #[ink::test]
+        fn supply_audit_trail_works() {
+            let reserve = account(0x1);
+            let clawback = account(0x9);
+            let holder = account(0x2);
+            let other = account(0x3);
+            let mut asset = new_asset(None, None, None, Some(clawback));
+            for account in [holder, other] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            assert_eq!(asset.supply_event_count(), 0);
+
+            set_caller(reserve);
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+            ink::env::test::advance_block::<Environment>();
+            set_caller(holder);
+            assert_eq!(asset.transfer(reserve, 100), Ok(()));
+            // transfers between holders leave the circulating supply unchanged
+            assert_eq!(asset.transfer(other, 10), Ok(()));
+            set_caller(clawback);
+            assert_eq!(asset.revoke_asset(other, holder, 50), Ok(()));
+
+            assert_eq!(asset.supply_event_count(), 3);
+            assert_eq!(
+                asset.get_supply_event(0),
+                Some(SupplyEvent {
+                    block: 0,
+                    operation: SupplyOperation::Mint,
+                    amount: 300,
+                    total_after: 300,
+                })
+            );
+            assert_eq!(
+                asset.get_supply_event(1),
+                Some(SupplyEvent {
+                    block: 1,
+                    operation: SupplyOperation::Burn,
+                    amount: 100,
+                    total_after: 200,
+                })
+            );
+            assert_eq!(
+                asset.get_supply_event(2),
+                Some(SupplyEvent {
+                    block: 1,
+                    operation: SupplyOperation::Revoke,
+                    amount: 50,
+                    total_after: 200,
+                })
+            );
+            assert_eq!(asset.get_supply_event(3), None);
         }
 
-        /// Returns the asset ID.
-        /// Note: the asset ID is the address of the contract.
-        #[ink(message)]
-        pub fn asset_id(&self) -> AccountId {
-            self.env().account_id()
+        // Test if the admin log records the actions of the role addresses in order
+        #[ink::test]
+        fn admin_log_works() {
+            let manager = account(0x1);
+            let officer = account(0x2);
+            let holder = account(0x3);
+            set_caller(manager);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                default_frozen: true,
+                manager: Some(manager),
+                freeze: Some(manager),
+                clawback: Some(manager),
+                mutable_url: true,
+                ..Default::default()
+            })
+            .unwrap();
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            assert_eq!(asset.admin_action_count(), 0);
+
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            assert_eq!(asset.set_freeze(officer), Ok(()));
+            set_caller(officer);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            // failed actions leave no entry
+            assert_eq!(asset.suspend(), Err(Error::NotManagerId));
+            ink::env::test::set_block_timestamp::<Environment>(20);
+            set_caller(manager);
+            assert_eq!(asset.set_secondary_freeze(true), Ok(()));
+            assert_eq!(asset.suspend(), Ok(()));
+            assert_eq!(asset.activate(), Ok(()));
+            assert_eq!(asset.revoke_asset(manager, holder, 50), Ok(()));
+            assert_eq!(asset.set_url("https://example.com".into()), Ok(()));
+
+            let entry = |actor, kind, account, value, timestamp| AdminAction {
+                actor,
+                kind,
+                account,
+                value,
+                timestamp,
+            };
+            assert_eq!(asset.admin_action_count(), 7);
+            assert_eq!(
+                asset.admin_actions(0, 10),
+                vec![
+                    entry(
+                        manager,
+                        AdminActionKind::RoleChange(Role::Freeze),
+                        Some(officer),
+                        0,
+                        10
+                    ),
+                    entry(officer, AdminActionKind::Freeze, Some(holder), 0, 10),
+                    entry(manager, AdminActionKind::SecondaryFreeze, None, 0, 20),
+                    entry(
+                        manager,
+                        AdminActionKind::StatusChange(AssetStatus::Suspended),
+                        None,
+                        0,
+                        20
+                    ),
+                    entry(
+                        manager,
+                        AdminActionKind::StatusChange(AssetStatus::Active),
+                        None,
+                        0,
+                        20
+                    ),
+                    entry(manager, AdminActionKind::Clawback, Some(holder), 50, 20),
+                    entry(manager, AdminActionKind::UrlChange, None, 0, 20),
+                ]
+            );
+
+            // pages stop at the end of the log
+            assert_eq!(asset.admin_actions(5, 10).len(), 2);
+            assert_eq!(
+                asset.admin_actions(1, 1),
+                vec![entry(officer, AdminActionKind::Freeze, Some(holder), 0, 10)]
+            );
+            assert!(asset.admin_actions(7, 10).is_empty());
         }
 
-        // Returns the creator address.
-        #[ink(message)]
-        pub fn creator_id(&self) -> AccountId {
-            self.creator
+        // Test if format_amount keeps the trailing zeros of the fraction
+        #[ink::test]
+        fn format_amount_works() {
+            assert_eq!(format_amount(12_340_000, 6, "TSSA"), "12.340000 TSSA");
+            assert_eq!(format_amount(0, 6, "TSSA"), "0.000000 TSSA");
+            assert_eq!(format_amount(12_000_000, 6, "TSSA"), "12.000000 TSSA");
         }
 
-        /// Returns the manager address.
-        #[ink(message)]
-        pub fn manager_id(&self) -> AccountId {
-            self.manager_id
+        // Test if format_amount leaves out the decimal point with 0 decimals
+        #[ink::test]
+        fn format_amount_without_decimals_works() {
+            assert_eq!(format_amount(0, 0, "TSSA"), "0 TSSA");
+            assert_eq!(format_amount(1234, 0, "TSSA"), "1234 TSSA");
         }
 
-        /// Returns the reserve address.
-        #[ink(message)]
-        pub fn reserve_id(&self) -> AccountId {
-            self.reserve_id
+        // Test if format_amount pads amounts smaller than one whole unit
+        #[ink::test]
+        fn format_amount_below_one_unit_works() {
+            assert_eq!(format_amount(1, 6, "TSSA"), "0.000001 TSSA");
+            assert_eq!(format_amount(999_999, 6, "TSSA"), "0.999999 TSSA");
+            // every amount is below one unit when 10^decimals does not fit in a Balance
+            let too_many = Balance::MAX.ilog10() + 1;
+            assert_eq!(
+                format_amount(1, too_many, "TSSA"),
+                format!("0.{:0>width$} TSSA", 1, width = too_many as usize)
+            );
+        }
+
+        // Test if format_amount formats the maximum Balance exactly
+        #[ink::test]
+        fn format_amount_max_balance_works() {
+            let digits = Balance::MAX.to_string();
+            assert_eq!(
+                format_amount(Balance::MAX, 0, "TSSA"),
+                format!("{digits} TSSA")
+            );
+            let (whole, fractional) = digits.split_at(digits.len() - 6);
+            assert_eq!(
+                format_amount(Balance::MAX, 6, "TSSA"),
+                format!("{whole}.{fractional} TSSA")
+            );
+            let decimals = Balance::MAX.ilog10();
+            let (whole, fractional) = digits.split_at(1);
+            assert_eq!(
+                format_amount(Balance::MAX, decimals, "TSSA"),
+                format!("{whole}.{fractional} TSSA")
+            );
+        }
+
+        // Test if display_balance_of formats the balance of opted-in accounts
+        #[ink::test]
+        fn display_balance_of_works() {
+            set_caller(account(0x1));
+            let mut asset = Subsa::new(
+                "Test subsa".into(),
+                "TSSA".into(),
+                100_000_000,
+                6,
+                true,
+                "www.test.com".into(),
+                [0x0; 4],
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let holder = account(0x2);
+            assert_eq!(asset.display_balance_of(holder), None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(
+                asset.display_balance_of(holder),
+                Some("0.000000 TSSA".into())
+            );
+            set_caller(account(0x1));
+            assert_eq!(asset.transfer(holder, 12_340_000), Ok(()));
+            assert_eq!(
+                asset.display_balance_of(holder),
+                Some("12.340000 TSSA".into())
+            );
+        }
+
+        // Test if verify_balance compares the balance of opted-in accounts to the minimum
+        #[ink::test]
+        fn verify_balance_works() {
+            let holder = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(account(0x1));
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+            assert_eq!(asset.verify_balance(holder, 300), Ok(true));
+            assert_eq!(asset.verify_balance(holder, 301), Ok(false));
+            assert_eq!(asset.verify_balance(holder, 0), Ok(true));
+        }
+
+        // Test if verify_balance treats accounts that have not opted in as holding 0
+        #[ink::test]
+        fn verify_balance_without_opt_in_works() {
+            let asset = new_asset(None, None, None, None);
+            let stranger = account(0x5);
+            assert_eq!(asset.balance_of(stranger), Err(Error::NotOptedIn));
+            assert_eq!(asset.verify_balance(stranger, 0), Ok(true));
+            assert_eq!(asset.verify_balance(stranger, 1), Ok(false));
+        }
+
+        // Test if a strict_url asset accepts each allowed scheme
+        #[ink::test]
+        fn strict_url_accepts_allowed_schemes() {
+            set_caller(account(0x1));
+            for (url, scheme) in [
+                ("https://example.com/asset.json", Some("https")),
+                (
+                    "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+                    Some("ipfs"),
+                ),
+                (
+                    "ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U",
+                    Some("ar"),
+                ),
+                ("", None),
+            ] {
+                let asset = Subsa::new_with_config(AssetConfig {
+                    total: 1000,
+                    url: url.into(),
+                    strict_url: true,
+                    ..Default::default()
+                })
+                .unwrap();
+                assert!(asset.strict_url());
+                assert_eq!(asset.url_scheme().as_deref(), scheme);
+            }
+        }
+
+        // Test if a strict_url asset rejects other schemes, and URLs without one
+        #[ink::test]
+        fn strict_url_rejects_other_schemes() {
+            set_caller(account(0x1));
+            for url in [
+                "javascript:alert(1)",
+                "http://example.com",
+                "www.test.com",
+                "HTTPS://a",
+            ] {
+                let asset = Subsa::new_with_config(AssetConfig {
+                    total: 1000,
+                    url: url.into(),
+                    strict_url: true,
+                    ..Default::default()
+                });
+                assert_eq!(asset.err(), Some(Error::InvalidUrl));
+            }
+        }
+
+        // Test if an asset without strict_url accepts any URL
+        #[ink::test]
+        fn non_strict_url_accepts_any_url() {
+            set_caller(account(0x1));
+            let asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                url: "javascript:alert(1)".into(),
+                ..Default::default()
+            })
+            .unwrap();
+            assert!(!asset.strict_url());
+            assert_eq!(asset.url_scheme(), None);
+
+            let asset = new_asset(None, None, None, None);
+            assert_eq!(asset.url(), "www.test.com");
+            assert_eq!(asset.url_scheme(), None);
+        }
+
+        // Test if reveal_transfer sends the committed amount and refunds the excess
+        #[ink::test]
+        fn commit_reveal_transfer_works() {
+            let sender = account(0x1);
+            let receiver = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(receiver);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            set_caller(sender);
+            let salt = [0x7; 32];
+            let commitment = transfer_commitment(120, salt);
+            let events_before = ink::env::test::recorded_events().count();
+            let id = asset.commit_transfer(commitment, receiver, 5, 200).unwrap();
+            assert_eq!(asset.balance_of(sender), Ok(800));
+            assert_eq!(asset.committed_supply(), 200);
+            assert_eq!(
+                asset
+                    .committed_transfer(id)
+                    .map(|committed| committed.locked),
+                Some(200)
+            );
+
+            // only the sender can reveal, with the committed amount and salt
+            set_caller(receiver);
+            assert_eq!(
+                asset.reveal_transfer(id, 120, salt),
+                Err(Error::NotCommitmentSender)
+            );
+            set_caller(sender);
+            assert_eq!(
+                asset.reveal_transfer(id, 121, salt),
+                Err(Error::CommitmentMismatch)
+            );
+            assert_eq!(
+                asset.reveal_transfer(id, 120, [0x8; 32]),
+                Err(Error::CommitmentMismatch)
+            );
+            assert_eq!(asset.reveal_transfer(id, 120, salt), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(120));
+            assert_eq!(asset.balance_of(sender), Ok(880));
+            assert_eq!(asset.committed_supply(), 0);
+            assert_eq!(asset.committed_transfer(id), None);
+            assert_eq!(
+                asset.reveal_transfer(id, 120, salt),
+                Err(Error::CommittedTransferNotFound)
+            );
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                events[0],
+                Event::TransferCommitted(TransferCommitted { id: 0, commitment: c, .. })
+                    if c == commitment
+            ));
+            assert!(matches!(
+                events[1],
+                Event::TransferRevealed(TransferRevealed { id: 0, amount: 120 })
+            ));
+        }
+
+        // Test if a committed transfer cannot reveal more than it escrowed, nor after it expired
+        #[ink::test]
+        fn commit_reveal_transfer_limits_work() {
+            let sender = account(0x1);
+            let receiver = account(0x2);
+            let other = account(0x3);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(receiver);
+            assert_eq!(asset.opt_in(), Ok(()));
+
+            set_caller(sender);
+            let salt = [0x7; 32];
+            assert_eq!(
+                asset.commit_transfer(transfer_commitment(1, salt), receiver, 0, 100),
+                Err(Error::InvalidCommitmentExpiry)
+            );
+            assert_eq!(
+                asset.commit_transfer(transfer_commitment(1, salt), receiver, 5, 0),
+                Err(Error::ZeroAmount)
+            );
+            assert_eq!(
+                asset.commit_transfer(transfer_commitment(1, salt), receiver, 5, 1001),
+                Err(Error::NotEnoughBalance)
+            );
+            let id = asset
+                .commit_transfer(transfer_commitment(150, salt), receiver, 2, 100)
+                .unwrap();
+            assert_eq!(
+                asset.reveal_transfer(id, 150, salt),
+                Err(Error::CommitmentExceedsLock)
+            );
+
+            // anyone can cancel once it expired, refunding the sender
+            set_caller(other);
+            assert_eq!(
+                asset.cancel_committed_transfer(id),
+                Err(Error::CannotCancelCommitment)
+            );
+            ink::env::test::advance_block::<Environment>();
+            ink::env::test::advance_block::<Environment>();
+            set_caller(sender);
+            assert_eq!(
+                asset.reveal_transfer(id, 150, salt),
+                Err(Error::CommittedTransferExpired)
+            );
+            set_caller(other);
+            assert_eq!(asset.cancel_committed_transfer(id), Ok(()));
+            assert_eq!(asset.balance_of(sender), Ok(1000));
+            assert_eq!(asset.committed_supply(), 0);
+        }
+
+        // Test if only the verifier can attest the asset, not the manager
+        #[ink::test]
+        fn verification_is_verifier_only() {
+            let manager = account(0x1);
+            let verifier = account(0x7);
+            set_caller(manager);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                manager: Some(manager),
+                verifier: Some(verifier),
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(asset.verifier(), Some(verifier));
+            assert!(!asset.is_verified());
+            assert!(asset.verification_uri().is_empty());
+
+            assert_eq!(asset.set_verified(true), Err(Error::NotVerifier));
+            assert_eq!(
+                asset.set_verification_uri(b"https://registry.example/ssa".to_vec()),
+                Err(Error::NotVerifier)
+            );
+
+            set_caller(verifier);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.set_verified(true), Ok(()));
+            assert_eq!(
+                asset.set_verification_uri(b"https://registry.example/ssa".to_vec()),
+                Ok(())
+            );
+            assert!(asset.is_verified());
+            assert_eq!(
+                asset.verification_uri(),
+                b"https://registry.example/ssa".to_vec()
+            );
+            assert_eq!(
+                asset.set_verification_uri(vec![0; MAX_VERIFICATION_URI_SIZE + 1]),
+                Err(Error::VerificationUriTooLarge)
+            );
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                events[1],
+                Event::VerificationChanged(VerificationChanged { verified: true, .. })
+            ));
+
+            assert_eq!(asset.set_verified(false), Ok(()));
+            assert!(!asset.is_verified());
+        }
+
+        // Test if an asset without verifier cannot be attested
+        #[ink::test]
+        fn verification_without_verifier_is_disabled() {
+            let mut asset = new_asset(Some(account(0x1)), None, None, None);
+            assert_eq!(asset.verifier(), None);
+            set_caller(account(0x1));
+            assert_eq!(asset.set_verified(true), Err(Error::VerifierDisabled));
+            assert_eq!(
+                asset.set_verification_uri(b"ipfs://attestation".to_vec()),
+                Err(Error::VerifierDisabled)
+            );
+            assert!(!asset.is_verified());
         }
 
-        /// Returns the freeze address.
-        #[ink(message)]
-        pub fn freeze_id(&self) -> AccountId {
-            self.freeze_id
+        // Test if a freeze_until freeze stops applying once its time has passed
+        #[ink::test]
+        fn freeze_until_expires_automatically() {
+            let manager = account(0x1);
+            let freezer = account(0x7);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, Some(freezer), None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+
+            ink::env::test::set_block_timestamp::<Environment>(1_000);
+            assert_eq!(asset.freeze_until(holder, 2_000), Err(Error::NotFreezeId));
+            set_caller(freezer);
+            assert_eq!(
+                asset.freeze_until(holder, 1_000),
+                Err(Error::InvalidFreezeExpiry)
+            );
+            assert_eq!(asset.freeze_until(holder, 2_000), Ok(()));
+            assert_eq!(asset.freeze_expires_at(holder), Some(2_000));
+            assert_eq!(asset.is_frozen(holder), Ok(true));
+            assert_eq!(asset.freeze_until(holder, 3_000), Err(Error::AlreadyFrozen));
+
+            // the frozen holder can neither send nor receive
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 100), Err(Error::FrozenAccount));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 100), Err(Error::FrozenAccount));
+
+            // once the time has passed, the freeze no longer applies without any unfreeze
+            ink::env::test::set_block_timestamp::<Environment>(2_000);
+            assert_eq!(asset.is_frozen(holder), Ok(false));
+            assert_eq!(
+                asset.holding_status(holder),
+                HoldingStatus::OptedIn {
+                    balance: 300,
+                    frozen: false
+                }
+            );
+            // the stale record is only cleared by the next write touching the account
+            assert!(asset.holder(holder).frozen);
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 100), Ok(()));
+            assert!(!asset.holder(holder).frozen);
+            assert_eq!(asset.freeze_expires_at(holder), None);
+            assert_eq!(asset.balance_of(holder), Ok(200));
         }
 
-        /// Returns the clawback address.
-        #[ink(message)]
-        pub fn clawback_id(&self) -> AccountId {
-            self.clawback_id
+        // Test if a freeze without time limit blocks transfers until unfrozen
+        #[ink::test]
+        fn frozen_account_cannot_transfer() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, Some(manager), None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 100), Err(Error::FrozenAccount));
+            assert_eq!(
+                asset.send_pending(manager, 100, 10_000),
+                Err(Error::FrozenAccount)
+            );
+            set_caller(manager);
+            assert_eq!(asset.freeze(holder, false, None), Ok(()));
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 100), Ok(()));
         }
 
-        /// Returns the balance of `account`.
-        /// Note: if the account has not opted in to this asset, NotOptedIn is returned.
-        #[ink(message)]
-        pub fn balance_of(&self, account: AccountId) -> Result<Balance, Error> {
-            let opted_in = self.accounts_opted_in.get(&account).unwrap_or(false);
-            if !opted_in {
-                return Err(Error::NotOptedIn);
+        // Test if a secondary freeze stops holder transfers but not reserve distributions
+        #[ink::test]
+        fn secondary_freeze_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let other = account(0x3);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for account in [holder, other] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
             }
+            assert_eq!(asset.set_secondary_freeze(true), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+            assert_eq!(asset.set_secondary_freeze(true), Ok(()));
+            assert!(asset.secondary_transfers_frozen());
 
-            Ok(self.balances.get(&account).unwrap_or(0))
-        }
+            // the reserve still distributes, holders cannot transfer
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            assert_eq!(asset.transfer(other, 100), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(400));
+            set_caller(holder);
+            assert_eq!(asset.transfer(other, 10), Err(Error::SecondaryMarketFrozen));
+            assert_eq!(
+                asset.transfer(manager, 10),
+                Err(Error::SecondaryMarketFrozen)
+            );
 
-        /// Returns whether `account` is frozen.
-        #[ink(message)]
-        pub fn is_frozen(&self, account: AccountId) -> Result<bool, Error> {
-            Ok(self.frozen_holders.get(&account).unwrap_or(false))
+            set_caller(manager);
+            assert_eq!(asset.set_secondary_freeze(false), Ok(()));
+            set_caller(holder);
+            assert_eq!(asset.transfer(other, 10), Ok(()));
+            assert_eq!(asset.balance_of(other), Ok(110));
         }
 
-        /// Returns whether `account` has opted in to this asset.
-        #[ink(message)]
-        pub fn is_opted_in(&self, account: AccountId) -> Result<bool, Error> {
-            Ok(self.accounts_opted_in.get(&account).unwrap_or(false))
-        }
+        // Test if a freeze-exempt account transacts under freezes until it loses the exemption
+        #[ink::test]
+        fn freeze_exempt_account_transacts_while_frozen() {
+            let manager = account(0x1);
+            let pool = account(0x2);
+            let holder = account(0x3);
+            let mut asset = new_asset(Some(manager), None, Some(manager), Some(manager));
+            for account in [pool, holder] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            assert_eq!(asset.set_freeze_exempt(pool, true), Err(Error::NotFreezeId));
+            set_caller(manager);
+            assert_eq!(asset.transfer(pool, 300), Ok(()));
+            assert_eq!(asset.transfer(holder, 300), Ok(()));
+            assert_eq!(asset.set_freeze_exempt(pool, true), Ok(()));
+            assert!(asset.is_freeze_exempt(pool));
+            assert!(!asset.is_freeze_exempt(holder));
 
-        /// Returns wheter `creator's balance is equal to total supply.
-        /// Note: an asset can only be destroyed if the creator's balance is equal to the total supply.
-        #[ink(message)]
-        pub fn is_destroyable(&self) -> bool {
-            self.balances.get(&self.creator).unwrap_or(0) == self.total
-        }
+            // the pool sends and receives under a global and an account freeze
+            assert_eq!(asset.set_secondary_freeze(true), Ok(()));
+            assert_eq!(asset.freeze(pool, true, None), Ok(()));
+            assert_eq!(asset.is_frozen(pool), Ok(true));
+            set_caller(pool);
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            set_caller(holder);
+            assert_eq!(asset.transfer(pool, 50), Ok(()));
+            assert_eq!(
+                asset.transfer(manager, 10),
+                Err(Error::SecondaryMarketFrozen)
+            );
+            assert_eq!(asset.balance_of(pool), Ok(250));
 
-        /// Transfer `amount` of tokens from `sender` to `receiver`.
-        #[ink(message)]
-        pub fn transfer(&mut self, receiver: AccountId, amount: Balance) -> Result<(), Error> {
-            let sender = self.env().caller();
+            // clawback still applies to the pool
+            set_caller(manager);
+            assert_eq!(asset.revoke_asset(manager, pool, 50), Ok(()));
+            assert_eq!(asset.balance_of(pool), Ok(200));
 
-            // check if sender has enough balance
-            let sender_balance = self.balances.get(&sender).unwrap_or(0);
-            if sender_balance < amount {
-                return Err(Error::NotEnoughBalance);
-            }
+            // without the exemption, both freezes apply again
+            assert_eq!(asset.set_freeze_exempt(pool, false), Ok(()));
+            assert!(!asset.is_freeze_exempt(pool));
+            set_caller(pool);
+            assert_eq!(
+                asset.transfer(holder, 10),
+                Err(Error::SecondaryMarketFrozen)
+            );
+            set_caller(manager);
+            assert_eq!(asset.set_secondary_freeze(false), Ok(()));
+            set_caller(pool);
+            assert_eq!(asset.transfer(holder, 10), Err(Error::FrozenAccount));
+        }
 
-            // check if receiver has opted in
-            let receiver_opted_in = self.accounts_opted_in.get(&receiver).unwrap_or(false);
-            if !receiver_opted_in {
-                return Err(Error::NotOptedIn);
+        // Test if a compliance diff applies at once and applying it again changes nothing
+        #[ink::test]
+        fn compliance_diff_works() {
+            let manager = account(0x1);
+            let alice = account(0x2);
+            let bob = account(0x3);
+            let carol = account(0x4);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for account in [alice, bob, carol] {
+                set_caller(account);
+                assert_eq!(asset.opt_in(), Ok(()));
             }
-
-            // update sender and receiver balances
-            self.balances.insert(&sender, &(sender_balance - amount));
-            self.balances.insert(
-                &receiver,
-                &(self.balances.get(&receiver).unwrap_or(0) + amount),
+            set_caller(manager);
+            assert_eq!(asset.transfer(alice, 100), Ok(()));
+            assert_eq!(asset.transfer(carol, 100), Ok(()));
+            let diff = ComplianceDiff {
+                add_whitelist: vec![alice, bob, bob],
+                remove_whitelist: vec![carol],
+                add_blacklist: vec![carol],
+                remove_blacklist: vec![],
+            };
+            set_caller(alice);
+            assert_eq!(
+                asset.apply_compliance_diff(diff.clone()),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            assert_eq!(asset.apply_compliance_diff(diff.clone()), Ok(3));
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let Event::ComplianceDiffApplied(event) = decode_event(emitted_events.last().unwrap())
+            else {
+                panic!("expected a ComplianceDiffApplied event");
+            };
+            assert_eq!(
+                (
+                    event.added_whitelist,
+                    event.removed_whitelist,
+                    event.added_blacklist,
+                    event.removed_blacklist
+                ),
+                (2, 0, 1, 0)
             );
+            assert_eq!(asset.apply_compliance_diff(diff), Ok(0));
+            assert!(asset.is_whitelisted(bob));
+            assert!(asset.is_blacklisted(carol));
 
-            // emit transfer event
-            self.env().emit_event(Transfer {
-                sender,
-                receiver,
-                asset_id: self.asset_id(),
-                amount: Some(amount),
-            });
+            // only whitelisted receivers, and no blacklisted accounts, take part in transfers
+            set_caller(alice);
+            assert_eq!(asset.transfer(bob, 10), Ok(()));
+            assert_eq!(asset.transfer(carol, 10), Err(Error::Blacklisted));
+            set_caller(carol);
+            assert_eq!(asset.transfer(alice, 10), Err(Error::Blacklisted));
+            set_caller(bob);
+            assert_eq!(asset.transfer(alice, 5), Ok(()));
+            set_caller(manager);
+            let diff = ComplianceDiff {
+                remove_whitelist: vec![bob],
+                remove_blacklist: vec![carol],
+                ..Default::default()
+            };
+            assert_eq!(asset.apply_compliance_diff(diff), Ok(2));
+            set_caller(alice);
+            assert_eq!(asset.transfer(bob, 10), Err(Error::NotWhitelisted));
+            set_caller(carol);
+            assert_eq!(asset.transfer(alice, 10), Ok(()));
 
-            Ok(())
+            // oversized diffs are rejected
+            set_caller(manager);
+            let diff = ComplianceDiff {
+                add_blacklist: vec![bob; MAX_STATUS_BATCH + 1],
+                ..Default::default()
+            };
+            assert_eq!(
+                asset.apply_compliance_diff(diff),
+                Err(Error::TooManyAccounts)
+            );
         }
 
-        /// OptIn to receive an asset
-        #[ink(message)]
-        pub fn opt_in(&mut self) -> Result<(), Error> {
-            let caller = self.env().caller();
-
-            // check if caller has already opted in
-            let caller_opted_in = self.accounts_opted_in.get(&caller).unwrap_or(false);
-            if caller_opted_in {
-                return Err(Error::AlreadyOptedIn);
-            }
+        // Test if anyone can execute a scheduled transfer once its time has come
+        #[ink::test]
+        fn scheduled_transfer_execute_works() {
+            let sender = account(0x1);
+            let receiver = account(0x2);
+            let executor = account(0x3);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(receiver);
+            assert_eq!(asset.opt_in(), Ok(()));
 
-            // update caller's opt in status
-            self.accounts_opted_in.insert(&caller, &true);
+            ink::env::test::set_block_timestamp::<Environment>(1_000);
+            set_caller(sender);
+            assert_eq!(
+                asset.schedule_transfer(receiver, 100, 1_000),
+                Err(Error::InvalidScheduleTime)
+            );
+            let id = asset.schedule_transfer(receiver, 100, 5_000).unwrap();
+            assert_eq!(asset.balance_of(sender), Ok(900));
+            assert_eq!(asset.scheduled_supply(), 100);
+            assert_eq!(
+                asset
+                    .scheduled_transfer(id)
+                    .map(|scheduled| scheduled.execute_after),
+                Some(5_000)
+            );
 
-            // emit opt in event
-            self.env().emit_event(OptIn {
-                asset_id: self.asset_id(),
-                account: caller,
-            });
+            set_caller(executor);
+            assert_eq!(
+                asset.execute_scheduled(id),
+                Err(Error::ScheduledTransferNotDue)
+            );
+            ink::env::test::set_block_timestamp::<Environment>(5_000);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.execute_scheduled(id), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(100));
+            assert_eq!(asset.scheduled_supply(), 0);
+            assert_eq!(asset.scheduled_transfer(id), None);
+            assert_eq!(
+                asset.execute_scheduled(id),
+                Err(Error::ScheduledTransferNotFound)
+            );
 
-            Ok(())
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                events[0],
+                Event::ScheduledExecuted(ScheduledExecuted { amount: 100, executor: e, .. })
+                    if e == executor
+            ));
         }
 
-        /// OptOut of receiving an asset
-        #[ink(message)]
-        pub fn opt_out(&mut self) -> Result<(), Error> {
-            let caller = self.env().caller();
-
-            // check if caller has opted in
-            let caller_opted_in = self.accounts_opted_in.get(&caller).unwrap_or(false);
-            if !caller_opted_in {
-                return Err(Error::NotOptedIn);
-            }
+        // Test if a scheduled transfer to an unready receiver fails, and its sender can cancel it
+        #[ink::test]
+        fn scheduled_transfer_cancel_works() {
+            let sender = account(0x1);
+            let receiver = account(0x2);
+            let mut asset = new_asset(Some(sender), None, Some(sender), None);
 
-            // update caller's opt in status
-            self.accounts_opted_in.insert(&caller, &false);
+            set_caller(sender);
+            let id = asset.schedule_transfer(receiver, 100, 10).unwrap();
+            ink::env::test::set_block_timestamp::<Environment>(10);
+            assert_eq!(asset.execute_scheduled(id), Err(Error::NotOptedIn));
 
-            // emit opt out event
-            self.env().emit_event(OptOut {
-                asset_id: self.asset_id(),
-                account: caller,
-            });
+            set_caller(receiver);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(sender);
+            assert_eq!(asset.freeze(receiver, true, None), Ok(()));
+            assert_eq!(asset.execute_scheduled(id), Err(Error::FrozenAccount));
 
-            Ok(())
+            // only the sender can cancel it, refunding itself
+            set_caller(receiver);
+            assert_eq!(asset.cancel_scheduled(id), Err(Error::NotScheduleSender));
+            set_caller(sender);
+            assert_eq!(asset.cancel_scheduled(id), Ok(()));
+            assert_eq!(asset.balance_of(sender), Ok(1000));
+            assert_eq!(asset.scheduled_supply(), 0);
+            assert_eq!(
+                asset.cancel_scheduled(id),
+                Err(Error::ScheduledTransferNotFound)
+            );
         }
 
-        /// Freeze an account
-        #[ink(message)]
-        pub fn freeze(&mut self, account: AccountId, freeze: bool) -> Result<(), Error> {
-            let caller = self.env().caller();
+        // Test if the asset symbol is stored and emitted on creation
+        #[ink::test]
+        fn asset_symbol_works() {
+            let mut symbol = [0; 12];
+            symbol[..4].copy_from_slice(b"TSSA");
+            set_caller(account(0x1));
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                asset_name: "Test subsa".into(),
+                total: 1000,
+                manager: Some(account(0x1)),
+                asset_symbol: symbol,
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(asset.asset_symbol(), symbol);
 
-            // check if token can be frozen
-            if !self.default_frozen {
-                return Err(Error::NotFreezable);
-            }
+            let events = ink::env::test::recorded_events()
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events[0],
+                Event::Creation(Creation { asset_symbol, .. }) if asset_symbol == symbol
+            ));
 
-            // check if caller is the freeze address
-            if caller != self.freeze_id {
-                return Err(Error::NotFreezeId);
-            }
+            // only the manager can register the symbol
+            set_caller(account(0x2));
+            assert_eq!(
+                asset.register_symbol(account(0x9)),
+                Err(Error::NotManagerId)
+            );
 
-            // check if account is already frozen
-            let account_frozen = self.frozen_holders.get(&account).unwrap_or(false);
-            if account_frozen {
-                return Err(Error::AlreadyFrozen);
-            }
+            // an asset without symbol cannot be registered
+            let mut asset = new_asset(Some(account(0x1)), None, None, None);
+            assert_eq!(asset.asset_symbol(), [0; 12]);
+            set_caller(account(0x1));
+            assert_eq!(
+                asset.register_symbol(account(0x9)),
+                Err(Error::InvalidSymbol)
+            );
+        }
 
-            // update account's frozen status
-            self.frozen_holders.insert(&account, &freeze);
+        // Test if a stream accrues linearly, and its recipient withdraws in parts
+        #[ink::test]
+        fn stream_withdraw_works() {
+            let reserve = account(0x1);
+            let recipient = account(0x2);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(recipient);
+            assert_eq!(asset.opt_in(), Ok(()));
 
-            // emit freeze event
-            self.env().emit_event(Freeze {
-                asset_id: self.asset_id(),
-                account,
-                freeze,
-                freeze_id: self.freeze_id,
-            });
+            ink::env::test::set_block_timestamp::<Environment>(100);
+            assert_eq!(
+                asset.create_stream(recipient, 300, 200, 500),
+                Err(Error::NotReserveId)
+            );
+            set_caller(reserve);
+            assert_eq!(
+                asset.create_stream(recipient, 300, 500, 500),
+                Err(Error::InvalidStreamTime)
+            );
+            assert_eq!(
+                asset.create_stream(account(0x3), 300, 200, 500),
+                Err(Error::NotOptedIn)
+            );
+            let id = asset.create_stream(recipient, 300, 200, 500).unwrap();
+            assert_eq!(asset.balance_of(reserve), Ok(700));
+            assert_eq!(asset.streamed_supply(), 300);
+            assert_eq!(asset.balance_in_stream(id, recipient), 0);
+            assert_eq!(asset.balance_in_stream(id, reserve), 300);
 
-            Ok(())
+            // a third of the stream accrued
+            ink::env::test::set_block_timestamp::<Environment>(300);
+            assert_eq!(asset.balance_in_stream(id, recipient), 100);
+            assert_eq!(asset.balance_in_stream(id, reserve), 200);
+            assert_eq!(asset.balance_in_stream(id, account(0x3)), 0);
+            assert_eq!(
+                asset.withdraw_from_stream(id, 40),
+                Err(Error::NotStreamRecipient)
+            );
+            set_caller(recipient);
+            assert_eq!(
+                asset.withdraw_from_stream(id, 101),
+                Err(Error::StreamBalanceExceeded)
+            );
+            assert_eq!(asset.withdraw_from_stream(id, 40), Ok(()));
+            assert_eq!(asset.balance_of(recipient), Ok(40));
+            assert_eq!(asset.balance_in_stream(id, recipient), 60);
+
+            // once fully accrued and withdrawn, the stream is removed
+            ink::env::test::set_block_timestamp::<Environment>(600);
+            assert_eq!(asset.balance_in_stream(id, recipient), 260);
+            assert_eq!(asset.withdraw_from_stream(id, 260), Ok(()));
+            assert_eq!(asset.balance_of(recipient), Ok(300));
+            assert_eq!(asset.streamed_supply(), 0);
+            assert_eq!(asset.stream(id), None);
+            assert_eq!(
+                asset.withdraw_from_stream(id, 1),
+                Err(Error::StreamNotFound)
+            );
         }
 
-        /// Modify/Reconfigure an asset
-        // Note: only the manager can modify an asset
-        // Note: only mutable asset params can be modified
-        // List of mutable asset params:
-        // - managerId, reserveId, freezeId, clawbackId
-        #[ink(message)]
-        pub fn modify_asset(
-            &mut self,
-            manager: Option<AccountId>,
-            reserve: Option<AccountId>,
-            freeze: Option<AccountId>,
-            clawback: Option<AccountId>,
-        ) -> Result<(), Error> {
-            let caller = self.env().caller();
+        // Test if cancelling a stream mid-way splits it, and frozen recipients cannot withdraw
+        #[ink::test]
+        fn stream_cancel_works() {
+            let reserve = account(0x1);
+            let recipient = account(0x2);
+            let mut asset = new_asset(None, None, Some(reserve), None);
+            set_caller(recipient);
+            assert_eq!(asset.opt_in(), Ok(()));
 
-            // check if caller is the manager
-            if caller != self.manager_id {
-                return Err(Error::NotManagerId);
-            }
+            set_caller(reserve);
+            let id = asset.create_stream(recipient, 1000, 0, 400).unwrap();
+            ink::env::test::set_block_timestamp::<Environment>(100);
+            assert_eq!(asset.freeze(recipient, true, None), Ok(()));
 
-            // update asset params
-            self.manager_id = manager.unwrap_or_else(|| AccountId::from([0x0; 32]));
-            self.reserve_id = reserve.unwrap_or_else(|| AccountId::from([0x0; 32]));
-            self.freeze_id = freeze.unwrap_or_else(|| AccountId::from([0x0; 32]));
-            self.clawback_id = clawback.unwrap_or_else(|| AccountId::from([0x0; 32]));
+            // a frozen recipient keeps accruing, but cannot withdraw
+            ink::env::test::set_block_timestamp::<Environment>(300);
+            assert_eq!(asset.balance_in_stream(id, recipient), 750);
+            set_caller(recipient);
+            assert_eq!(asset.withdraw_from_stream(id, 1), Err(Error::FrozenAccount));
 
-            // emit modify asset event
-            self.env().emit_event(Modify {
-                manager_id: self.manager_id,
-                reserve_id: self.reserve_id,
-                freeze_id: self.freeze_id,
-                clawback_id: self.clawback_id,
-            });
+            set_caller(account(0x3));
+            assert_eq!(asset.cancel_stream(id), Err(Error::NotStreamParty));
+            set_caller(reserve);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.cancel_stream(id), Ok(()));
+            assert_eq!(asset.balance_of(recipient), Ok(750));
+            assert_eq!(asset.balance_of(reserve), Ok(250));
+            assert_eq!(asset.streamed_supply(), 0);
+            assert_eq!(asset.stream(id), None);
 
-            Ok(())
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last(),
+                Some(Event::StreamCancelled(StreamCancelled {
+                    recipient_amount: 750,
+                    reserve_amount: 250,
+                    ..
+                }))
+            ));
         }
 
-        /// Revoke an asset
-        // Note: only the clawback address can revoke an asset
-        // Note: must specify amount, revocation target id, and receiver
-        #[ink(message)]
-        pub fn revoke_asset(
-            &mut self,
-            receiver: AccountId,
-            recovation_target: AccountId,
-            amount: Balance,
-        ) -> Result<(), Error> {
-            let caller = self.env().caller();
+        // Test if an NFT is only transferred whole, emitting NFTTransfer instead of Transfer
+        #[ink::test]
+        fn nft_transfer_works() {
+            let creator = account(0x1);
+            let receiver = account(0x2);
+            set_caller(creator);
+            let mut asset = Subsa::new_nft(
+                "Test NFT".into(),
+                "TNFT".into(),
+                "ipfs://test".into(),
+                [0x1; 4],
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            set_caller(receiver);
+            assert_eq!(asset.opt_in(), Ok(()));
 
-            // check if caller is the clawback address
-            if caller != self.clawback_id {
-                return Err(Error::NotClawbackId);
-            }
+            set_caller(creator);
+            assert_eq!(
+                asset.transfer(receiver, 2),
+                Err(Error::NFTPartialTransferNotAllowed)
+            );
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.transfer(receiver, 1), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(1));
 
-            // check if receiver has opted in
-            let receiver_opted_in = self.accounts_opted_in.get(&receiver).unwrap_or(false);
-            if !receiver_opted_in {
-                return Err(Error::NotOptedIn);
-            }
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                events[0],
+                Event::NFTTransfer(NFTTransfer { from, to, .. }) if from == creator && to == receiver
+            ));
 
-            // check if recovation target account has enough balance
-            let receiver_balance = self.balances.get(&receiver).unwrap_or(0);
-            if receiver_balance < amount {
-                return Err(Error::NotEnoughBalance);
+            // a regular asset allows any amount, and emits Transfer
+            let receiver = account(0x3);
+            let mut asset = new_asset(None, None, None, None);
+            set_caller(receiver);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(creator);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.transfer(receiver, 2), Ok(()));
+            assert_eq!(asset.transfer(receiver, 0), Ok(()));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(events
+                .iter()
+                .all(|event| matches!(event, Event::Transfer(_))));
+        }
+
+        // Test if revoke_many applies every revocation, or none when one fails
+        #[ink::test]
+        fn revoke_many_works() {
+            let reserve = account(0x1);
+            let clawback = account(0x9);
+            let holders = [account(0x2), account(0x3), account(0x4)];
+            let mut asset = new_asset(None, None, None, Some(clawback));
+            for holder in holders {
+                set_caller(holder);
+                assert_eq!(asset.opt_in(), Ok(()));
+                set_caller(reserve);
+                assert_eq!(asset.transfer(holder, 100), Ok(()));
             }
 
-            // update recovation target balance
-            self.balances
-                .insert(&recovation_target, &(receiver_balance - amount));
+            assert_eq!(
+                asset.revoke_many(vec![(holders[0], None, reserve)]),
+                Err(Error::NotClawbackId)
+            );
+            set_caller(clawback);
+            assert_eq!(
+                asset.revoke_many(vec![(holders[0], Some(0), reserve); MAX_REVOKE_BATCH + 1]),
+                Err(Error::TooManyRevocations)
+            );
 
-            // update receiver balance
-            self.balances
-                .insert(&receiver, &(receiver_balance + amount));
+            // the last entry exceeds what is left of the first holder, so nothing applies
+            assert_eq!(
+                asset.revoke_many(vec![
+                    (holders[0], Some(60), reserve),
+                    (holders[1], None, reserve),
+                    (holders[0], Some(50), reserve),
+                ]),
+                Err(Error::NotEnoughBalance)
+            );
+            for holder in holders {
+                assert_eq!(asset.balance_of(holder), Ok(100));
+            }
+            assert_eq!(
+                asset.revoke_many(vec![(holders[0], None, account(0x5))]),
+                Err(Error::NotOptedIn)
+            );
 
-            // emit revoke asset event
-            self.env().emit_event(Revoke {
-                asset_id: self.asset_id(),
-                from: receiver,
-                amount: Some(amount),
-                clawback_id: self.clawback_id,
-            });
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(
+                asset.revoke_many(vec![
+                    (holders[0], Some(60), reserve),
+                    (holders[1], None, holders[2]),
+                    (holders[0], None, reserve),
+                ]),
+                Ok(())
+            );
+            assert_eq!(asset.balance_of(holders[0]), Ok(0));
+            assert_eq!(asset.balance_of(holders[1]), Ok(0));
+            assert_eq!(asset.balance_of(holders[2]), Ok(200));
+            assert_eq!(asset.balance_of(reserve), Ok(800));
 
-            Ok(())
+            let revoked = ink::env::test::recorded_events()
+                .skip(events_before)
+                .filter_map(|event| match decode_event(&event) {
+                    Event::Revoke(revoke) => Some(revoke.amount),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(revoked, vec![60, 100, 40]);
         }
 
-        /// Destroy an asset
-        // Note: only the manager can destroy an asset
-        // Note: all asset holdings are transferred to the manager
-        #[ink(message)]
-        pub fn destroy_asset(&mut self) -> Result<(), Error> {
-            let caller = self.env().caller();
+        // Test if voting_power applies each voting formula, which only the manager can set
+        #[ink::test]
+        fn voting_power_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            set_caller(manager);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                asset_name: "Test subsa".into(),
+                total: 10_000,
+                decimals: 2,
+                manager: Some(manager),
+                ..Default::default()
+            })
+            .unwrap();
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            set_caller(manager);
+            assert_eq!(asset.transfer(holder, 400), Ok(()));
 
-            // check if caller is the manager
-            if caller != self.manager_id {
-                return Err(Error::NotManagerId);
+            assert_eq!(asset.voting_formula(), VotingFormula::Linear);
+            assert_eq!(asset.voting_power(holder), 400);
+            assert_eq!(asset.voting_power(account(0x3)), 0);
+
+            // 400 base units are 4 units, weighing 16 units
+            assert_eq!(asset.set_voting_formula(VotingFormula::Quadratic), Ok(()));
+            assert_eq!(asset.voting_power(holder), 1_600);
+            assert_eq!(asset.set_voting_formula(VotingFormula::SquareRoot), Ok(()));
+            assert_eq!(asset.voting_power(holder), 20);
+            assert_eq!(asset.voting_power(manager), 97);
+
+            set_caller(holder);
+            assert_eq!(
+                asset.set_voting_formula(VotingFormula::Linear),
+                Err(Error::NotManagerId)
+            );
+        }
+
+        // Test if integer_sqrt rounds down, up to the largest balance
+        #[ink::test]
+        fn integer_sqrt_works() {
+            for (value, root) in [(0, 0), (1, 1), (2, 1), (3, 1), (4, 2), (99, 9), (100, 10)] {
+                assert_eq!(integer_sqrt(value), root);
             }
+            let root = integer_sqrt(Balance::MAX);
+            assert!(root.checked_mul(root).is_some());
+            assert!((root + 1).checked_mul(root + 1).is_none());
+        }
 
-            // check if manager balance is equal to total supply
-            let manager_balance = self.balances.get(&self.manager_id).unwrap_or(0);
-            if manager_balance != self.total {
-                return Err(Error::NotAllAssetsOwnedByManager);
+        // Test if statuses_of reports every account in order, including unknown ones
+        #[ink::test]
+        fn statuses_of_works() {
+            let manager = account(0x1);
+            let mut asset = new_asset(Some(manager), None, Some(manager), None);
+            let accounts: Vec<AccountId> = (0x2..0x6).map(account).collect();
+            for &holder in &accounts[1..] {
+                set_caller(holder);
+                assert_eq!(asset.opt_in(), Ok(()));
             }
+            set_caller(manager);
+            assert_eq!(asset.transfer(accounts[1], 10), Ok(()));
+            assert_eq!(asset.freeze(accounts[2], true, None), Ok(()));
 
-            // emit destroy asset event
-            self.env().emit_event(Destruction {
-                asset_id: self.asset_id(),
-                destroyer: self.manager_id,
-            });
+            assert_eq!(
+                asset.statuses_of(accounts.clone()),
+                Ok(vec![
+                    HoldingStatus::NotOptedIn,
+                    HoldingStatus::OptedIn {
+                        balance: 10,
+                        frozen: false,
+                    },
+                    HoldingStatus::OptedIn {
+                        balance: 0,
+                        frozen: true,
+                    },
+                    HoldingStatus::OptedIn {
+                        balance: 0,
+                        frozen: false,
+                    },
+                ])
+            );
+            assert_eq!(
+                asset.is_frozen_batch(accounts),
+                vec![false, false, true, false]
+            );
 
-            // terminate contract
-            self.env().terminate_contract(self.manager_id);
+            assert_eq!(asset.statuses_of(Vec::new()), Ok(Vec::new()));
+            assert_eq!(
+                asset.statuses_of(vec![manager; MAX_STATUS_BATCH + 1]),
+                Err(Error::TooManyAccounts)
+            );
         }
-    }
 
-    /// Unit tests
+        // Test if the manager can only purge empty, unfrozen accounts inactive for long enough
+        #[ink::test]
+        fn purge_stale_account_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, Some(manager), None);
+            ink::env::test::set_block_timestamp::<Environment>(1_000);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            let opted_in_count = asset.opted_in_count();
+
+            // purging is disabled until the manager sets a threshold
+            ink::env::test::set_block_timestamp::<Environment>(100_000);
+            set_caller(manager);
+            assert_eq!(
+                asset.purge_stale_account(holder),
+                Err(Error::AccountStillActive)
+            );
+            set_caller(holder);
+            assert_eq!(
+                asset.set_inactivity_threshold(10_000),
+                Err(Error::NotManagerId)
+            );
+            assert_eq!(asset.purge_stale_account(holder), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(asset.set_inactivity_threshold(10_000), Ok(()));
+            assert_eq!(asset.inactivity_threshold(), 10_000);
 
-    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
-    /// module and test functions are marked with a `#[test]` attribute.
-    /// The below code is technically just normal Rust code.
-    #[cfg(test)]
-    mod tests {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
+            // a recent activity, a balance or a freeze keeps the account
+            assert_eq!(asset.transfer(holder, 10), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(200_000);
+            assert_eq!(
+                asset.purge_stale_account(holder),
+                Err(Error::AccountStillActive)
+            );
+            set_caller(holder);
+            assert_eq!(asset.transfer(manager, 10), Ok(()));
+            set_caller(manager);
+            assert_eq!(
+                asset.purge_stale_account(holder),
+                Err(Error::AccountStillActive)
+            );
+            ink::env::test::set_block_timestamp::<Environment>(300_000);
+            assert_eq!(asset.freeze(holder, true, None), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(400_000);
+            assert_eq!(
+                asset.purge_stale_account(holder),
+                Err(Error::AccountStillActive)
+            );
+            assert_eq!(asset.freeze(holder, false, None), Ok(()));
+            ink::env::test::set_block_timestamp::<Environment>(500_000);
 
-        /// Imports `ink_lang` so we can use `#[ink::test]`.
-        use ink;
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.purge_stale_account(holder), Ok(()));
+            assert_eq!(asset.is_opted_in(holder), Ok(false));
+            assert_eq!(asset.opted_in_count(), opted_in_count - 1);
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events[0],
+                Event::AccountPurged(AccountPurged { account, manager_id })
+                    if account == holder && manager_id == manager
+            ));
+            assert_eq!(asset.purge_stale_account(holder), Err(Error::NotOptedIn));
+        }
 
-        /// Test constructor and initial state
+        // Test if native_accounting splits the contract balance, and only collected opt-in fees
+        // can be withdrawn
+        // Note: the off-chain value transfer API needs u128 balances
+        #[cfg(not(feature = "custom-env"))]
         #[ink::test]
-        fn constructor_works() {
-            let asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                Some(AccountId::from([0x0; 32])),
-                Some(AccountId::from([0x0; 32])),
-                Some(AccountId::from([0x0; 32])),
-                Some(AccountId::from([0x0; 32])),
+        fn native_accounting_works() {
+            let manager = account(0x1);
+            let holder = account(0x2);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            let contract = asset.asset_id();
+            set_caller(manager);
+            assert_eq!(asset.set_opt_in_fee(10), Ok(()));
+            set_caller(holder);
+            ink::env::test::set_value_transferred::<Environment>(10);
+            assert_eq!(asset.opt_in(), Ok(()));
+            ink::env::test::set_value_transferred::<Environment>(0);
+
+            // the off-chain environment does not credit the paid value to the contract
+            ink::env::test::set_account_balance::<Environment>(contract, 100);
+            assert_eq!(
+                asset.native_accounting(),
+                NativeAccounting {
+                    contract_balance: 100,
+                    opt_in_fees: 10,
+                    unattributed: 90,
+                    overdrawn: false,
+                }
             );
 
-            assert_eq!(asset.asset_name(), "Test subsa");
-            assert_eq!(asset.unit_name(), "TSSA");
-            assert_eq!(asset.total(), 1000);
-            assert_eq!(asset.decimals(), 10);
-            assert_eq!(asset.default_frozen(), true);
-            assert_eq!(asset.url(), "www.test.com");
-            assert_eq!(asset.manager_id(), AccountId::from([0x0; 32]));
-            assert_eq!(asset.reserve_id(), AccountId::from([0x0; 32]));
-            assert_eq!(asset.freeze_id(), AccountId::from([0x0; 32]));
-            assert_eq!(asset.clawback_id(), AccountId::from([0x0; 32]));
+            assert_eq!(
+                asset.withdraw_opt_in_fees(holder, 4),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            assert_eq!(
+                asset.withdraw_opt_in_fees(manager, 11),
+                Err(Error::InsufficientCollectedFees)
+            );
+            // Note: the manager is the contract itself off-chain, so withdraw elsewhere
+            assert_eq!(asset.withdraw_opt_in_fees(account(0x3), 4), Ok(()));
+            assert_eq!(asset.native_accounting().opt_in_fees, 6);
+            assert_eq!(asset.native_accounting().contract_balance, 96);
+
+            // a balance below the buckets saturates, and is flagged
+            ink::env::test::set_account_balance::<Environment>(contract, 3);
+            let accounting = asset.native_accounting();
+            assert_eq!(accounting.unattributed, 0);
+            assert!(accounting.overdrawn);
+            assert_eq!(accounting.opt_in_fees, 6);
         }
 
-        // Test if constructor works with default values
+        // Test if transfers pay each royalty recipient its share, net of the transfer fee
         #[ink::test]
-        fn constructor_works_with_default_values() {
-            let asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
+        fn royalty_splits_work() {
+            let manager = account(0x1);
+            let (sender, receiver) = (account(0x2), account(0x3));
+            let (author, label) = (account(0x4), account(0x5));
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for holder in [sender, receiver, author] {
+                set_caller(holder);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            let splits = vec![
+                RoyaltySplit {
+                    recipient: author,
+                    share_bps: 1_000,
+                },
+                RoyaltySplit {
+                    recipient: label,
+                    share_bps: 500,
+                },
+            ];
+
+            set_caller(manager);
+            assert_eq!(
+                asset.set_royalty_splits(splits.clone()),
+                Err(Error::NotOptedIn)
+            );
+            set_caller(label);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(
+                asset.set_royalty_splits(splits.clone()),
+                Err(Error::NotManagerId)
+            );
+            set_caller(manager);
+            let too_large = vec![RoyaltySplit {
+                recipient: author,
+                share_bps: 10_001,
+            }];
+            assert_eq!(
+                asset.set_royalty_splits(too_large),
+                Err(Error::RoyaltySharesMismatch)
+            );
+            assert_eq!(
+                asset.set_royalty_splits(vec![splits[0]; MAX_ROYALTY_SPLITS + 1]),
+                Err(Error::TooManyRoyaltySplits)
             );
+            assert_eq!(asset.set_royalty_splits(splits.clone()), Ok(()));
+            assert_eq!(asset.royalty_splits(), splits);
 
-            assert_eq!(asset.asset_name(), "Test subsa");
-            assert_eq!(asset.unit_name(), "TSSA");
-            assert_eq!(asset.total(), 1000);
-            assert_eq!(asset.decimals(), 10);
-            assert_eq!(asset.default_frozen(), true);
-            assert_eq!(asset.url(), "www.test.com");
-            assert_eq!(asset.manager_id(), AccountId::from([0x0; 32]));
-            assert_eq!(asset.reserve_id(), AccountId::from([0x1; 32]));
-            assert_eq!(asset.freeze_id(), AccountId::from([0x0; 32]));
-            assert_eq!(asset.clawback_id(), AccountId::from([0x0; 32]));
-            assert_eq!(asset.balances.get(&asset.reserve_id()).unwrap_or(0), 1000);
+            // the reserve pays no royalties
+            assert_eq!(asset.transfer(sender, 500), Ok(()));
+            assert_eq!(asset.balance_of(sender), Ok(500));
+
+            // 10% and 5% of the 180 left once the reserve takes its 10% fee
+            assert_eq!(asset.set_transfer_fee(1_000), Ok(()));
+            set_caller(sender);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.transfer(receiver, 200), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(153));
+            assert_eq!(asset.balance_of(author), Ok(18));
+            assert_eq!(asset.balance_of(label), Ok(9));
+            assert_eq!(asset.balance_of(manager), Ok(520));
+
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last(),
+                Some(Event::RoyaltySplitPaid(RoyaltySplitPaid {
+                    splits_count: 2,
+                    total_royalty: 27,
+                    ..
+                }))
+            ));
         }
 
-        // Test if asset_id field is set correctly in constructor to the contract address
+        // Test if native value sent to the contract unsolicited shows up as unattributed, and
+        // only the manager can return it, without touching the collected opt-in fees
+        // Note: the off-chain value transfer API needs u128 balances
+        #[cfg(not(feature = "custom-env"))]
         #[ink::test]
-        fn constructor_sets_asset_id() {
-            use ink::codegen::Env;
-            let asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
+        fn recover_native_works() {
+            let manager = account(0x1);
+            let (holder, sender) = (account(0x2), account(0x3));
+            let mut asset = new_asset(Some(manager), None, None, None);
+            let contract = asset.asset_id();
+            set_caller(manager);
+            assert_eq!(asset.set_opt_in_fee(10), Ok(()));
+            set_caller(holder);
+            ink::env::test::set_value_transferred::<Environment>(10);
+            assert_eq!(asset.opt_in(), Ok(()));
+            ink::env::test::set_value_transferred::<Environment>(0);
+
+            // a bare transfer of 50 by sender, on top of the collected fee
+            // Note: the off-chain environment does not credit transferred value to the contract
+            ink::env::test::set_account_balance::<Environment>(contract, 60);
+            assert_eq!(asset.native_accounting().unattributed, 50);
+
+            set_caller(sender);
+            assert_eq!(asset.recover_native(sender, 50), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(
+                asset.recover_native(sender, 51),
+                Err(Error::InsufficientUnattributedBalance)
             );
-            assert_eq!(asset.asset_id(), asset.env().account_id());
+            assert_eq!(asset.recover_native(sender, 50), Ok(()));
+            assert_eq!(
+                asset.native_accounting(),
+                NativeAccounting {
+                    contract_balance: 10,
+                    opt_in_fees: 10,
+                    unattributed: 0,
+                    overdrawn: false,
+                }
+            );
+
+            let events = ink::env::test::recorded_events()
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last(),
+                Some(Event::NativeRecovered(NativeRecovered { amount: 50, .. }))
+            ));
         }
 
-        // check if Create event is emitted in constructor
+        // Test if clone_asset deploys with the parameters of the asset, and the overrides
+        // Note: the off-chain environment cannot instantiate contracts, so check the config
         #[ink::test]
-        fn constructor_emits_create_event() {
-            let asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
-            );
-            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(events.len(), 1);
-            let event = &events[0];
-            assert_eq!(event.topics.len(), 5);
-            // TODO TEST EVENT WITH Event as scale:Decode
+        fn clone_asset_works() {
+            let (manager, freeze) = (account(0x1), account(0x3));
+            let asset = new_asset(Some(manager), None, Some(freeze), None);
+
+            let config = asset.clone_config(None, None);
+            assert_eq!(config.asset_name, asset.asset_name());
+            assert_eq!(config.unit_name, asset.unit_name());
+            assert_eq!(config.total, 1000);
+            assert_eq!(config.decimals, 10);
+            assert!(config.default_frozen);
+            assert_eq!(config.url, asset.url());
+            assert_eq!(config.metadata_hash, asset.metadata_hash());
+            assert_eq!(config.manager, Some(manager));
+            assert_eq!(config.reserve, Some(asset.reserve_id()));
+            assert_eq!(config.freeze, Some(freeze));
+            assert_eq!(config.asset_symbol, [0; 12]);
+
+            let tranche = account(0x5);
+            let config = asset.clone_config(Some(500), Some(tranche));
+            assert_eq!(config.total, 500);
+            assert_eq!(config.manager, Some(tranche));
+            assert_eq!(config.decimals, 10);
+
+            let mut asset = asset;
+            set_caller(account(0x2));
+            assert_eq!(asset.clone_asset(None, None), Err(Error::NotManagerId));
         }
 
-        // Test if optIn works
+        // Test if only the manager can recover tokens, and never this asset itself
+        // Note: the off-chain environment cannot call the foreign token
         #[ink::test]
-        fn opt_in_works() {
-            // set caller
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x0; 32]));
-            let mut asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
-            );
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x1; 32]));
-            asset.opt_in();
-            // check if caller account is opted in in accounts_opted_in map
+        fn recover_token_checks_work() {
+            let manager = account(0x1);
+            let (token, to) = (account(0x4), account(0x5));
+            let mut asset = new_asset(Some(manager), None, None, None);
+            let own_token = asset.asset_id();
+
+            set_caller(account(0x2));
+            assert_eq!(asset.recover_token(token, to, 10), Err(Error::NotManagerId));
+            set_caller(manager);
             assert_eq!(
-                asset.accounts_opted_in.get(&AccountId::from([0x1; 32])),
-                Some(true)
+                asset.recover_token(own_token, to, 10),
+                Err(Error::CannotRecoverOwnToken)
             );
         }
 
-        // Test if optIn emits OptIn event
+        // Test if only the manager can set the fee token, which fee-free transfers never call
+        // Note: the off-chain environment cannot call the fee token
         #[ink::test]
-        fn opt_in_emits_opt_in_event() {
-            // set caller
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x0; 32]));
-            let mut asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
-            );
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x1; 32]));
-            asset.opt_in();
-            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(events.len(), 2);
-            let event = &events[1];
-            assert_eq!(event.topics.len(), 3);
-            // TODO TEST EVENT WITH Event as scale:Decode
+        fn set_fee_token_works() {
+            let manager = account(0x1);
+            let (token, holder) = (account(0x4), account(0x5));
+            let mut asset = new_asset(Some(manager), None, None, None);
+            set_caller(holder);
+            assert_eq!(asset.opt_in(), Ok(()));
+            assert_eq!(asset.set_fee_token(Some(token)), Err(Error::NotManagerId));
+            set_caller(manager);
+            assert_eq!(asset.set_transfer_fee(250), Ok(()));
+            assert_eq!(asset.set_fee_token(Some(token)), Ok(()));
+            assert_eq!(asset.fee_token(), Some(token));
+            let event = ink::env::test::recorded_events().last().unwrap();
+            let Event::FeeTokenSet(event) = decode_event(&event) else {
+                panic!("expected a FeeTokenSet event");
+            };
+            assert_eq!(event.token, Some(token));
+
+            // the reserve pays no fee, so its transfers need no fee token call
+            assert_eq!(asset.transfer(holder, 100), Ok(()));
+            assert_eq!(asset.balance_of(holder), Ok(100));
+
+            assert_eq!(asset.set_fee_token(None), Ok(()));
+            assert_eq!(asset.fee_token(), None);
         }
 
+        // Test if transfers between two jurisdictions pay their tax rate to the reserve
         #[ink::test]
-        fn opt_in_throws_already_opt_in_if_already_opt_in() {
-            // set caller
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x0; 32]));
-            let mut asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
+        fn transfer_tax_works() {
+            let manager = account(0x1);
+            let (sender, receiver, other) = (account(0x2), account(0x3), account(0x4));
+            let mut asset = new_asset(Some(manager), None, None, None);
+            for holder in [sender, receiver, other] {
+                set_caller(holder);
+                assert_eq!(asset.opt_in(), Ok(()));
+            }
+            assert_eq!(
+                asset.set_jurisdiction(sender, *b"US"),
+                Err(Error::NotManagerId)
             );
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x1; 32]));
-            asset.opt_in();
-            // check if caller account is opted in in accounts_opted_in map
+
+            set_caller(manager);
             assert_eq!(
-                asset.accounts_opted_in.get(&AccountId::from([0x1; 32])),
-                Some(true)
+                asset.set_jurisdiction(sender, *b"us"),
+                Err(Error::InvalidJurisdiction)
             );
-            // check if optIn throws AlreadyOptedIn error
-            assert_eq!(asset.opt_in(), Err(Error::AlreadyOptedIn));
+            assert_eq!(
+                asset.set_tax_rate(*b"US", *b"DE", 10_001),
+                Err(Error::InvalidTaxRate)
+            );
+            assert_eq!(asset.set_jurisdiction(sender, *b"US"), Ok(()));
+            assert_eq!(asset.set_jurisdiction(receiver, *b"DE"), Ok(()));
+            assert_eq!(asset.set_tax_rate(*b"US", *b"DE", 3_000), Ok(()));
+            assert_eq!(asset.jurisdiction_of(sender), Some(*b"US"));
+            assert_eq!(asset.tax_rate(*b"US", *b"DE"), 3_000);
+            assert_eq!(asset.tax_rate(*b"DE", *b"US"), 0);
+            assert_eq!(asset.transfer(sender, 500), Ok(()));
+
+            // 30% withholding on transfers from US to DE
+            set_caller(sender);
+            let events_before = ink::env::test::recorded_events().count();
+            assert_eq!(asset.transfer(receiver, 100), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(70));
+            assert_eq!(asset.balance_of(manager), Ok(530));
+            let events = ink::env::test::recorded_events()
+                .skip(events_before)
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last(),
+                Some(Event::TaxCollected(TaxCollected {
+                    amount: 30,
+                    jurisdiction: ([b'U', b'S'], [b'D', b'E']),
+                    ..
+                }))
+            ));
+
+            // no tax without a rate, or without a jurisdiction
+            set_caller(receiver);
+            assert_eq!(asset.transfer(sender, 10), Ok(()));
+            assert_eq!(asset.balance_of(sender), Ok(410));
+            set_caller(sender);
+            assert_eq!(asset.transfer(other, 10), Ok(()));
+            assert_eq!(asset.balance_of(other), Ok(10));
+
+            // a zero code clears the jurisdiction
+            set_caller(manager);
+            assert_eq!(asset.set_jurisdiction(receiver, [0; 2]), Ok(()));
+            assert_eq!(asset.jurisdiction_of(receiver), None);
+            set_caller(sender);
+            assert_eq!(asset.transfer(receiver, 100), Ok(()));
+            assert_eq!(asset.balance_of(receiver), Ok(160));
         }
 
-        // Test if optOut works
+        // Test if the manager of a mutable_url asset can change the url, with the same
+        // validation as on creation
         #[ink::test]
-        fn opt_out_works() {
-            // set caller
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x0; 32]));
-            let mut asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
+        fn set_url_works_with_mutable_url() {
+            let manager = account(0x1);
+            set_caller(manager);
+            let mut asset = Subsa::new_with_config(AssetConfig {
+                total: 1000,
+                url: "https://old.example.com/asset.json".into(),
+                manager: Some(manager),
+                strict_url: true,
+                mutable_url: true,
+                ..Default::default()
+            })
+            .unwrap();
+            assert!(asset.mutable_url());
+
+            assert_eq!(
+                asset.set_url("http://new.example.com".into()),
+                Err(Error::InvalidUrl)
             );
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x1; 32]));
-            asset.opt_in();
-            asset.opt_out();
-            // check if caller account is opted in in accounts_opted_in map
             assert_eq!(
-                asset.accounts_opted_in.get(&AccountId::from([0x1; 32])),
-                Some(false)
+                asset.set_url("https://new.example.com/asset.json".into()),
+                Ok(())
+            );
+            assert_eq!(asset.url(), "https://new.example.com/asset.json");
+            let events = ink::env::test::recorded_events()
+                .map(|event| decode_event(&event))
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last(),
+                Some(Event::UrlChanged(UrlChanged { old, by, .. }))
+                    if old == "https://old.example.com/asset.json" && *by == manager
+            ));
+
+            set_caller(account(0x2));
+            assert_eq!(
+                asset.set_url("https://other.example.com".into()),
+                Err(Error::NotManagerId)
             );
+
+            // once the manager is renounced, nobody can change the url
+            set_caller(manager);
+            assert_eq!(asset.set_manager(zero_account()), Ok(()));
+            assert_eq!(
+                asset.set_url("https://other.example.com".into()),
+                Err(Error::NotManagerId)
+            );
+            assert_eq!(asset.url(), "https://new.example.com/asset.json");
         }
 
-        // Test if optOut emits OptOut event
+        // Test if the url of an asset created without mutable_url cannot change
         #[ink::test]
-        fn opt_out_emits_opt_out_event() {
-            // set caller
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x0; 32]));
-            let mut asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
+        fn set_url_rejects_immutable_url() {
+            let manager = account(0x1);
+            let mut asset = new_asset(Some(manager), None, None, None);
+            assert!(!asset.mutable_url());
+            set_caller(manager);
+            assert_eq!(
+                asset.set_url("https://new.example.com".into()),
+                Err(Error::ImmutableField)
             );
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x1; 32]));
-            asset.opt_in();
-            asset.opt_out();
-            let events = ink::env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(events.len(), 3);
-            let event = &events[2];
-            assert_eq!(event.topics.len(), 3);
-            // TODO TEST EVENT WITH Event as scale:Decode
+            assert_eq!(asset.url(), "www.test.com");
         }
 
+        // Test if reserve proofs verify the inclusion of each leaf, and nothing else
         #[ink::test]
-        fn opt_out_throws_not_opt_in_when_opt_out_without_opt_in() {
-            // set caller
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x0; 32]));
-            let mut asset = Subsa::new(
-                "Test subsa".into(),
-                "TSSA".into(),
-                1000,
-                10,
-                true,
-                "www.test.com".into(),
-                [0x0; 4],
-                None,
-                None,
-                None,
-                None,
+        fn reserve_proof_works() {
+            let manager = account(0x1);
+            let (bank, custodian, vault) = (account(0x4), account(0x5), account(0x6));
+            let mut asset = new_asset(Some(manager), None, None, None);
+            assert_eq!(
+                asset.verify_reserve_proof(bank, 500, Vec::new()),
+                Err(Error::NoReserveProof)
+            );
+
+            // a tree of three leaves, the last one promoted to the second level
+            let leaves = [
+                reserve_proof_leaf(bank, 500),
+                reserve_proof_leaf(custodian, 300),
+                reserve_proof_leaf(vault, 200),
+            ];
+            let pair = reserve_proof_parent(leaves[0], leaves[1]);
+            let root = reserve_proof_parent(pair, leaves[2]);
+
+            set_caller(account(0x2));
+            assert_eq!(asset.publish_reserve_proof(root), Err(Error::NotManagerId));
+            set_caller(manager);
+            ink::env::test::advance_block::<Environment>();
+            assert_eq!(asset.publish_reserve_proof(root), Ok(()));
+            assert_eq!(asset.reserve_proof(), Some((root, 1)));
+
+            assert_eq!(
+                asset.verify_reserve_proof(bank, 500, vec![leaves[1], leaves[2]]),
+                Ok(true)
+            );
+            assert_eq!(
+                asset.verify_reserve_proof(custodian, 300, vec![leaves[0], leaves[2]]),
+                Ok(true)
+            );
+            assert_eq!(asset.verify_reserve_proof(vault, 200, vec![pair]), Ok(true));
+            assert_eq!(
+                asset.verify_reserve_proof(bank, 501, vec![leaves[1], leaves[2]]),
+                Ok(false)
+            );
+            assert_eq!(
+                asset.verify_reserve_proof(vault, 500, vec![leaves[1], leaves[2]]),
+                Ok(false)
             );
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x1; 32]));
-            assert_eq!(asset.opt_out(), Err(Error::NotOptedIn));
         }
     }
 }
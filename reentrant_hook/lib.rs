@@ -0,0 +1,98 @@
+//! # reentrant_hook
+//!
+//! A malicious transfer hook, attempting to re-enter the subsa asset that calls it.
+//!
+//! Once set as the transfer hook of an asset, every notified movement makes the hook read its
+//! balance on the asset, then try to transfer `amount` of it back out in the middle of the
+//! asset's message. The asset serves the read, but rejects the transfer with
+//! `ReentrancyDetected`; the hook records both outcomes so that they can be checked.
+//!
+//! ## Docs
+//!
+//! Check readme for more information.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::reentrant_hook::{ReentrantHook, ReentrantHookRef};
+
+/// The reentrant_hook smart contract
+#[ink::contract]
+mod reentrant_hook {
+    use ink::codegen::TraitCallBuilder;
+    use ink::env::call::FromAccountId;
+
+    use ssa::{SubsaRef, TransferOperation};
+
+    /// Defines the storage of the hook.
+    #[ink(storage)]
+    pub struct ReentrantHook {
+        // The asset the hook attacks, and the amount it tries to move out of it ↓
+        asset: AccountId,
+        receiver: AccountId,
+        amount: Balance,
+        // Outcomes of the last attack: the balance read, and whether the transfer was rejected ↓
+        read_balance: Option<Balance>,
+        reentry_rejected: bool,
+    }
+
+    impl ReentrantHook {
+        // Creates a new hook attacking `asset`, sending `amount` to `receiver`.
+        // Note: the hook has to be opted in to the asset and funded for the attack to run
+        #[ink(constructor)]
+        pub fn new(asset: AccountId, receiver: AccountId, amount: Balance) -> Self {
+            Self {
+                asset,
+                receiver,
+                amount,
+                read_balance: None,
+                reentry_rejected: false,
+            }
+        }
+
+        /// Returns the balance the last attack read, and whether its transfer was rejected.
+        #[ink(message)]
+        pub fn outcome(&self) -> (Option<Balance>, bool) {
+            (self.read_balance, self.reentry_rejected)
+        }
+    }
+
+    impl ssa::TransferHook for ReentrantHook {
+        #[ink(message)]
+        fn before_transfer(
+            &self,
+            _operation: TransferOperation,
+            _from: AccountId,
+            _to: AccountId,
+            _amount: Balance,
+        ) -> Result<(), u32> {
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn after_transfer(
+            &mut self,
+            _operation: TransferOperation,
+            _from: AccountId,
+            _to: AccountId,
+            _amount: Balance,
+        ) {
+            let mut asset: SubsaRef = FromAccountId::from_account_id(self.asset);
+
+            // read the hook's balance, which the asset allows
+            self.read_balance = asset
+                .call()
+                .balance_of(self.env().account_id())
+                .try_invoke()
+                .ok()
+                .and_then(Result::ok)
+                .and_then(Result::ok);
+
+            // move it out mid-message, which the asset rejects
+            let result = asset
+                .call_mut()
+                .transfer(self.receiver, self.amount)
+                .try_invoke();
+            self.reentry_rejected = matches!(result, Ok(Ok(Err(ssa::Error::ReentrancyDetected))));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! # wnative
+//!
+//! A wrapped native token, keeping its accounting in a subsa asset.
+//!
+//! The wrapper instantiates a subsa asset of which it is both the reserve and the clawback
+//! address, with the largest possible supply. Deposits fund the depositor from the reserve
+//! (opting it in on its first deposit), and withdrawals revoke the units back into the reserve
+//! before paying out the native value, so that the circulating supply of the asset always
+//! matches the native value held for its holders.
+//!
+//! ## Docs
+//!
+//! Check readme for more information.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use self::wnative::{Error, Wnative, WnativeRef};
+
+/// The wnative smart contract
+#[ink::contract]
+mod wnative {
+    use ink::codegen::EmitEvent;
+    use ink::prelude::{string::String, vec::Vec};
+
+    use scale::{Decode, Encode};
+    use ssa::{AssetConfig, SubsaRef};
+
+    /// Defines the storage of the wrapper.
+    #[ink(storage)]
+    pub struct Wnative {
+        // The subsa asset keeping the accounting ↓
+        asset: SubsaRef,
+    }
+
+    // Errors
+
+    #[derive(Debug, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// The underlying asset rejected the call.
+        Asset(ssa::Error),
+        ZeroAmount,
+        Frozen,
+        NativeTransferFailed,
+    }
+
+    impl From<ssa::Error> for Error {
+        fn from(error: ssa::Error) -> Self {
+            Self::Asset(error)
+        }
+    }
+
+    // Events
+
+    /// Event emitted when native value is wrapped.
+    #[ink(event)]
+    pub struct Deposit {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when units are unwrapped back into native value.
+    #[ink(event)]
+    pub struct Withdrawal {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    impl Wnative {
+        // Creates a new wrapper, instantiating its asset from the uploaded subsa code.
+        // Note: a `freeze` address makes the asset freezable, and frozen holders cannot withdraw
+        #[ink(constructor)]
+        pub fn new(
+            subsa_code_hash: Hash,
+            asset_name: String,
+            unit_name: String,
+            decimals: u32,
+            freeze: Option<AccountId>,
+        ) -> Result<Self, Error> {
+            // the wrapper instantiates the asset, so it is its creator and reserve
+            let asset = SubsaRef::new_with_config(AssetConfig {
+                asset_name,
+                unit_name,
+                total: Balance::MAX,
+                decimals,
+                default_frozen: freeze.is_some(),
+                freeze,
+                clawback: Some(Self::env().account_id()),
+                ..Default::default()
+            })
+            .code_hash(subsa_code_hash)
+            .endowment(0)
+            .salt_bytes(Vec::new())
+            .instantiate()?;
+
+            Ok(Self { asset })
+        }
+
+        /// Wrap the transferred value into as many units of the asset
+        // Note: opts the caller in to the asset on its first deposit
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+
+            if self.asset.is_opted_in(caller)? {
+                self.asset.transfer(caller, amount)?;
+            } else {
+                self.asset.opt_in_and_fund(caller, amount)?;
+            }
+
+            // emit deposit event
+            // Note: named in full, as the subsa dependency implements EmitEvent for the same
+            // environment
+            EmitEvent::<Wnative>::emit_event(
+                self.env(),
+                Deposit {
+                    account: caller,
+                    amount,
+                },
+            );
+
+            Ok(())
+        }
+
+        /// Unwrap `amount` units of the asset back into native value
+        // Note: frozen holders cannot withdraw
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+
+            // check if caller is not frozen in the asset
+            if self.asset.is_frozen(caller)? {
+                return Err(Error::Frozen);
+            }
+
+            // take the units back into the reserve before paying out
+            let wrapper = self.env().account_id();
+            self.asset.revoke_asset(wrapper, caller, amount)?;
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::NativeTransferFailed)?;
+
+            // emit withdrawal event
+            EmitEvent::<Wnative>::emit_event(
+                self.env(),
+                Withdrawal {
+                    account: caller,
+                    amount,
+                },
+            );
+
+            Ok(())
+        }
+
+        /// Returns the underlying asset.
+        #[ink(message)]
+        pub fn asset_id(&self) -> AccountId {
+            ink::ToAccountId::to_account_id(&self.asset)
+        }
+
+        /// Returns the wrapped supply, i.e. the units held outside of the reserve.
+        #[ink(message)]
+        pub fn total_supply(&self) -> Balance {
+            self.asset.circulating_supply()
+        }
+
+        /// Returns the balance of `account`.
+        /// Note: if the account has not opted in to the asset, NotOptedIn is returned.
+        #[ink(message)]
+        pub fn balance_of(&self, account: AccountId) -> Result<Balance, Error> {
+            Ok(self.asset.balance_of(account)?)
+        }
+
+        /// Returns whether `account` is frozen in the asset.
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> Result<bool, Error> {
+            Ok(self.asset.is_frozen(account)?)
+        }
+
+        /// Returns the asset name.
+        #[ink(message)]
+        pub fn asset_name(&self) -> String {
+            self.asset.asset_name()
+        }
+
+        /// Returns the unit name.
+        #[ink(message)]
+        pub fn unit_name(&self) -> String {
+            self.asset.unit_name()
+        }
+
+        /// Returns the decimals.
+        #[ink(message)]
+        pub fn decimals(&self) -> u32 {
+            self.asset.decimals()
+        }
+    }
+}